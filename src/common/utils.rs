@@ -1,10 +1,12 @@
 use std::str::FromStr;
 
+use convert_case::{Case, Casing};
 use proc_macro2::TokenStream;
 use quote::ToTokens;
+use syn::ext::IdentExt;
 use syn::{
-    meta::ParseNestedMeta, parse2, punctuated::Punctuated, token::Comma, Attribute, Data,
-    DeriveInput, Field, Fields, File, Ident, Meta, PathArguments, Type,
+    parse2, punctuated::Punctuated, token, token::Comma, Attribute, Data, DeriveInput, Expr, Field,
+    Fields, File, Ident, LitStr, Meta, PathArguments, Type,
 };
 
 pub enum Additional {
@@ -49,6 +51,14 @@ pub fn is_custom_enum_attr(attr: &Attribute) -> bool {
     }
 }
 
+// Helper function to check if an attribute is #[json]
+pub fn is_json_attr(attr: &Attribute) -> bool {
+    match attr.meta {
+        Meta::Path(ref path) => path.is_ident("json"),
+        _ => false,
+    }
+}
+
 pub fn is_key_attr(attr: &Attribute) -> bool {
     match attr.meta {
         Meta::Path(ref path) => path.is_ident("key"),
@@ -56,88 +66,334 @@ pub fn is_key_attr(attr: &Attribute) -> bool {
     }
 }
 
-fn extract_inner_string(input: &str) -> Option<String> {
-    // Remove leading "= " and surrounding quotes
-    if let Some(stripped) = input
-        .trim()
-        .strip_prefix("=")?
-        .trim()
-        .strip_prefix("\"")
-        .and_then(|s| s.strip_suffix("\""))
-    {
-        return Some(stripped.to_string());
+// Helper function to check if an attribute is #[db_default]
+pub fn is_db_default_attr(attr: &Attribute) -> bool {
+    match attr.meta {
+        Meta::Path(ref path) => path.is_ident("db_default"),
+        _ => false,
     }
-    None
 }
 
-#[test]
-fn extract_inner_string_1() {
-    let result = extract_inner_string("= \"foo\"").expect("couldnt get inner");
-    assert_eq!(result, "foo");
+// Helper function to check if an attribute is #[soft_delete]
+pub fn is_soft_delete_attr(attr: &Attribute) -> bool {
+    match attr.meta {
+        Meta::Path(ref path) => path.is_ident("soft_delete"),
+        _ => false,
+    }
 }
-#[test]
-fn extract_inner_string_2() {
-    let result = extract_inner_string("=\"foo\"").expect("couldnt get inner");
-    assert_eq!(result, "foo");
+
+// Helper function to check if an attribute is #[readonly]
+pub fn is_readonly_attr(attr: &Attribute) -> bool {
+    match attr.meta {
+        Meta::Path(ref path) => path.is_ident("readonly"),
+        _ => false,
+    }
 }
-#[test]
-fn extract_inner_string_3() {
-    let result = extract_inner_string(" =\"foo\"").expect("couldnt get inner");
-    assert_eq!(result, "foo");
+
+// Helper function to check if an attribute is #[version]
+pub fn is_version_attr(attr: &Attribute) -> bool {
+    match attr.meta {
+        Meta::Path(ref path) => path.is_ident("version"),
+        _ => false,
+    }
 }
 
-pub fn get_table_name(input: &DeriveInput) -> String {
-    let struct_name = &input.ident;
-    let mut table_name = struct_name.to_string().to_lowercase();
+// Helper function to check if an attribute is #[created_at]
+pub fn is_created_at_attr(attr: &Attribute) -> bool {
+    match attr.meta {
+        Meta::Path(ref path) => path.is_ident("created_at"),
+        _ => false,
+    }
+}
 
-    for attr in &input.attrs {
+// Helper function to check if an attribute is #[updated_at]
+pub fn is_updated_at_attr(attr: &Attribute) -> bool {
+    match attr.meta {
+        Meta::Path(ref path) => path.is_ident("updated_at"),
+        _ => false,
+    }
+}
+
+// Finds `key = "value"` inside any `#[dbset(...)]` attribute, skipping over the other entries
+pub fn get_dbset_attr_value(attrs: &[Attribute], key: &str) -> Option<LitStr> {
+    let mut value = None;
+    for attr in attrs {
         if let Meta::List(meta) = attr.meta.clone() {
             if meta.path.is_ident("dbset") {
                 let _ = meta.parse_nested_meta(|meta| {
-                    if meta.path.is_ident("table_name") {
-                        // if let Lit::Str(lit_str) = meta {}
-                        if let ParseNestedMeta { input, .. } = meta {
-                            let instring = input.to_string();
-                            let parsed_inn_string_maybe = extract_inner_string(&instring);
-                            if let Some(parsed_inn_string) = parsed_inn_string_maybe {
-                                table_name = parsed_inn_string;
+                    if meta.input.peek(token::Eq) {
+                        let expr: Expr = meta.value()?.parse()?;
+                        if meta.path.is_ident(key) {
+                            if let Expr::Lit(syn::ExprLit {
+                                lit: syn::Lit::Str(lit_str),
+                                ..
+                            }) = expr
+                            {
+                                value = Some(lit_str);
                             }
                         }
+                    } else if meta.input.peek(token::Paren) {
+                        meta.input.parse::<proc_macro2::Group>()?;
                     }
                     Ok(())
                 });
             }
         }
     }
+    value
+}
 
-    table_name
+// Checks for a bare flag such as `tracked` inside any `#[dbset(...)]` attribute
+pub fn has_dbset_flag(attrs: &[Attribute], flag: &str) -> bool {
+    let mut found = false;
+    for attr in attrs {
+        if let Meta::List(meta) = attr.meta.clone() {
+            if meta.path.is_ident("dbset") {
+                let _ = meta.parse_nested_meta(|meta| {
+                    if meta.input.peek(token::Eq) {
+                        meta.value()?.parse::<Expr>()?;
+                    } else if meta.input.peek(token::Paren) {
+                        meta.input.parse::<proc_macro2::Group>()?;
+                    } else if meta.path.is_ident(flag) {
+                        found = true;
+                    }
+                    Ok(())
+                });
+            }
+        }
+    }
+    found
 }
 
-pub fn get_dbset_name(input: &DeriveInput) -> Ident {
-    let struct_name = &input.ident;
-    let mut set_name = format!("{struct_name}DbSet");
+// Expression from #[dbset(default = "...")], used by inserts when the field is not set
+pub fn get_field_default(attrs: &[Attribute]) -> Option<Expr> {
+    get_dbset_attr_value(attrs, "default").map(|lit_str| {
+        lit_str
+            .parse::<Expr>()
+            .expect("dbset default must be a valid expression")
+    })
+}
 
-    for attr in &input.attrs {
+// Column for a field, from `#[dbset(column = "...")]` on the field or the struct's
+// `#[dbset(rename_all = "...")]`, falling back to the field name (without any `r#`)
+pub fn get_column_name(input: &DeriveInput, field_name: &Ident) -> String {
+    let field = get_struct_fields(input)
+        .iter()
+        .find(|field| field.ident.as_ref() == Some(field_name))
+        .unwrap_or_else(|| panic!("{field_name} is not a field on {}", input.ident));
+
+    if let Some(column) = get_dbset_attr_value(&field.attrs, "column") {
+        return column.value();
+    }
+
+    rename_column(input, field_name.unraw().to_string())
+}
+
+// Each set has a hidden `{FIELD}_COLUMN` const with the (unquoted) column of a field, so
+// relations on other sets naming a field that doesn't exist fail to compile
+pub fn get_column_const_name(field_name: &Ident) -> Ident {
+    quote::format_ident!(
+        "{}_COLUMN",
+        field_name.unraw().to_string().to_case(Case::UpperSnake)
+    )
+}
+
+// Applies the struct's `#[dbset(rename_all = "...")]` to a snake_case name
+fn rename_column(input: &DeriveInput, name: String) -> String {
+    match get_dbset_attr_value(&input.attrs, "rename_all") {
+        Some(rename_all) => match rename_all.value().as_str() {
+            "lowercase" => name.to_lowercase(),
+            "UPPERCASE" => name.to_uppercase(),
+            "camelCase" => name.from_case(Case::Snake).to_case(Case::Camel),
+            "PascalCase" => name.from_case(Case::Snake).to_case(Case::Pascal),
+            "snake_case" => name,
+            "SCREAMING_SNAKE_CASE" => name.from_case(Case::Snake).to_case(Case::UpperSnake),
+            "kebab-case" => name.from_case(Case::Snake).to_case(Case::Kebab),
+            "SCREAMING-KEBAB-CASE" => name.from_case(Case::Snake).to_case(Case::UpperKebab),
+            other => panic!(
+                "unknown rename_all \"{other}\" on {}, expected one of lowercase, UPPERCASE, \
+                 camelCase, PascalCase, snake_case, SCREAMING_SNAKE_CASE, kebab-case or SCREAMING-KEBAB-CASE",
+                input.ident
+            ),
+        },
+        None => name,
+    }
+}
+
+// A field marked `#[dbset(flatten(street, city))]` embeds a struct whose listed fields each have
+// their own column, `{field}_{sub_field}` (after any rename_all) or `{prefix}{sub_field}` when
+// a `prefix = "..."` is given
+pub struct FlattenedField {
+    pub fields: Vec<Ident>,
+    pub prefix: Option<String>,
+}
+
+pub fn get_flattened_field(attrs: &[Attribute]) -> Option<FlattenedField> {
+    let mut flattened = None;
+    for attr in attrs {
         if let Meta::List(meta) = attr.meta.clone() {
             if meta.path.is_ident("dbset") {
-                let _ = meta.parse_nested_meta(|meta| {
-                    if meta.path.is_ident("set_name") {
-                        // if let Lit::Str(lit_str) = meta {}
-                        if let ParseNestedMeta { input, .. } = meta {
-                            let instring = input.to_string();
-                            let parsed_inn_string_maybe = extract_inner_string(&instring);
-                            if let Some(parsed_inn_string) = parsed_inn_string_maybe {
-                                set_name = parsed_inn_string;
+                meta.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("flatten") {
+                        let mut fields = Vec::new();
+                        let mut prefix = None;
+                        meta.parse_nested_meta(|meta| {
+                            if meta.path.is_ident("prefix") {
+                                prefix = Some(meta.value()?.parse::<LitStr>()?.value());
+                            } else {
+                                fields.push(meta.path.require_ident()?.clone());
                             }
-                        }
+                            Ok(())
+                        })?;
+                        flattened = Some(FlattenedField { fields, prefix });
+                    } else if meta.input.peek(token::Eq) {
+                        meta.value()?.parse::<Expr>()?;
+                    } else if meta.input.peek(token::Paren) {
+                        meta.input.parse::<proc_macro2::Group>()?;
                     }
                     Ok(())
-                });
+                })
+                .expect("could not parse dbset attribute");
             }
         }
     }
+    flattened
+}
+
+pub fn is_flattened_field(attrs: &[Attribute]) -> bool {
+    get_flattened_field(attrs).is_some()
+}
+
+// Every column behind a field, each with the embedded field it holds for flattened fields
+pub fn get_field_columns(input: &DeriveInput, field_name: &Ident) -> Vec<(Option<Ident>, String)> {
+    let field = get_fields(input)
+        .into_iter()
+        .find(|field| field.ident.as_ref() == Some(field_name))
+        .unwrap_or_else(|| panic!("{field_name} is not a field on {}", input.ident));
+
+    match get_flattened_field(&field.attrs) {
+        Some(_) if get_inner_option_type(&field.ty).is_some() => panic!(
+            "flattened field {field_name} on {} can not be an Option",
+            input.ident
+        ),
+        Some(flattened) => flattened
+            .fields
+            .into_iter()
+            .map(|sub_field| {
+                let sub_field_name = sub_field.unraw().to_string();
+                let column = match &flattened.prefix {
+                    Some(prefix) => format!("{prefix}{sub_field_name}"),
+                    None => {
+                        rename_column(input, format!("{}_{sub_field_name}", field_name.unraw()))
+                    }
+                };
+                (Some(sub_field), column)
+            })
+            .collect(),
+        None => vec![(None, get_column_name(input, field_name))],
+    }
+}
+
+// A flattened field is always inserted from its embedded struct, so it can't also fall back to
+// a `#[dbset(default = "...")]` or a `#[db_default]`
+pub fn check_flattened_fields(input: &DeriveInput) -> Result<(), syn::Error> {
+    for field in get_fields(input) {
+        let has_default =
+            get_field_default(&field.attrs).is_some() || field.attrs.iter().any(is_db_default_attr);
+        if is_flattened_field(&field.attrs) && has_default {
+            return Err(syn::Error::new_spanned(
+                field,
+                "a flattened field can not have a default or #[db_default]",
+            ));
+        }
+    }
+    Ok(())
+}
+
+// How a flattened column is named in `query!` records, `{field}_{sub_field}`
+pub fn get_flattened_column_alias(field_name: &Ident, sub_field: &Ident) -> Ident {
+    quote::format_ident!("{}_{}", field_name.unraw(), sub_field.unraw())
+}
+
+// An identifier as written in SQL. Always quoted, so reserved words like `order` and
+// mixed case names are kept as they are.
+pub fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+// The field's column, ready to put in a query
+pub fn get_column_sql(input: &DeriveInput, field_name: &Ident) -> String {
+    quote_identifier(&get_column_name(input, field_name))
+}
+
+// The table as written in SQL: `#[dbset(table_name = "...")]` (the lowercased struct name by default)
+// with the optional `#[dbset(schema = "...")]` in front, each part quoted. Without a schema,
+// a `table_name` like "billing.invoices" is still read as schema-qualified.
+pub fn get_table_name(input: &DeriveInput) -> String {
+    let table_name = get_dbset_attr_value(&input.attrs, "table_name")
+        .map(|table_name| table_name.value())
+        .unwrap_or_else(|| input.ident.to_string().to_lowercase());
+
+    match get_dbset_attr_value(&input.attrs, "schema") {
+        Some(schema) => format!(
+            "{}.{}",
+            quote_identifier(&schema.value()),
+            quote_identifier(&table_name)
+        ),
+        None => quote_table_path(&table_name),
+    }
+}
+
+// A possibly schema-qualified table name such as "billing.invoices", with each part quoted
+pub fn quote_table_path(name: &str) -> String {
+    name.split('.')
+        .map(quote_identifier)
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+pub fn get_dbset_name(input: &DeriveInput) -> Ident {
+    get_set_name(
+        &input.ident,
+        get_dbset_attr_value(&input.attrs, "set_name").as_ref(),
+    )
+}
+
+// `{Struct}DbSet` unless renamed with `set_name = "..."`, for this struct or a related one
+pub fn get_set_name(struct_name: &Ident, set_name: Option<&LitStr>) -> Ident {
+    match set_name {
+        Some(set_name) => Ident::new(&set_name.value(), struct_name.span()),
+        None => quote::format_ident!("{}DbSet", struct_name),
+    }
+}
 
-    Ident::new(&set_name, struct_name.span())
+// `Post` gives `posts`, `Category` gives `categories` and `Address` gives `addresses`
+pub fn get_plural_snake_name(related: &Ident) -> Ident {
+    let snake = related
+        .to_string()
+        .from_case(Case::Pascal)
+        .to_case(Case::Snake);
+    pluralize(&snake)
+}
+
+// `team` gives `teams`, `category` gives `categories` and `address` gives `addresses`
+pub fn pluralize(snake: &str) -> Ident {
+    let ends_in_consonant_y = snake.strip_suffix('y').is_some_and(|stem| {
+        stem.chars()
+            .last()
+            .is_some_and(|last| !"aeiou".contains(last))
+    });
+    let plural = if ends_in_consonant_y {
+        format!("{}ies", &snake[..snake.len() - 1])
+    } else if ["s", "x", "z", "ch", "sh"]
+        .iter()
+        .any(|suffix| snake.ends_with(suffix))
+    {
+        format!("{snake}es")
+    } else {
+        format!("{snake}s")
+    };
+    quote::format_ident!("{}", plural)
 }
 
 pub fn get_inner_option_type(ty: &Type) -> Option<&Type> {
@@ -156,11 +412,37 @@ pub fn get_inner_option_type(ty: &Type) -> Option<&Type> {
     None
 }
 
+// The type-state generic tracking whether a field was set, named after the field in PascalCase.
+// A field such as `address: Address` would shadow its own type, so it becomes `AddressField`.
+pub fn get_type_state_name(input: &DeriveInput, field_name: &Ident) -> Ident {
+    let name = field_name
+        .unraw()
+        .to_string()
+        .from_case(Case::Snake)
+        .to_case(Case::Pascal);
+    let is_type_name = get_fields(input)
+        .iter()
+        .any(|field| type_mentions(field.ty.to_token_stream(), &name));
+    if is_type_name {
+        quote::format_ident!("{name}Field")
+    } else {
+        quote::format_ident!("{name}")
+    }
+}
+
+fn type_mentions(tokens: TokenStream, name: &str) -> bool {
+    tokens.into_iter().any(|token| match token {
+        proc_macro2::TokenTree::Ident(ident) => ident == name,
+        proc_macro2::TokenTree::Group(group) => type_mentions(group.stream(), name),
+        _ => false,
+    })
+}
+
 pub fn get_struct_name(input: &DeriveInput) -> &Ident {
     &input.ident
 }
 
-pub fn get_fields(input: &DeriveInput) -> &Punctuated<Field, Comma> {
+fn get_struct_fields(input: &DeriveInput) -> &Punctuated<Field, Comma> {
     if let Data::Struct(data) = &input.data {
         match &data.fields {
             Fields::Named(fields) => &fields.named,
@@ -171,6 +453,23 @@ pub fn get_fields(input: &DeriveInput) -> &Punctuated<Field, Comma> {
     }
 }
 
+// The fields backed by a column, leaving out any marked `#[dbset(skip)]`
+pub fn get_fields(input: &DeriveInput) -> Vec<&Field> {
+    get_struct_fields(input)
+        .iter()
+        .filter(|field| !has_dbset_flag(&field.attrs, "skip"))
+        .collect()
+}
+
+// Fields marked `#[dbset(skip)]`, which are not columns and start out as `Default::default()`
+pub fn get_skipped_field_names(input: &DeriveInput) -> Vec<&Ident> {
+    get_struct_fields(input)
+        .iter()
+        .filter(|field| has_dbset_flag(&field.attrs, "skip"))
+        .filter_map(|field| field.ident.as_ref())
+        .collect()
+}
+
 pub fn get_field_names(input: &DeriveInput) -> Vec<&Ident> {
     let fields = get_fields(input);
     let mut field_names = Vec::new();
@@ -201,6 +500,356 @@ pub fn get_auto_fields(input: &DeriveInput) -> Vec<(&Ident, &Type, &Vec<Attribut
     auto_fields
 }
 
+pub fn get_db_default_fields(input: &DeriveInput) -> Vec<(&Ident, &Type, &Vec<Attribute>)> {
+    let fields = get_fields(input);
+    let mut db_default_fields = Vec::new();
+
+    for field in fields {
+        let field_name_maybe = field.ident.as_ref();
+        if let Some(field_name) = field_name_maybe {
+            let field_type = &field.ty;
+            let is_db_default = field.attrs.iter().any(is_db_default_attr);
+
+            if is_db_default {
+                db_default_fields.push((field_name, field_type, &field.attrs));
+            }
+        }
+    }
+    db_default_fields
+}
+
+// Fields marked #[created_at] or #[updated_at], these are always written as now()
+pub fn get_timestamp_fields(input: &DeriveInput) -> Vec<(&Ident, &Type, &Vec<Attribute>)> {
+    let fields = get_fields(input);
+    let mut timestamp_fields = Vec::new();
+
+    for field in fields {
+        let field_name_maybe = field.ident.as_ref();
+        if let Some(field_name) = field_name_maybe {
+            let field_type = &field.ty;
+            let is_timestamp = field
+                .attrs
+                .iter()
+                .any(|attr| is_created_at_attr(attr) || is_updated_at_attr(attr));
+
+            if is_timestamp {
+                timestamp_fields.push((field_name, field_type, &field.attrs));
+            }
+        }
+    }
+    timestamp_fields
+}
+
+pub fn get_default_fields(input: &DeriveInput) -> Vec<(&Ident, &Type, Expr)> {
+    let fields = get_fields(input);
+    let mut default_fields = Vec::new();
+
+    for field in fields {
+        let field_name_maybe = field.ident.as_ref();
+        if let Some(field_name) = field_name_maybe {
+            let field_type = &field.ty;
+
+            if let Some(default) = get_field_default(&field.attrs) {
+                default_fields.push((field_name, field_type, default));
+            }
+        }
+    }
+    default_fields
+}
+
+// Whether a `#[dbset(default = "...")]` is a bare path to a function, like `Uuid::new_v4`,
+// rather than a value. Only snake_case names are functions, so constants like `i32::MAX` and
+// unit variants like `UserStatus::Unverified` are used as they are.
+pub fn is_function_path(default: &Expr) -> bool {
+    match default {
+        Expr::Path(expr_path) => expr_path.path.segments.last().is_some_and(|segment| {
+            let name = segment.ident.unraw().to_string();
+            name.starts_with(|first: char| first.is_ascii_lowercase() || first == '_')
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+        }),
+        _ => false,
+    }
+}
+
+// The #[version] column used for optimistic concurrency, only the first one is used
+pub fn get_version_field(input: &DeriveInput) -> Option<(&Ident, &Type)> {
+    get_fields(input).iter().find_map(|field| {
+        let field_name = field.ident.as_ref()?;
+        if field.attrs.iter().any(is_version_attr) {
+            Some((field_name, &field.ty))
+        } else {
+            None
+        }
+    })
+}
+
+// The #[soft_delete] timestamp column, deletes set it to now() instead of removing the row
+pub fn get_soft_delete_field(input: &DeriveInput) -> Option<(&Ident, &Type)> {
+    get_fields(input).iter().find_map(|field| {
+        let field_name = field.ident.as_ref()?;
+        if field.attrs.iter().any(is_soft_delete_attr) {
+            Some((field_name, &field.ty))
+        } else {
+            None
+        }
+    })
+}
+
+// Matches rows on whether they are soft deleted, `$n` is the builder's `deleted_filter`:
+// Some(false) for live rows only (the default), Some(true) for deleted rows only, None for both
+pub fn get_soft_delete_where_clause(soft_delete_column: &str, index: usize) -> String {
+    format!("(({soft_delete_column} IS NOT NULL) = ${index} OR ${index} IS NULL)")
+}
+
+// A struct level `#[relation(belongs_to = "Team", foreign_key = "team_id")]`,
+// `references` is the key field on the related set and defaults to `id`.
+// `set_name` matches a related struct with a `#[dbset(set_name = "...")]`.
+pub struct BelongsToRelation<'a> {
+    pub related: Ident,
+    pub related_dbset: Ident,
+    pub name: Ident,
+    pub foreign_key: &'a Ident,
+    pub foreign_key_type: &'a Type,
+    pub references: Ident,
+}
+
+// Reads the `key = "value"` entries of each `#[relation(...)]` attribute
+fn get_relation_attr_values(attrs: &[Attribute]) -> Vec<Vec<(Ident, LitStr)>> {
+    attrs
+        .iter()
+        .filter_map(|attr| match &attr.meta {
+            Meta::List(meta) if meta.path.is_ident("relation") => {
+                let mut values = Vec::new();
+                meta.parse_nested_meta(|meta| {
+                    let key = meta.path.require_ident()?.clone();
+                    let value: LitStr = meta.value()?.parse()?;
+                    values.push((key, value));
+                    Ok(())
+                })
+                .expect("relation entries must be written as key = \"value\"");
+                Some(values)
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+pub fn get_belongs_to_relations(input: &DeriveInput) -> Vec<BelongsToRelation<'_>> {
+    let struct_name = get_struct_name(input);
+
+    get_relation_attr_values(&input.attrs)
+        .into_iter()
+        .filter_map(|values| {
+            let get_value = |key: &str| {
+                values
+                    .iter()
+                    .find(|(value_key, _)| value_key == key)
+                    .map(|(_, value)| Ident::new(&value.value(), value.span()))
+            };
+
+            let related = get_value("belongs_to")?;
+            let foreign_key_name = get_value("foreign_key").unwrap_or_else(|| {
+                panic!("belongs_to relation to {related} on {struct_name} needs a foreign_key")
+            });
+            let (foreign_key, foreign_key_type) = get_fields(input)
+                .iter()
+                .find_map(|field| {
+                    let field_name = field.ident.as_ref()?;
+                    (*field_name == foreign_key_name).then_some((field_name, &field.ty))
+                })
+                .unwrap_or_else(|| {
+                    panic!("foreign_key {foreign_key_name} is not a field on {struct_name}")
+                });
+
+            let set_name = values
+                .iter()
+                .find(|(value_key, _)| value_key == "set_name")
+                .map(|(_, value)| value);
+            // `team_id` gives `team()`, falling back to the related struct name for other
+            // foreign keys, unless given a `name = "..."`
+            let name = get_value("name").unwrap_or_else(|| {
+                match foreign_key_name.to_string().strip_suffix("_id") {
+                    Some(name) if !name.is_empty() => quote::format_ident!("{}", name),
+                    _ => quote::format_ident!(
+                        "{}",
+                        related
+                            .to_string()
+                            .from_case(Case::Pascal)
+                            .to_case(Case::Snake)
+                    ),
+                }
+            });
+            Some(BelongsToRelation {
+                related_dbset: get_set_name(&related, set_name),
+                related,
+                name,
+                foreign_key,
+                foreign_key_type,
+                references: get_value("references")
+                    .unwrap_or_else(|| Ident::new("id", proc_macro2::Span::call_site())),
+            })
+        })
+        .collect()
+}
+
+// Reads each struct level `#[dbset(kind(Related, key = "value", ...))]`, such as `has_many`
+fn get_dbset_relation_entries(
+    input: &DeriveInput,
+    kind: &str,
+) -> Vec<(Ident, Vec<(Ident, LitStr)>)> {
+    let struct_name = get_struct_name(input);
+    let mut entries = Vec::new();
+
+    for attr in &input.attrs {
+        if let Meta::List(meta) = attr.meta.clone() {
+            if meta.path.is_ident("dbset") {
+                meta.parse_nested_meta(|meta| {
+                    if meta.path.is_ident(kind) {
+                        let mut related = None;
+                        let mut values = Vec::new();
+                        meta.parse_nested_meta(|meta| {
+                            if meta.input.peek(token::Eq) {
+                                let key = meta.path.require_ident()?.clone();
+                                let value: LitStr = meta.value()?.parse()?;
+                                values.push((key, value));
+                            } else {
+                                related = Some(meta.path.require_ident()?.clone());
+                            }
+                            Ok(())
+                        })?;
+
+                        let related = related.unwrap_or_else(|| {
+                            panic!("{kind} on {struct_name} needs the related struct")
+                        });
+                        entries.push((related, values));
+                    } else if meta.input.peek(token::Eq) {
+                        meta.value()?.parse::<Expr>()?;
+                    } else if meta.input.peek(token::Paren) {
+                        meta.input.parse::<proc_macro2::Group>()?;
+                    }
+                    Ok(())
+                })
+                .expect("could not parse dbset attribute");
+            }
+        }
+    }
+    entries
+}
+
+// Looks up a required `key = "value"` of a relation
+fn get_relation_value<'a>(
+    values: &'a [(Ident, LitStr)],
+    key: &str,
+    kind: &str,
+    related: &Ident,
+    struct_name: &Ident,
+) -> &'a LitStr {
+    values
+        .iter()
+        .find(|(value_key, _)| value_key == key)
+        .map(|(_, value)| value)
+        .unwrap_or_else(|| panic!("{kind} relation to {related} on {struct_name} needs a {key}"))
+}
+
+// As `get_relation_value`, for values naming a field
+fn get_relation_ident(
+    values: &[(Ident, LitStr)],
+    key: &str,
+    kind: &str,
+    related: &Ident,
+    struct_name: &Ident,
+) -> Ident {
+    let value = get_relation_value(values, key, kind, related, struct_name);
+    Ident::new(&value.value(), value.span())
+}
+
+// A struct level `#[dbset(has_many(Post, foreign_key = "author_id"))]`,
+// where `author_id` is the field on the related struct holding this struct's key.
+// The methods are named after the plural of the related struct unless given a `name = "..."`.
+pub struct HasManyRelation {
+    pub related: Ident,
+    pub related_dbset: Ident,
+    pub name: Ident,
+    pub foreign_key: Ident,
+}
+
+// The optional `name` and `set_name` of a has_many or many_to_many relation
+fn get_relation_names(values: &[(Ident, LitStr)], related: &Ident) -> (Ident, Ident) {
+    let get_value = |key: &str| {
+        values
+            .iter()
+            .find(|(value_key, _)| value_key == key)
+            .map(|(_, value)| value)
+    };
+    let name = match get_value("name") {
+        Some(name) => Ident::new(&name.value(), name.span()),
+        None => get_plural_snake_name(related),
+    };
+    (name, get_set_name(related, get_value("set_name")))
+}
+
+pub fn get_has_many_relations(input: &DeriveInput) -> Vec<HasManyRelation> {
+    let struct_name = get_struct_name(input);
+
+    get_dbset_relation_entries(input, "has_many")
+        .into_iter()
+        .map(|(related, values)| {
+            let (name, related_dbset) = get_relation_names(&values, &related);
+            HasManyRelation {
+                foreign_key: get_relation_ident(
+                    &values,
+                    "foreign_key",
+                    "has_many",
+                    &related,
+                    struct_name,
+                ),
+                related,
+                related_dbset,
+                name,
+            }
+        })
+        .collect()
+}
+
+// A struct level `#[dbset(many_to_many(Tag, through = "post_tags", local = "post_id", remote = "tag_id"))]`,
+// where the `through` join table pairs this struct's key in `local` with the related key in `remote`.
+// These name the join table and its columns directly, as it has no struct of its own.
+// As with has_many, `name` and `set_name` rename the methods and the related set.
+pub struct ManyToManyRelation {
+    pub related: Ident,
+    pub related_dbset: Ident,
+    pub name: Ident,
+    pub has_custom_name: bool,
+    pub through: String,
+    pub local: String,
+    pub remote: String,
+}
+
+pub fn get_many_to_many_relations(input: &DeriveInput) -> Vec<ManyToManyRelation> {
+    let struct_name = get_struct_name(input);
+
+    get_dbset_relation_entries(input, "many_to_many")
+        .into_iter()
+        .map(|(related, values)| {
+            let get_value = |key| {
+                get_relation_value(&values, key, "many_to_many", &related, struct_name).value()
+            };
+            let (name, related_dbset) = get_relation_names(&values, &related);
+            ManyToManyRelation {
+                related_dbset,
+                name,
+                has_custom_name: values.iter().any(|(value_key, _)| value_key == "name"),
+                through: get_value("through"),
+                local: get_value("local"),
+                remote: get_value("remote"),
+                related: related.clone(),
+            }
+        })
+        .collect()
+}
+
 pub fn get_key_fields(input: &DeriveInput) -> Vec<(&Ident, &Type)> {
     let fields = get_fields(input);
     let mut key_fields = Vec::new();
@@ -251,29 +900,141 @@ pub fn get_all_fields(input: &DeriveInput) -> Vec<(&Ident, &Type, &Vec<Attribute
     all_fields
 }
 
+// `#[json]` fields are bound through `sqlx::types::Json`, `optional` when `value` is an Option
+pub fn get_json_bind(
+    attrs: &[Attribute],
+    value: proc_macro2::TokenStream,
+    optional: bool,
+) -> proc_macro2::TokenStream {
+    if !attrs.iter().any(is_json_attr) {
+        value
+    } else if optional {
+        quote::quote! { #value.map(sqlx::types::Json) }
+    } else {
+        quote::quote! { sqlx::types::Json(#value) }
+    }
+}
+
+// As `get_json_bind`, for the query! macros. Their type check only knows `serde_json::Value`
+// for JSONB parameters, so the `Json` wrapper is cast with `as _`, which skips it.
+pub fn get_json_arg(
+    attrs: &[Attribute],
+    value: proc_macro2::TokenStream,
+    optional: bool,
+) -> proc_macro2::TokenStream {
+    let bind = get_json_bind(attrs, value, optional);
+    if attrs.iter().any(is_json_attr) {
+        quote::quote! { #bind as _ }
+    } else {
+        bind
+    }
+}
+
+// Postgres cuts column names off at 63 bytes, too short for most `sqlx::types::Json<..>`
+// overrides, so each `#[json]` field gets a type alias to name in its override instead
+pub fn get_json_type_alias(field_name: &Ident) -> Ident {
+    quote::format_ident!(
+        "{}Json",
+        field_name
+            .unraw()
+            .to_string()
+            .from_case(Case::Snake)
+            .to_case(Case::Pascal)
+    )
+}
+
+// The type a `#[json]` field is decoded as, and how to unwrap the `Json` once decoded
+pub fn get_json_decode(field_type: &Type) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
+    match get_inner_option_type(field_type) {
+        Some(inner_type) => (
+            quote::quote! { Option<sqlx::types::Json<#inner_type>> },
+            quote::quote! { .map(|json| json.0) },
+        ),
+        None => (
+            quote::quote! { sqlx::types::Json<#field_type> },
+            quote::quote! { .0 },
+        ),
+    }
+}
+
+// A field as selected for the query! macros, which match output columns to fields by name,
+// so renamed columns are aliased back to the field
+pub fn get_query_field_string(
+    input: &DeriveInput,
+    field_name: &Ident,
+    field_type: &Type,
+    attrs: &[Attribute],
+) -> String {
+    let field_name_string = field_name.unraw().to_string();
+    if is_flattened_field(attrs) {
+        return get_field_columns(input, field_name)
+            .into_iter()
+            .map(|(sub_field, column)| {
+                let alias = get_flattened_column_alias(field_name, &sub_field.unwrap()).to_string();
+                if column == alias {
+                    quote_identifier(&column)
+                } else {
+                    format!(
+                        "{} AS {}",
+                        quote_identifier(&column),
+                        quote_identifier(&alias)
+                    )
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+    }
+    let column = get_column_sql(input, field_name);
+    let is_custom_enum = attrs.iter().any(is_custom_enum_attr);
+    if is_custom_enum {
+        let field_type_string = field_type.to_token_stream().to_string();
+        format!("{column} AS \"{field_name_string}:{field_type_string}\"")
+    } else if attrs.iter().any(is_json_attr) {
+        let json_type_alias = get_json_type_alias(field_name);
+        format!("{column} AS \"{field_name_string}: {json_type_alias}\"")
+    } else if get_column_name(input, field_name) != field_name_string {
+        format!("{column} AS {}", quote_identifier(&field_name_string))
+    } else {
+        column
+    }
+}
+
 pub fn get_query_fields_string(input: &DeriveInput) -> String {
     let mut all_fields = Vec::new();
     let fields = get_fields(input);
     for field in fields {
         let field_name_maybe = field.ident.as_ref();
         if let Some(field_name) = field_name_maybe {
-            let field_type = &field.ty;
-
-            let field_name_string = field_name.to_string();
-            let is_custom_enum = field.attrs.iter().any(is_custom_enum_attr);
-            if is_custom_enum {
-                let field_type_string = field_type.to_token_stream().to_string();
-                let custom_enum_field =
-                    format!("{field_name_string} AS \"{field_name_string}:{field_type_string}\"");
-                all_fields.push(custom_enum_field);
-            } else {
-                all_fields.push(field_name.to_string())
-            }
+            all_fields.push(get_query_field_string(
+                input,
+                field_name,
+                &field.ty,
+                &field.attrs,
+            ));
         }
     }
     all_fields.join(", ")
 }
 
+// Column list for queries built at runtime and decoded through the generated FromRow,
+// so custom enums are not given the `query_as!` type override.
+pub fn get_row_fields_string(input: &DeriveInput) -> String {
+    get_column_names(input)
+        .iter()
+        .map(|column| quote_identifier(column))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// Every column of the table, with flattened fields expanded
+pub fn get_column_names(input: &DeriveInput) -> Vec<String> {
+    get_field_names(input)
+        .into_iter()
+        .flat_map(|field_name| get_field_columns(input, field_name))
+        .map(|(_, column)| column)
+        .collect()
+}
+
 pub fn join_field_names(fields: &[(&Ident, &Type)], separator: &str) -> String {
     fields
         .iter()
@@ -10,26 +10,53 @@ mod modules;
 use modules::dbset;
 use modules::from_row;
 use modules::many_query_builder;
+use modules::relations;
 
 use common::utils::{self};
 
-#[proc_macro_derive(DbSet, attributes(unique, dbset, relation, auto, key, custom_enum))]
+#[proc_macro_derive(
+    DbSet,
+    attributes(
+        unique,
+        dbset,
+        relation,
+        auto,
+        key,
+        custom_enum,
+        db_default,
+        version,
+        created_at,
+        updated_at,
+        readonly,
+        soft_delete,
+        json
+    )
+)]
 pub fn dbset_derive(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
+    if let Err(err) = utils::check_flattened_fields(&input) {
+        return err.to_compile_error().into();
+    }
+    if let Err(err) = update_query_builder::check_update_target(&input) {
+        return err.to_compile_error().into();
+    }
 
     let struct_name = &input.ident;
     let dbset_name = utils::get_dbset_name(&input);
 
     let many_query_builder_impl = many_query_builder::get_query_builder(&input);
+    let subquery_filter_impl = many_query_builder::get_subquery_filter(&input);
     let one_query_builder_impl = one_query_builder::get_query_builder(&input);
     let insert_builder_impl = insert_query_builder::get_insert_query_builder(&input);
     let update_builder_impl = update_query_builder::get_update_query_builder(&input);
+    let many_update_builder_impl = update_query_builder::get_many_update_query_builder(&input);
     let delete_builder_impl = delete_query_builder::get_query_builder(&input);
 
     // println!("{}", pretty_print_tokenstream(insert_builder_impl.clone()));
 
     let from_row_impl = from_row::get_from_row_impl(&input);
     let dbset_impl = dbset::get_dbset_impl(&input);
+    let relations_impl = relations::get_relations_impl(&input);
 
     let module_name = quote::format_ident!(
         "{}_module",
@@ -39,6 +66,35 @@ pub fn dbset_derive(input: TokenStream) -> TokenStream {
             .to_case(Case::Snake)
     );
 
+    let has_update_target = update_query_builder::has_update_target(&input);
+
+    let update_error_export = if has_update_target && utils::get_version_field(&input).is_some() {
+        let update_error_name = update_query_builder::get_update_error_name(&input);
+        quote! { pub use #module_name::#update_error_name; }
+    } else {
+        quote! {}
+    };
+
+    let tracked_export = if has_update_target && utils::has_dbset_flag(&input.attrs, "tracked") {
+        let tracked_struct_name = update_query_builder::get_tracked_struct_name(&input);
+        quote! { pub use #module_name::#tracked_struct_name; }
+    } else {
+        quote! {}
+    };
+
+    let many_query_builder_name =
+        many_query_builder::utils::get_many_query_builder_struct_name(&input);
+    let filtered_many_query_builder_name =
+        many_query_builder::utils::get_filtered_many_query_builder_name(&input);
+
+    let key_alias = match utils::get_key_fields(&input).as_slice() {
+        [(_, key_type)] => {
+            let key_alias_name = dbset::get_dbset_key_alias_name(&input);
+            quote! { pub type #key_alias_name = #key_type; }
+        }
+        _ => quote! {},
+    };
+
     let expanded = quote! {
 
         mod #module_name {
@@ -49,14 +105,23 @@ pub fn dbset_derive(input: TokenStream) -> TokenStream {
 
             #from_row_impl
             #many_query_builder_impl
+            #subquery_filter_impl
             #one_query_builder_impl
             #insert_builder_impl
             #update_builder_impl
+            #many_update_builder_impl
             #delete_builder_impl
             #dbset_impl
+            #relations_impl
         }
 
         pub use #module_name::#dbset_name;
+        pub use #module_name::#many_query_builder_name;
+        pub type #filtered_many_query_builder_name =
+            #module_name::#many_query_builder_name<#module_name::Set>;
+        #key_alias
+        #update_error_export
+        #tracked_export
     };
 
     TokenStream::from(expanded)
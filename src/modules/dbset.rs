@@ -6,16 +6,34 @@ use crate::modules::delete_query_builder::get_delete_builder_struct_name;
 use crate::modules::insert_query_builder::get_insert_builder_struct_name;
 use crate::modules::many_query_builder;
 use crate::modules::one_query_builder::get_one_builder_struct_name;
-use crate::modules::update_query_builder::get_update_builder_struct_name;
+use crate::modules::update_query_builder::{get_update_builder_struct_name, has_update_target};
+// Alias for the type of a single key, so other sets' relations can name it
+pub fn get_dbset_key_alias_name(input: &DeriveInput) -> proc_macro2::Ident {
+    let dbset_name = utils::get_dbset_name(input);
+    quote::format_ident!("{}Key", dbset_name)
+}
+
 pub fn get_dbset_impl(input: &DeriveInput) -> proc_macro2::TokenStream {
     let many_query_builder_struct_name =
         many_query_builder::utils::get_many_query_builder_struct_name(input);
     let one_query_builder_struct_name = get_one_builder_struct_name(input);
     let insert_builder_struct_name = get_insert_builder_struct_name(input);
     let update_builder_struct_name = get_update_builder_struct_name(input);
+    let update = if has_update_target(input) {
+        quote! {
+            pub fn update() -> #update_builder_struct_name {
+                #update_builder_struct_name::new()
+            }
+        }
+    } else {
+        quote! {}
+    };
     let delete_builder_struct_name = get_delete_builder_struct_name(input);
 
     let dbset_name = utils::get_dbset_name(input);
+    let fetch_by_keys = get_fetch_by_keys(input);
+    let fetch_by_column_any = get_fetch_by_column_any(input);
+    let join_helpers = get_join_helpers(input);
 
     quote! {
         pub struct #dbset_name;
@@ -30,12 +48,179 @@ pub fn get_dbset_impl(input: &DeriveInput) -> proc_macro2::TokenStream {
             pub fn insert() -> #insert_builder_struct_name {
                 #insert_builder_struct_name::new()
             }
-            pub fn update() -> #update_builder_struct_name {
-                #update_builder_struct_name::new()
-            }
+            #update
             pub fn delete() -> #delete_builder_struct_name {
                 #delete_builder_struct_name::new()
             }
+
+            #fetch_by_keys
+            #fetch_by_column_any
+            #join_helpers
+        }
+    }
+}
+
+// With a single key, rows can be fetched for a batch of keys in one query, used by relation loaders.
+// Built at runtime like fetch_by_column_any, so the loaders need no entries in the offline query cache.
+fn get_fetch_by_keys(input: &DeriveInput) -> proc_macro2::TokenStream {
+    let key_fields = utils::get_key_fields(input);
+    let [(key_name, key_type)] = key_fields.as_slice() else {
+        return quote! {};
+    };
+
+    let struct_name = utils::get_struct_name(input);
+    let table_name = utils::get_table_name(input);
+    let key_column = utils::get_column_sql(input, key_name);
+    let soft_delete_where = match utils::get_soft_delete_field(input) {
+        Some((soft_delete_name, _)) => format!(
+            " AND {} IS NULL",
+            utils::get_column_sql(input, soft_delete_name)
+        ),
+        None => String::new(),
+    };
+    let row_fields_str = utils::get_row_fields_string(input);
+    let keys_any_query_start =
+        format!("SELECT {row_fields_str} FROM {table_name} WHERE {key_column} = ANY(");
+    let keys_any_query_end = format!("){soft_delete_where}");
+
+    // The keys can also come from a subquery ending in a bind, such as a join table lookup
+    // for many_to_many relations on another set
+    let keys_in_query_start =
+        format!("SELECT {row_fields_str} FROM {table_name} WHERE {key_column} IN (");
+    let keys_in_query_end = format!("){soft_delete_where}");
+
+    quote! {
+        pub async fn fetch_by_keys<'e, E: sqlx::PgExecutor<'e>>(
+            keys: &[#key_type],
+            executor: E,
+        ) -> Result<Vec<#struct_name>, sqlx::Error> {
+            let mut query_builder = sqlx::QueryBuilder::<sqlx::Postgres>::new(#keys_any_query_start);
+            query_builder.push_bind(keys);
+            query_builder.push(#keys_any_query_end);
+            query_builder
+                .build_query_as::<#struct_name>()
+                .fetch_all(executor)
+                .await
+        }
+
+        #[doc(hidden)]
+        pub async fn fetch_by_keys_in<'e, E, T>(
+            select_keys: &'static str,
+            value: T,
+            executor: E,
+        ) -> Result<Vec<#struct_name>, sqlx::Error>
+        where
+            E: sqlx::PgExecutor<'e>,
+            T: for<'q> sqlx::Encode<'q, sqlx::Postgres> + sqlx::Type<sqlx::Postgres> + Send,
+        {
+            let mut query_builder = sqlx::QueryBuilder::<sqlx::Postgres>::new(#keys_in_query_start);
+            query_builder.push(select_keys);
+            query_builder.push_bind(value);
+            query_builder.push(#keys_in_query_end);
+            query_builder
+                .build_query_as::<#struct_name>()
+                .fetch_all(executor)
+                .await
+        }
+    }
+}
+
+// Rows whose `column` matches any of `values`, for has_many loaders on the parent set which only
+// know the foreign key column. Built at runtime so it works for any column and decodes via FromRow.
+// `column` is unquoted, as given by the `{FIELD}_COLUMN` consts.
+fn get_fetch_by_column_any(input: &DeriveInput) -> proc_macro2::TokenStream {
+    let struct_name = utils::get_struct_name(input);
+    let table_name = utils::get_table_name(input);
+    let row_fields_str = utils::get_row_fields_string(input);
+    let query_start = format!("SELECT {row_fields_str} FROM {table_name} WHERE ");
+    let push_soft_delete_where =
+        utils::get_soft_delete_field(input).map(|(soft_delete_name, _)| {
+            let soft_delete_where = format!(
+                " AND {} IS NULL",
+                utils::get_column_sql(input, soft_delete_name)
+            );
+            quote! { query_builder.push(#soft_delete_where); }
+        });
+
+    quote! {
+        #[doc(hidden)]
+        pub async fn fetch_by_column_any<'e, E, T>(
+            column: &'static str,
+            values: Vec<T>,
+            executor: E,
+        ) -> Result<Vec<#struct_name>, sqlx::Error>
+        where
+            E: sqlx::PgExecutor<'e>,
+            T: for<'q> sqlx::Encode<'q, sqlx::Postgres>
+                + sqlx::Type<sqlx::Postgres>
+                + sqlx::postgres::PgHasArrayType
+                + Send,
+        {
+            let mut query_builder = sqlx::QueryBuilder::<sqlx::Postgres>::new(#query_start);
+            query_builder.push(format!("\"{column}\" = ANY("));
+            query_builder.push_bind(values);
+            query_builder.push(")");
+            #push_soft_delete_where
+            query_builder
+                .build_query_as::<#struct_name>()
+                .fetch_all(executor)
+                .await
+        }
+    }
+}
+
+// Pieces for another set to LEFT JOIN this table under `alias`, with the columns
+// named `{alias}__{column}` so they can be decoded with `from_row_prefixed`
+fn get_join_helpers(input: &DeriveInput) -> proc_macro2::TokenStream {
+    let table_name = utils::get_table_name(input);
+    let column_names = utils::get_column_names(input);
+    let column_sqls = column_names
+        .iter()
+        .map(|column_name| utils::quote_identifier(column_name))
+        .collect::<Vec<_>>();
+    // Flattened fields have no single column to relate on
+    let field_names = utils::get_all_fields(input)
+        .into_iter()
+        .filter(|(_, _, attrs)| !utils::is_flattened_field(attrs))
+        .map(|(field_name, _, _)| field_name)
+        .collect::<Vec<_>>();
+    let column_const_names = field_names
+        .iter()
+        .map(|field_name| utils::get_column_const_name(field_name))
+        .collect::<Vec<_>>();
+    let field_column_names = field_names
+        .iter()
+        .map(|field_name| utils::get_column_name(input, field_name))
+        .collect::<Vec<_>>();
+    let soft_delete_condition = match utils::get_soft_delete_field(input) {
+        Some((soft_delete_name, _)) => format!(
+            " AND \"{{alias}}\".{} IS NULL",
+            utils::get_column_sql(input, soft_delete_name)
+        ),
+        None => String::new(),
+    };
+    let left_join =
+        format!(" LEFT JOIN {table_name} AS \"{{alias}}\" ON {{on}}{soft_delete_condition}");
+
+    quote! {
+        #[doc(hidden)]
+        pub fn aliased_row_fields(alias: &str) -> String {
+            [#((#column_sqls, #column_names)),*]
+                .iter()
+                .map(|(column, column_name)| format!("\"{alias}\".{column} AS \"{alias}__{column_name}\""))
+                .collect::<Vec<_>>()
+                .join(", ")
+        }
+
+        // Relations name fields of other sets, these give the (unquoted) column for each
+        #(
+            #[doc(hidden)]
+            pub const #column_const_names: &'static str = #field_column_names;
+        )*
+
+        #[doc(hidden)]
+        pub fn left_join(alias: &str, on: &str) -> String {
+            format!(#left_join, alias = alias, on = on)
         }
     }
 }
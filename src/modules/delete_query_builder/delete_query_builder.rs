@@ -1,23 +1,37 @@
-use convert_case::{Case, Casing};
 use proc_macro2::Ident;
 use quote::quote;
 use syn::DeriveInput;
 
 use crate::common::utils::{
-    get_dbset_name, get_inner_option_type, get_key_fields, get_struct_name, get_table_name,
-    get_unique_fields,
+    get_column_sql, get_dbset_name, get_inner_option_type, get_key_fields, get_query_fields_string,
+    get_soft_delete_field, get_struct_name, get_table_name, get_type_state_name, get_unique_fields,
 };
+use crate::modules::from_row::get_query_as_parts;
 pub fn get_delete_builder_struct_name(input: &DeriveInput) -> Ident {
     let dbset_name = get_dbset_name(input);
     quote::format_ident!("{}DeleteQueryBuilder", dbset_name)
 }
 
 pub fn get_query_builder(input: &DeriveInput) -> proc_macro2::TokenStream {
+    let (query_as, query_as_struct, query_as_map) = get_query_as_parts(input);
     let table_name = get_table_name(input);
     let struct_name = get_struct_name(input);
     let builder_struct_name = get_delete_builder_struct_name(input);
     let key_fields = get_key_fields(input);
     let unique_fields = get_unique_fields(input);
+    let all_fields_str = get_query_fields_string(input);
+    let soft_delete_column = get_soft_delete_field(input)
+        .map(|(soft_delete_name, _)| get_column_sql(input, soft_delete_name));
+
+    // With #[soft_delete] rows are marked as deleted rather than removed
+    let get_delete_query = |where_clause: &str| {
+        match &soft_delete_column {
+        Some(soft_delete_column) => format!(
+            "UPDATE {table_name} SET {soft_delete_column} = now() WHERE {where_clause} AND {soft_delete_column} IS NULL"
+        ),
+        None => format!("DELETE FROM {table_name} WHERE {where_clause}"),
+    }
+    };
 
     let non_nullable_fields = key_fields
         .iter()
@@ -31,13 +45,7 @@ pub fn get_query_builder(input: &DeriveInput) -> proc_macro2::TokenStream {
     let builder_struct_generics = all_required_insert_fields
         .clone()
         .map(|(field_name, _)| {
-            let gen_name_pascal = quote::format_ident!(
-                "{}",
-                field_name
-                    .to_string()
-                    .from_case(Case::Snake)
-                    .to_case(Case::Pascal)
-            );
+            let gen_name_pascal = get_type_state_name(input, field_name);
             quote! {
                 #gen_name_pascal = NotSet,
             }
@@ -63,12 +71,7 @@ pub fn get_query_builder(input: &DeriveInput) -> proc_macro2::TokenStream {
         }]);
 
     let phantom_struct_fields = all_required_insert_fields.clone().map(|(name, _)| {
-        let gen_name_pascal = quote::format_ident!(
-            "{}",
-            name.to_string()
-                .from_case(Case::Snake)
-                .to_case(Case::Pascal)
-        );
+        let gen_name_pascal = get_type_state_name(input, name);
         let ph_name = quote::format_ident!("_{}", name);
         quote! { #ph_name: std::marker::PhantomData::<#gen_name_pascal>, }
     });
@@ -152,7 +155,7 @@ pub fn get_query_builder(input: &DeriveInput) -> proc_macro2::TokenStream {
             let pre_impl_generics_in = all_required_insert_fields.clone().map(|(gen_name, _)|{
                 if gen_name != field_name {
                     if !is_unique_field  {
-                    let gen_name_pascal = quote::format_ident!("{}", gen_name.to_string().from_case(Case::Snake).to_case(Case::Pascal));
+                    let gen_name_pascal = get_type_state_name(input, gen_name);
                         return quote!{ #gen_name_pascal, }
                     } else {
                         return quote!{  }
@@ -166,7 +169,7 @@ pub fn get_query_builder(input: &DeriveInput) -> proc_macro2::TokenStream {
             let generics_in = all_required_insert_fields.clone().map(|(gen_name, _)|{
                 if gen_name != field_name {
                     if !is_unique_field  {
-                    let gen_name_pascal = quote::format_ident!("{}", gen_name.to_string().from_case(Case::Snake).to_case(Case::Pascal));
+                    let gen_name_pascal = get_type_state_name(input, gen_name);
                         return quote!{ #gen_name_pascal, }
                     } else {
                         return quote!{ NotSet, }
@@ -178,7 +181,7 @@ pub fn get_query_builder(input: &DeriveInput) -> proc_macro2::TokenStream {
             let generics_out = all_required_insert_fields.clone().map(|(gen_name, _)|{
                 if gen_name != field_name {
                     if !is_unique_field  {
-                    let gen_name_pascal = quote::format_ident!("{}", gen_name.to_string().from_case(Case::Snake).to_case(Case::Pascal));
+                    let gen_name_pascal = get_type_state_name(input, gen_name);
                         return quote!{ #gen_name_pascal, }
                     } else {
                         return quote!{ NotSet, }
@@ -240,7 +243,9 @@ pub fn get_query_builder(input: &DeriveInput) -> proc_macro2::TokenStream {
     let key_query_builder_fields_where_clause = key_fields
         .iter()
         .enumerate()
-        .map(|(index, (field_name, _))| format!("{} = ${}", field_name, index + 1,))
+        .map(|(index, (field_name, _))| {
+            format!("{} = ${}", get_column_sql(input, field_name), index + 1,)
+        })
         .collect::<Vec<_>>()
         .join(" AND ");
 
@@ -250,7 +255,7 @@ pub fn get_query_builder(input: &DeriveInput) -> proc_macro2::TokenStream {
         .map(|(index, (field_name, _))| {
             format!(
                 "({} = ${} OR ${} is null)",
-                field_name,
+                get_column_sql(input, field_name),
                 index + 1,
                 index + 1,
             )
@@ -263,10 +268,11 @@ pub fn get_query_builder(input: &DeriveInput) -> proc_macro2::TokenStream {
     });
 
     let unique_query_args_2 = unique_query_args.clone();
+    let unique_query_args_3 = unique_query_args.clone();
 
     let unique_fetch_one = if !unique_fields.is_empty() {
-        let query =
-            format!("DELETE FROM {table_name} WHERE {unique_query_builder_fields_where_clause}");
+        let query = get_delete_query(&unique_query_builder_fields_where_clause);
+        let returning_query = format!("{query} RETURNING {all_fields_str}");
 
         quote! {
 
@@ -274,14 +280,27 @@ pub fn get_query_builder(input: &DeriveInput) -> proc_macro2::TokenStream {
                 pub async fn delete<'e, E: sqlx::PgExecutor<'e>>(
                     self,
                     executor: E,
-                ) -> Result<(), sqlx::Error> {
-                    sqlx::query!(
+                ) -> Result<u64, sqlx::Error> {
+                    let result = sqlx::query!(
                         #query,
                         #(#unique_query_args_2)*
                     )
                         .execute(executor)
                         .await?;
-                    Ok(())
+                    Ok(result.rows_affected())
+            }
+                pub async fn delete_returning<'e, E: sqlx::PgExecutor<'e>>(
+                    self,
+                    executor: E,
+                ) -> Result<Option<#struct_name>, sqlx::Error> {
+                    #query_as(
+                        #query_as_struct
+                        #returning_query,
+                        #(#unique_query_args_3)*
+                    )
+                        #query_as_map
+                        .fetch_optional(executor)
+                        .await
             }
         }
         }
@@ -293,24 +312,39 @@ pub fn get_query_builder(input: &DeriveInput) -> proc_macro2::TokenStream {
         quote! { self.#name, }
     });
     let key_query_args_2 = key_query_args.clone();
+    let key_query_args_3 = key_query_args.clone();
 
     let key_fetch_one = if !key_fields.is_empty() {
-        let query =
-            format!("DELETE FROM {table_name} WHERE {key_query_builder_fields_where_clause}");
+        let query = get_delete_query(&key_query_builder_fields_where_clause);
+        let returning_query = format!("{query} RETURNING {all_fields_str}");
 
         quote! {
             impl  #builder_struct_name <#(#key_fetch_delete_method_generics)*> {
                     pub async fn delete<'e, E: sqlx::PgExecutor<'e>>(
                         self,
                         executor: E,
-                    ) -> Result<(), sqlx::Error> {
-                        sqlx::query!(
+                    ) -> Result<u64, sqlx::Error> {
+                        let result = sqlx::query!(
                             #query,
                             #(#key_query_args_2)*
                         )
                             .execute(executor)
                             .await?;
-                        Ok(())
+                        Ok(result.rows_affected())
+                }
+
+                    pub async fn delete_returning<'e, E: sqlx::PgExecutor<'e>>(
+                        self,
+                        executor: E,
+                    ) -> Result<Option<#struct_name>, sqlx::Error> {
+                        #query_as(
+                            #query_as_struct
+                            #returning_query,
+                            #(#key_query_args_3)*
+                        )
+                            #query_as_map
+                            .fetch_optional(executor)
+                            .await
                 }
             }
         }
@@ -70,28 +70,49 @@ impl UserDbSetDeleteQueryBuilder<Set, NotSet> {
     pub async fn delete<'e, E: sqlx::PgExecutor<'e>>(
         self,
         executor: E,
-    ) -> Result<(), sqlx::Error> {
-        sqlx::query!("DELETE FROM users WHERE id = $1", self.id,)
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!("DELETE FROM \"users\" WHERE \"id\" = $1", self.id,)
             .execute(executor)
             .await?;
-        Ok(())
+        Ok(result.rows_affected())
+    }
+    pub async fn delete_returning<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<Option<User>, sqlx::Error> {
+        sqlx::query_as!(
+            User, "DELETE FROM \"users\" WHERE \"id\" = $1 RETURNING \"id\", \"name\", \"details\", \"email\"",
+            self.id,
+        )
+            .fetch_optional(executor)
+            .await
     }
 }
 impl UserDbSetDeleteQueryBuilder<NotSet, Set> {
     pub async fn delete<'e, E: sqlx::PgExecutor<'e>>(
         self,
         executor: E,
-    ) -> Result<(), sqlx::Error> {
-        sqlx::query!(
-             "DELETE FROM users WHERE (email = $1 OR $1 is null)", self.email,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM \"users\" WHERE (\"email\" = $1 OR $1 is null)", self.email,
         )
             .execute(executor)
             .await?;
-            Ok(())
+        Ok(result.rows_affected())
+    }
+    pub async fn delete_returning<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<Option<User>, sqlx::Error> {
+        sqlx::query_as!(
+            User,
+            "DELETE FROM \"users\" WHERE (\"email\" = $1 OR $1 is null) RETURNING \"id\", \"name\", \"details\", \"email\"",
+            self.email,
+        )
+            .fetch_optional(executor)
+            .await
     }
 }
-
-
     "#;
 
     compare_computed_to_expected(input_str, output);
@@ -152,17 +173,27 @@ impl TagDbSetDeleteQueryBuilder<Set> {
     pub async fn delete<'e, E: sqlx::PgExecutor<'e>>(
         self,
         executor: E,
-    ) -> Result<(), sqlx::Error> {
-        sqlx::query!(
-             "DELETE FROM tags WHERE (tag_name = $1 OR $1 is null)", self.tag_name,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM \"tags\" WHERE (\"tag_name\" = $1 OR $1 is null)", self.tag_name,
         )
             .execute(executor)
             .await?;
-        Ok(())
+        Ok(result.rows_affected())
+    }
+    pub async fn delete_returning<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<Option<Tag>, sqlx::Error> {
+        sqlx::query_as!(
+            Tag,
+            "DELETE FROM \"tags\" WHERE (\"tag_name\" = $1 OR $1 is null) RETURNING \"tag_name\"",
+            self.tag_name,
+        )
+            .fetch_optional(executor)
+            .await
     }
 }
-
-
     "#;
 
     compare_computed_to_expected(input_str, output);
@@ -193,7 +224,6 @@ pub struct FavouritedProductDbSetDeleteQueryBuilder<
     _product_id: std::marker::PhantomData<ProductId>,
     _user_id: std::marker::PhantomData<UserId>,
 }
-
 impl FavouritedProductDbSetDeleteQueryBuilder {
     pub fn new() -> FavouritedProductDbSetDeleteQueryBuilder<NotSet, NotSet, NotSet> {
         Self {
@@ -237,18 +267,236 @@ impl FavouritedProductDbSetDeleteQueryBuilder<Set, Set, NotSet> {
     pub async fn delete<'e, E: sqlx::PgExecutor<'e>>(
         self,
         executor: E,
-    ) -> Result<(), sqlx::Error> {
-        sqlx::query!(
-            "DELETE FROM favourite_products WHERE product_id = $1 AND user_id = $2", self
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM \"favourite_products\" WHERE \"product_id\" = $1 AND \"user_id\" = $2", self
             .product_id, self.user_id,
         )
             .execute(executor)
             .await?;
-        Ok(())
+        Ok(result.rows_affected())
     }
+    pub async fn delete_returning<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<Option<FavouritedProduct>, sqlx::Error> {
+        sqlx::query_as!(
+            FavouritedProduct,
+            "DELETE FROM \"favourite_products\" WHERE \"product_id\" = $1 AND \"user_id\" = $2 RETURNING \"product_id\", \"user_id\"",
+            self.product_id, self.user_id,
+        )
+            .fetch_optional(executor)
+            .await
+    }
+}
+    "#;
+
+    compare_computed_to_expected(input_str, output);
+    Ok(())
 }
 
+#[test]
+fn can_parse_soft_delete_post_into_delete_builder() -> Result<(), String> {
+    let input_str = r#"
+        #[derive(DbSet)]
+        #[dbset(table_name = "posts")]
+        pub struct Post {
+            #[key]
+            id: i32,
+            #[unique]
+            slug: String,
+            title: String,
+            #[soft_delete]
+            deleted_at: Option<DateTime<Utc>>,
+        }
+    "#;
 
+    let output = r#"
+pub struct PostDbSetDeleteQueryBuilder<Id = NotSet, UniqueFields = NotSet> {
+    id: Option<i32>,
+    slug: Option<String>,
+    _unique_fields: std::marker::PhantomData<UniqueFields>,
+    _id: std::marker::PhantomData<Id>,
+}
+impl PostDbSetDeleteQueryBuilder {
+    pub fn new() -> PostDbSetDeleteQueryBuilder<NotSet, NotSet> {
+        Self {
+            id: None,
+            slug: None,
+            _unique_fields: std::marker::PhantomData::<NotSet>,
+            _id: std::marker::PhantomData::<NotSet>,
+        }
+    }
+}
+impl PostDbSetDeleteQueryBuilder<NotSet, NotSet> {
+    pub fn id_eq(self, id: i32) -> PostDbSetDeleteQueryBuilder<Set, NotSet> {
+        PostDbSetDeleteQueryBuilder {
+            id: Some(id),
+            slug: self.slug,
+            _id: std::marker::PhantomData::<Set>,
+            _unique_fields: std::marker::PhantomData::<NotSet>,
+        }
+    }
+}
+impl PostDbSetDeleteQueryBuilder<NotSet, NotSet> {
+    pub fn slug_eq(self, slug: String) -> PostDbSetDeleteQueryBuilder<NotSet, Set> {
+        PostDbSetDeleteQueryBuilder {
+            slug: Some(slug),
+            id: self.id,
+            _unique_fields: std::marker::PhantomData::<Set>,
+            _id: self._id,
+        }
+    }
+}
+impl PostDbSetDeleteQueryBuilder<Set, NotSet> {
+    pub async fn delete<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "UPDATE \"posts\" SET \"deleted_at\" = now() WHERE \"id\" = $1 AND \"deleted_at\" IS NULL",
+            self.id,
+        )
+            .execute(executor)
+            .await?;
+        Ok(result.rows_affected())
+    }
+    pub async fn delete_returning<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<Option<Post>, sqlx::Error> {
+        sqlx::query_as!(
+            Post,
+            "UPDATE \"posts\" SET \"deleted_at\" = now() WHERE \"id\" = $1 AND \"deleted_at\" IS NULL RETURNING \"id\", \"slug\", \"title\", \"deleted_at\"",
+            self.id,
+        )
+            .fetch_optional(executor)
+            .await
+    }
+}
+impl PostDbSetDeleteQueryBuilder<NotSet, Set> {
+    pub async fn delete<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "UPDATE \"posts\" SET \"deleted_at\" = now() WHERE (\"slug\" = $1 OR $1 is null) AND \"deleted_at\" IS NULL",
+            self.slug,
+        )
+            .execute(executor)
+            .await?;
+        Ok(result.rows_affected())
+    }
+    pub async fn delete_returning<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<Option<Post>, sqlx::Error> {
+        sqlx::query_as!(
+            Post,
+            "UPDATE \"posts\" SET \"deleted_at\" = now() WHERE (\"slug\" = $1 OR $1 is null) AND \"deleted_at\" IS NULL RETURNING \"id\", \"slug\", \"title\", \"deleted_at\"",
+            self.slug,
+        )
+            .fetch_optional(executor)
+            .await
+    }
+}
+    "#;
+
+    compare_computed_to_expected(input_str, output);
+    Ok(())
+}
+
+#[test]
+fn can_parse_key_named_after_its_type_into_delete_builder() -> Result<(), String> {
+    let input_str = r#"
+#[dbset(table_name = "memberships")]
+pub struct Membership {
+    #[key]
+    user_id: String,
+    #[key]
+    #[custom_enum]
+    group: Group,
+    role: String,
+}
+    "#;
+
+    let output = r#"
+pub struct MembershipDbSetDeleteQueryBuilder<
+    UserId = NotSet,
+    GroupField = NotSet,
+    UniqueFields = NotSet,
+> {
+    user_id: Option<String>,
+    group: Option<Group>,
+    _unique_fields: std::marker::PhantomData<UniqueFields>,
+    _user_id: std::marker::PhantomData<UserId>,
+    _group: std::marker::PhantomData<GroupField>,
+}
+impl MembershipDbSetDeleteQueryBuilder {
+    pub fn new() -> MembershipDbSetDeleteQueryBuilder<NotSet, NotSet, NotSet> {
+        Self {
+            user_id: None,
+            group: None,
+            _unique_fields: std::marker::PhantomData::<NotSet>,
+            _user_id: std::marker::PhantomData::<NotSet>,
+            _group: std::marker::PhantomData::<NotSet>,
+        }
+    }
+}
+impl<GroupField> MembershipDbSetDeleteQueryBuilder<NotSet, GroupField, NotSet> {
+    pub fn user_id_eq(
+        self,
+        user_id: String,
+    ) -> MembershipDbSetDeleteQueryBuilder<Set, GroupField, NotSet> {
+        MembershipDbSetDeleteQueryBuilder {
+            user_id: Some(user_id),
+            group: self.group,
+            _user_id: std::marker::PhantomData::<Set>,
+            _group: self._group,
+            _unique_fields: std::marker::PhantomData::<NotSet>,
+        }
+    }
+}
+impl<UserId> MembershipDbSetDeleteQueryBuilder<UserId, NotSet, NotSet> {
+    pub fn group_eq(
+        self,
+        group: Group,
+    ) -> MembershipDbSetDeleteQueryBuilder<UserId, Set, NotSet> {
+        MembershipDbSetDeleteQueryBuilder {
+            group: Some(group),
+            user_id: self.user_id,
+            _group: std::marker::PhantomData::<Set>,
+            _user_id: self._user_id,
+            _unique_fields: std::marker::PhantomData::<NotSet>,
+        }
+    }
+}
+impl MembershipDbSetDeleteQueryBuilder<Set, Set, NotSet> {
+    pub async fn delete<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM \"memberships\" WHERE \"user_id\" = $1 AND \"group\" = $2", self
+            .user_id, self.group,
+        )
+            .execute(executor)
+            .await?;
+        Ok(result.rows_affected())
+    }
+    pub async fn delete_returning<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<Option<Membership>, sqlx::Error> {
+        sqlx::query_as!(
+            Membership,
+            "DELETE FROM \"memberships\" WHERE \"user_id\" = $1 AND \"group\" = $2 RETURNING \"user_id\", \"group\" AS \"group:Group\", \"role\"",
+            self.user_id, self.group,
+        )
+            .fetch_optional(executor)
+            .await
+    }
+}
     "#;
 
     compare_computed_to_expected(input_str, output);
@@ -5,23 +5,143 @@ use crate::utils;
 
 pub fn get_from_row_impl(input: &DeriveInput) -> proc_macro2::TokenStream {
     let struct_name = utils::get_struct_name(input);
-    let field_names = utils::get_field_names(input);
+    let all_fields = utils::get_all_fields(input);
 
-    let from_row_field_initializers = field_names.iter().map(|field_name| {
-        let field_name_str = format!("{field_name}");
-        quote! {
-            #field_name: sqlx::Row::try_get(row, #field_name_str)?,
-        }
-    });
+    // A flattened field is built from its own columns, the others are read from theirs,
+    // `#[json]` ones decoded through `sqlx::types::Json`
+    let get_field_initializers = |get_column: &dyn Fn(&String) -> proc_macro2::TokenStream| {
+        all_fields
+            .iter()
+            .map(|(field_name, field_type, attrs)| {
+                let columns = utils::get_field_columns(input, field_name);
+                if utils::is_flattened_field(attrs) {
+                    let sub_fields = columns.iter().map(|(sub_field, column_name)| {
+                        let column = get_column(column_name);
+                        quote! { #sub_field: sqlx::Row::try_get(row, #column)?, }
+                    });
+                    quote! {
+                        #field_name: #field_type {
+                            #(#sub_fields)*
+                        },
+                    }
+                } else if attrs.iter().any(utils::is_json_attr) {
+                    let column = get_column(&columns[0].1);
+                    let (json_type, unwrap_json) = utils::get_json_decode(field_type);
+                    quote! {
+                        #field_name: sqlx::Row::try_get::<#json_type, _>(row, #column)?#unwrap_json,
+                    }
+                } else {
+                    let column = get_column(&columns[0].1);
+                    quote! {
+                        #field_name: sqlx::Row::try_get(row, #column)?,
+                    }
+                }
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let from_row_field_initializers =
+        get_field_initializers(&|column_name| quote! { #column_name });
+
+    // Joined queries alias this struct's columns as `{prefix}{column}`
+    let from_row_prefixed_field_initializers = get_field_initializers(
+        &|column_name| quote! { format!("{}{}", prefix, #column_name).as_str() },
+    );
+
+    // #[dbset(skip)] fields are not selected, so they are filled in with their Default
+    let skipped_field_names = utils::get_skipped_field_names(input);
+
+    let json_type_aliases = all_fields
+        .iter()
+        .filter(|(_, _, attrs)| attrs.iter().any(utils::is_json_attr))
+        .map(|(field_name, field_type, _)| {
+            let json_type_alias = utils::get_json_type_alias(field_name);
+            let inner_type = utils::get_inner_option_type(field_type).unwrap_or(field_type);
+            quote! { type #json_type_alias = sqlx::types::Json<#inner_type>; }
+        });
 
     let from_row_impl = quote! {
+            #(#json_type_aliases)*
+
             impl<'r> sqlx::FromRow<'r, sqlx::postgres::PgRow> for #struct_name {
                 fn from_row(row: &'r sqlx::postgres::PgRow) -> Result<Self, sqlx::Error> {
                     Ok(#struct_name {
                         #(#from_row_field_initializers)*
+                        #(#skipped_field_names: Default::default(),)*
+                    })
+                }
+            }
+
+            impl #struct_name {
+                #[doc(hidden)]
+                pub fn from_row_prefixed(
+                    row: &sqlx::postgres::PgRow,
+                    prefix: &str,
+                ) -> Result<Self, sqlx::Error> {
+                    Ok(#struct_name {
+                        #(#from_row_prefixed_field_initializers)*
+                        #(#skipped_field_names: Default::default(),)*
                     })
                 }
             }
     };
     from_row_impl
 }
+// The static queries go through `query_as!`, which needs a column of the same type for every
+// field. With `#[dbset(skip)]`, flattened or `#[json]` fields they use `query!` instead and build
+// the struct from the record, so call sites emit
+// `#query_as(#query_as_struct query, args..) #query_as_map`.
+pub fn get_query_as_parts(
+    input: &DeriveInput,
+) -> (
+    proc_macro2::TokenStream,
+    proc_macro2::TokenStream,
+    proc_macro2::TokenStream,
+) {
+    let struct_name = utils::get_struct_name(input);
+    let skipped_field_names = utils::get_skipped_field_names(input);
+
+    let all_fields = utils::get_all_fields(input);
+    let has_flattened_fields = all_fields
+        .iter()
+        .any(|(_, _, attrs)| utils::is_flattened_field(attrs));
+    let has_json_fields = all_fields
+        .iter()
+        .any(|(_, _, attrs)| attrs.iter().any(utils::is_json_attr));
+
+    if skipped_field_names.is_empty() && !has_flattened_fields && !has_json_fields {
+        return (
+            quote! { sqlx::query_as! },
+            quote! { #struct_name, },
+            quote! {},
+        );
+    }
+
+    let field_initializers = all_fields.iter().map(|(field_name, field_type, attrs)| {
+        if utils::is_flattened_field(attrs) {
+            let sub_fields = utils::get_field_columns(input, field_name)
+                .into_iter()
+                .filter_map(|(sub_field, _)| sub_field)
+                .map(|sub_field| {
+                    let alias = utils::get_flattened_column_alias(field_name, &sub_field);
+                    quote! { #sub_field: row.#alias, }
+                });
+            quote! { #field_name: #field_type { #(#sub_fields)* }, }
+        } else if attrs.iter().any(utils::is_json_attr) {
+            let (_, unwrap_json) = utils::get_json_decode(field_type);
+            quote! { #field_name: row.#field_name #unwrap_json, }
+        } else {
+            quote! { #field_name: row.#field_name, }
+        }
+    });
+    (
+        quote! { sqlx::query! },
+        quote! {},
+        quote! {
+            .map(|row| #struct_name {
+                #(#field_initializers)*
+                #(#skipped_field_names: Default::default(),)*
+            })
+        },
+    )
+}
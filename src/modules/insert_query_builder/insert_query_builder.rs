@@ -1,23 +1,32 @@
-use convert_case::{Case, Casing};
 use proc_macro2::Ident;
-use quote::{quote, ToTokens};
+use quote::quote;
 use syn::{Attribute, DeriveInput, Type};
 
 use crate::common::utils::{
-    get_all_fields, get_auto_fields, get_dbset_name, get_inner_option_type,
-    get_query_fields_string, get_struct_name, get_table_name, is_custom_enum_attr,
+    get_all_fields, get_auto_fields, get_column_sql, get_db_default_fields, get_dbset_name,
+    get_default_fields, get_field_columns, get_inner_option_type, get_json_arg, get_json_bind,
+    get_key_fields, get_query_field_string, get_query_fields_string, get_row_fields_string,
+    get_struct_name, get_table_name, get_timestamp_fields, get_type_state_name, get_version_field,
+    is_custom_enum_attr, is_db_default_attr, is_flattened_field, is_function_path,
+    quote_identifier,
 };
+use crate::modules::from_row::get_query_as_parts;
 pub fn get_insert_builder_struct_name(input: &DeriveInput) -> Ident {
     let dbset_name = get_dbset_name(input);
     quote::format_ident!("{}InsertBuilder", dbset_name)
 }
 
 pub fn get_insert_query_builder(input: &DeriveInput) -> proc_macro2::TokenStream {
+    let (query_as, query_as_struct, query_as_map) = get_query_as_parts(input);
     let table_name = get_table_name(input);
     let struct_name = get_struct_name(input);
     let builder_struct_name = get_insert_builder_struct_name(input);
     let all_fields = get_all_fields(input);
     let auto_fields = get_auto_fields(input);
+    let db_default_fields = get_db_default_fields(input);
+    let default_fields = get_default_fields(input);
+    let timestamp_fields = get_timestamp_fields(input);
+    let version_field = get_version_field(input);
 
     let is_not_auto_field = |(field, _, _): &(&proc_macro2::Ident, &Type, &Vec<Attribute>)| {
         !auto_fields
@@ -25,25 +34,50 @@ pub fn get_insert_query_builder(input: &DeriveInput) -> proc_macro2::TokenStream
             .any(|(auto_field, _, _)| auto_field == field)
     };
 
+    let is_not_db_default_field =
+        |(field, _, _): &(&proc_macro2::Ident, &Type, &Vec<Attribute>)| {
+            !db_default_fields
+                .iter()
+                .any(|(db_default_field, _, _)| db_default_field == field)
+        };
+
+    let is_not_default_field = |(field, _, _): &(&proc_macro2::Ident, &Type, &Vec<Attribute>)| {
+        !default_fields
+            .iter()
+            .any(|(default_field, _, _)| default_field == field)
+    };
+
+    let is_not_timestamp_field = |(field, _, _): &(&proc_macro2::Ident, &Type, &Vec<Attribute>)| {
+        !timestamp_fields
+            .iter()
+            .any(|(timestamp_field, _, _)| timestamp_field == field)
+    };
+
+    let is_not_version_field = |(field, _, _): &(&proc_macro2::Ident, &Type, &Vec<Attribute>)| {
+        version_field.is_none_or(|(version_field, _)| version_field != *field)
+    };
+
     let non_nullable_fields = all_fields
         .iter()
         .filter(|(_, ty, _)| get_inner_option_type(ty).is_none());
 
-    let all_required_insert_fields = non_nullable_fields.filter(|&x| is_not_auto_field(x));
+    let all_required_insert_fields = non_nullable_fields
+        .filter(|&x| is_not_auto_field(x))
+        .filter(|&x| is_not_db_default_field(x))
+        .filter(|&x| is_not_default_field(x))
+        .filter(|&x| is_not_timestamp_field(x))
+        .filter(|&x| is_not_version_field(x));
 
-    let all_insert_fields = all_fields.iter().filter(|&x| is_not_auto_field(x));
+    let all_insert_fields = all_fields
+        .iter()
+        .filter(|&x| is_not_auto_field(x))
+        .filter(|&x| is_not_timestamp_field(x));
 
     // Get builder struct generics
     let builder_struct_generics = all_required_insert_fields
         .clone()
         .map(|(field_name, _, _)| {
-            let gen_name_pascal = quote::format_ident!(
-                "{}",
-                field_name
-                    .to_string()
-                    .from_case(Case::Snake)
-                    .to_case(Case::Pascal)
-            );
+            let gen_name_pascal = get_type_state_name(input, field_name);
             quote! {
                 #gen_name_pascal = NotSet,
             }
@@ -64,12 +98,7 @@ pub fn get_insert_query_builder(input: &DeriveInput) -> proc_macro2::TokenStream
         });
 
     let phantom_struct_fields = all_required_insert_fields.clone().map(|(name, _, _)| {
-        let gen_name_pascal = quote::format_ident!(
-            "{}",
-            name.to_string()
-                .from_case(Case::Snake)
-                .to_case(Case::Pascal)
-        );
+        let gen_name_pascal = get_type_state_name(input, name);
         let ph_name = quote::format_ident!("_{}", name);
         quote! { #ph_name: std::marker::PhantomData::<#gen_name_pascal>, }
     });
@@ -119,13 +148,14 @@ pub fn get_insert_query_builder(input: &DeriveInput) -> proc_macro2::TokenStream
 
     let builder_methods = all_insert_fields.clone()
         .map(|(field_name, field_type,_)| {
-            let method_name = quote::format_ident!("{}", field_name);
+            let method_name = field_name;
             let ph_name = quote::format_ident!("_{}", field_name);
 
             let inner_field_type= get_inner_option_type(field_type);
+            let is_required_field = all_required_insert_fields.clone().any(|(required_name, _, _)| required_name == field_name);
 
             let ph_field =
-            if inner_field_type.is_none() {
+            if is_required_field {
                 quote! { #ph_name: std::marker::PhantomData::<Set>, }
             } else {
                 quote!{}
@@ -137,7 +167,7 @@ pub fn get_insert_query_builder(input: &DeriveInput) -> proc_macro2::TokenStream
 
             let pre_impl_generics_in = all_required_insert_fields.clone().map(|(gen_name, _,_)|{
                if gen_name != field_name {
-                    let gen_name_pascal = quote::format_ident!("{}", gen_name.to_string().from_case(Case::Snake).to_case(Case::Pascal));
+                    let gen_name_pascal = get_type_state_name(input, gen_name);
                     return quote!{ #gen_name_pascal, }
                 }
                 quote!{}
@@ -145,14 +175,14 @@ pub fn get_insert_query_builder(input: &DeriveInput) -> proc_macro2::TokenStream
 
             let generics_in = all_required_insert_fields.clone().map(|(gen_name, _,_)|{
                 if gen_name != field_name {
-                    let gen_name_pascal = quote::format_ident!("{}", gen_name.to_string().from_case(Case::Snake).to_case(Case::Pascal));
+                    let gen_name_pascal = get_type_state_name(input, gen_name);
                     return quote!{ #gen_name_pascal, }
                 }
                 quote!{ NotSet, }
             });
             let generics_out = all_required_insert_fields.clone().map(|(gen_name, _, _)|{
                 if gen_name != field_name {
-                    let gen_name_pascal = quote::format_ident!("{}", gen_name.to_string().from_case(Case::Snake).to_case(Case::Pascal));
+                    let gen_name_pascal = get_type_state_name(input, gen_name);
                     return quote!{ #gen_name_pascal, }
                 }
                 quote!{ Set, }
@@ -197,43 +227,320 @@ pub fn get_insert_query_builder(input: &DeriveInput) -> proc_macro2::TokenStream
     //     .join(", ");
     let all_fields_str = get_query_fields_string(input);
 
-    let all_insert_fields_str = all_insert_fields
+    let key_fields = get_key_fields(input);
+    let key_names = key_fields.iter().map(|(name, _)| *name).collect::<Vec<_>>();
+    let key_types = key_fields.iter().map(|(_, ty)| *ty).collect::<Vec<_>>();
+    let key_type = match key_types.as_slice() {
+        [single_key_type] => quote! { #single_key_type },
+        _ => quote! { (#(#key_types),*) },
+    };
+
+    // #[created_at] and #[updated_at] columns are not set by the builder, they go in last as now()
+    let timestamp_names = timestamp_fields
+        .iter()
+        .map(|(name, _, _)| get_column_sql(input, name))
+        .collect::<Vec<_>>();
+
+    // A flattened field is inserted into each of its columns
+    let all_insert_columns = all_insert_fields
         .clone()
-        .map(|(name, _ty, _attrs)| name.to_string())
+        .flat_map(|(name, _ty, _attrs)| get_field_columns(input, name))
+        .collect::<Vec<_>>();
+
+    let all_insert_fields_str = all_insert_columns
+        .iter()
+        .map(|(_, column)| quote_identifier(column))
+        .chain(timestamp_names.iter().cloned())
         .collect::<Vec<String>>()
         .join(", ");
 
-    let all_params = all_insert_fields
-        .clone()
+    let all_params = all_insert_columns
+        .iter()
         .enumerate()
         .map(|(index, _)| format!("${}", (index + 1)))
+        .chain(timestamp_names.iter().map(|_| "now()".to_string()))
         .collect::<Vec<String>>()
         .join(", ");
 
-    let query = format!("INSERT INTO {table_name}({all_insert_fields_str}) VALUES ({all_params}) RETURNING {all_fields_str};");
+    // With nothing to send, e.g. only an #[auto] key, every column takes its default
+    let insert_query = if all_insert_fields_str.is_empty() {
+        format!("INSERT INTO {table_name} DEFAULT VALUES")
+    } else {
+        format!("INSERT INTO {table_name}({all_insert_fields_str}) VALUES ({all_params})")
+    };
+
+    // Fields with a #[dbset(default = "...")] fall back to their default when unset. The default
+    // is a value of the field's inner type, or a function path such as `Uuid::new_v4` to call.
+    // A #[version] column without one starts at 1.
+    let insert_value = |name: &Ident| {
+        let default_maybe = default_fields
+            .iter()
+            .find(|(default_field, _, _)| *default_field == name);
+        match default_maybe {
+            Some((_, _, default)) if is_function_path(default) => {
+                quote! { self.#name.or_else(|| Some(#default())) }
+            }
+            Some((_, _, default)) => quote! { self.#name.or_else(|| Some(#default)) },
+            None if version_field.is_some_and(|(version_field, _)| version_field == name) => {
+                quote! { self.#name.or_else(|| Some(1)) }
+            }
+            None => quote! { self.#name },
+        }
+    };
 
-    let query_args = all_insert_fields.clone().map(|(name, ty, attrs)| {
-        let is_custom_enum = attrs.iter().any(is_custom_enum_attr);
-        if is_custom_enum {
-            quote! { self.#name as Option<#ty>, }
+    // Flattened fields have no default, so the type-state only allows inserting once they are
+    // set. Each is moved out of the builder and its columns are bound from its fields.
+    let take_flattened_fields = all_insert_fields
+        .clone()
+        .filter(|(_, _, attrs)| is_flattened_field(attrs))
+        .map(|(name, _, _)| {
+            let expect_message = format!("{name} is set before inserting");
+            quote! { let #name = self.#name.expect(#expect_message); }
+        })
+        .collect::<Vec<_>>();
+
+    // The values bound for a field, one per column
+    let insert_binds = |name: &Ident, attrs: &[Attribute]| {
+        if is_flattened_field(attrs) {
+            get_field_columns(input, name)
+                .into_iter()
+                .filter_map(|(sub_field, _)| sub_field)
+                .map(|sub_field| quote! { #name.#sub_field })
+                .collect::<Vec<_>>()
         } else {
-            quote! { self.#name, }
+            vec![insert_value(name)]
         }
-    });
+    };
 
-    let insert_method = quote! {
-        impl  #builder_struct_name <#(#insert_method_generics)*> {
-                pub async fn insert<'e, E: sqlx::PgExecutor<'e>>(
-                    self,
-                    executor: E,
-                ) -> Result<#struct_name, sqlx::Error> {
-                    sqlx::query_as!(
-                        #struct_name,
-                        #query,
+    let insert_method = if db_default_fields.is_empty() {
+        let query = format!("{insert_query} RETURNING {all_fields_str};");
+        let no_return_query = format!("{insert_query};");
+
+        let query_args = all_insert_fields
+            .clone()
+            .flat_map(|(name, ty, attrs)| {
+                let is_custom_enum = attrs.iter().any(is_custom_enum_attr);
+                insert_binds(name, attrs).into_iter().map(move |value| {
+                    if is_custom_enum {
+                        quote! { #value as Option<#ty>, }
+                    } else {
+                        let value = get_json_arg(attrs, value, true);
+                        quote! { #value, }
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let insert_returning_key = if key_fields.is_empty() {
+            quote! {}
+        } else {
+            let key_fields_str = all_fields
+                .iter()
+                .filter(|(name, _, _)| key_names.contains(name))
+                .map(|(name, ty, attrs)| get_query_field_string(input, name, ty, attrs))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let key_query = format!("{insert_query} RETURNING {key_fields_str};");
+
+            let fetch_key = if key_names.len() == 1 {
+                quote! {
+                    #(#take_flattened_fields)*
+                    sqlx::query_scalar!(
+                        #key_query,
                         #(#query_args)*
                     )
                         .fetch_one(executor)
                         .await
+                }
+            } else {
+                quote! {
+                    #(#take_flattened_fields)*
+                    let row = sqlx::query!(
+                        #key_query,
+                        #(#query_args)*
+                    )
+                        .fetch_one(executor)
+                        .await?;
+                    Ok((#(row.#key_names),*))
+                }
+            };
+
+            quote! {
+                    pub async fn insert_returning_key<'e, E: sqlx::PgExecutor<'e>>(
+                        self,
+                        executor: E,
+                    ) -> Result<#key_type, sqlx::Error> {
+                        #fetch_key
+                }
+            }
+        };
+
+        quote! {
+            impl  #builder_struct_name <#(#insert_method_generics)*> {
+                    pub async fn insert<'e, E: sqlx::PgExecutor<'e>>(
+                        self,
+                        executor: E,
+                    ) -> Result<#struct_name, sqlx::Error> {
+                        #(#take_flattened_fields)*
+                        #query_as(
+                            #query_as_struct
+                            #query,
+                            #(#query_args)*
+                        )
+                            #query_as_map
+                            .fetch_one(executor)
+                            .await
+                }
+
+                #insert_returning_key
+
+                    pub async fn insert_no_return<'e, E: sqlx::PgExecutor<'e>>(
+                        self,
+                        executor: E,
+                    ) -> Result<u64, sqlx::Error> {
+                        #(#take_flattened_fields)*
+                        let result = sqlx::query!(
+                            #no_return_query,
+                            #(#query_args)*
+                        )
+                            .execute(executor)
+                            .await?;
+                        Ok(result.rows_affected())
+                }
+            }
+        }
+    } else {
+        // Columns with a database default are only sent when set, so the column list
+        // is not known until runtime and the query is decoded through FromRow instead.
+        let insert_query_start = format!("INSERT INTO {table_name}(");
+        let returning_all = format!(" RETURNING {};", get_row_fields_string(input));
+        let returning_key = format!(
+            " RETURNING {};",
+            key_names
+                .iter()
+                .map(|name| get_column_sql(input, name))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+
+        let insert_columns = all_insert_fields.clone().map(|(name, _, attrs)| {
+            let name_strs = get_field_columns(input, name)
+                .into_iter()
+                .map(|(_, column)| quote_identifier(&column));
+            if attrs.iter().any(is_db_default_attr) {
+                quote! {
+                    if self.#name.is_some() {
+                        #(columns.push(#name_strs);)*
+                    }
+                }
+            } else {
+                quote! { #(columns.push(#name_strs);)* }
+            }
+        });
+
+        let insert_values = all_insert_fields.clone().map(|(name, _, attrs)| {
+            if attrs.iter().any(is_db_default_attr) {
+                let bind = get_json_bind(attrs, quote! { #name }, false);
+                quote! {
+                    if let Some(#name) = self.#name {
+                        values.push_bind(#bind);
+                    }
+                }
+            } else {
+                let binds = insert_binds(name, attrs)
+                    .into_iter()
+                    .map(|value| get_json_bind(attrs, value, true));
+                quote! { #(values.push_bind(#binds);)* }
+            }
+        });
+
+        let timestamp_now = timestamp_names.iter().map(|_| "now()");
+
+        // When every column has a database default, leaving them all unset inserts DEFAULT VALUES
+        let always_has_columns = all_insert_fields
+            .clone()
+            .any(|(_, _, attrs)| !attrs.iter().any(is_db_default_attr))
+            || !timestamp_names.is_empty();
+        let (insert_query_start, push_default_values) = if always_has_columns {
+            (insert_query_start, quote! {})
+        } else {
+            let unset_checks = all_insert_fields
+                .clone()
+                .map(|(name, _, _)| quote! { self.#name.is_none() });
+            (
+                format!("INSERT INTO {table_name}"),
+                quote! {
+                    if #(#unset_checks)&&* {
+                        query_builder.push(" DEFAULT VALUES");
+                        query_builder.push(returning);
+                        return query_builder;
+                    }
+                    query_builder.push("(");
+                },
+            )
+        };
+
+        let insert_returning_key = if key_fields.is_empty() {
+            quote! {}
+        } else {
+            let build_key_query = if key_names.len() == 1 {
+                quote! { build_query_scalar::<#key_type>() }
+            } else {
+                quote! { build_query_as::<#key_type>() }
+            };
+            quote! {
+                    pub async fn insert_returning_key<'e, E: sqlx::PgExecutor<'e>>(
+                        self,
+                        executor: E,
+                    ) -> Result<#key_type, sqlx::Error> {
+                        let mut query_builder = self.build_insert_query(#returning_key);
+                        query_builder
+                            .#build_key_query
+                            .fetch_one(executor)
+                            .await
+                }
+            }
+        };
+
+        quote! {
+            impl  #builder_struct_name <#(#insert_method_generics)*> {
+                    fn build_insert_query<'q>(self, returning: &str) -> sqlx::QueryBuilder<'q, sqlx::Postgres> {
+                        #(#take_flattened_fields)*
+                        let mut query_builder = sqlx::QueryBuilder::<sqlx::Postgres>::new(#insert_query_start);
+                        #push_default_values
+                        let mut columns = query_builder.separated(", ");
+                        #(#insert_columns)*
+                        #(columns.push(#timestamp_names);)*
+                        query_builder.push(") VALUES (");
+                        let mut values = query_builder.separated(", ");
+                        #(#insert_values)*
+                        #(values.push(#timestamp_now);)*
+                        query_builder.push(")");
+                        query_builder.push(returning);
+                        query_builder
+                }
+
+                    pub async fn insert<'e, E: sqlx::PgExecutor<'e>>(
+                        self,
+                        executor: E,
+                    ) -> Result<#struct_name, sqlx::Error> {
+                        let mut query_builder = self.build_insert_query(#returning_all);
+                        query_builder
+                            .build_query_as::<#struct_name>()
+                            .fetch_one(executor)
+                            .await
+                }
+
+                #insert_returning_key
+
+                    pub async fn insert_no_return<'e, E: sqlx::PgExecutor<'e>>(
+                        self,
+                        executor: E,
+                    ) -> Result<u64, sqlx::Error> {
+                        let mut query_builder = self.build_insert_query(";");
+                        let result = query_builder.build().execute(executor).await?;
+                        Ok(result.rows_affected())
+                }
             }
         }
     };
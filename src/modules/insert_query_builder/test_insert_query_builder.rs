@@ -1,7 +1,8 @@
 use pretty_assertions::assert_eq;
 
 use crate::common::utils::{
-    derive_input_from_string, pretty_print_tokenstream, tokenstream_from_string,
+    check_flattened_fields, derive_input_from_string, pretty_print_tokenstream,
+    tokenstream_from_string,
 };
 
 use super::insert_query_builder;
@@ -54,12 +55,35 @@ impl AccountDbSetInsertBuilder<Set> {
         executor: E,
     ) -> Result<Account, sqlx::Error> {
         sqlx::query_as!(
-            Account, "INSERT INTO users(email) VALUES ($1) RETURNING id, email;", self
+            Account, "INSERT INTO \"users\"(\"email\") VALUES ($1) RETURNING \"id\", \"email\";", self
             .email,
         )
             .fetch_one(executor)
             .await
     }
+    pub async fn insert_returning_key<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<uuid::Uuid, sqlx::Error> {
+        sqlx::query_scalar!(
+            "INSERT INTO \"users\"(\"email\") VALUES ($1) RETURNING \"id\";",
+            self.email,
+        )
+            .fetch_one(executor)
+            .await
+    }
+    pub async fn insert_no_return<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "INSERT INTO \"users\"(\"email\") VALUES ($1);",
+            self.email,
+        )
+            .execute(executor)
+            .await?;
+        Ok(result.rows_affected())
+    }
 }
 
     "#;
@@ -126,13 +150,38 @@ impl AccountDbSetInsertBuilder<Set, Set> {
         executor: E,
     ) -> Result<Account, sqlx::Error> {
         sqlx::query_as!(
-            Account, "INSERT INTO users(email, status) VALUES ($1, $2) RETURNING id, email, status AS \"status:AccountStatus\";", 
+            Account, "INSERT INTO \"users\"(\"email\", \"status\") VALUES ($1, $2) RETURNING \"id\", \"email\", \"status\" AS \"status:AccountStatus\";", 
+            self.email,
+            self.status as Option<AccountStatus>,
+        )
+            .fetch_one(executor)
+            .await
+    }
+    pub async fn insert_returning_key<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<uuid::Uuid, sqlx::Error> {
+        sqlx::query_scalar!(
+            "INSERT INTO \"users\"(\"email\", \"status\") VALUES ($1, $2) RETURNING \"id\";",
             self.email,
             self.status as Option<AccountStatus>,
         )
             .fetch_one(executor)
             .await
     }
+    pub async fn insert_no_return<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "INSERT INTO \"users\"(\"email\", \"status\") VALUES ($1, $2);",
+            self.email,
+            self.status as Option<AccountStatus>,
+        )
+            .execute(executor)
+            .await?;
+        Ok(result.rows_affected())
+    }
 }
 
     "#;
@@ -236,15 +285,1216 @@ impl UserDbSetInsertBuilder<Set, Set, Set> {
     ) -> Result<User, sqlx::Error> {
         sqlx::query_as!(
             User,
-            "INSERT INTO users(id, name, details, email) VALUES ($1, $2, $3, $4) RETURNING id, name, details, email;",
+            "INSERT INTO \"users\"(\"id\", \"name\", \"details\", \"email\") VALUES ($1, $2, $3, $4) RETURNING \"id\", \"name\", \"details\", \"email\";",
+            self.id, self.name, self.details, self.email,
+        )
+            .fetch_one(executor)
+            .await
+    }
+    pub async fn insert_returning_key<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<String, sqlx::Error> {
+        sqlx::query_scalar!(
+            "INSERT INTO \"users\"(\"id\", \"name\", \"details\", \"email\") VALUES ($1, $2, $3, $4) RETURNING \"id\";",
+            self.id, self.name, self.details, self.email,
+        )
+            .fetch_one(executor)
+            .await
+    }
+    pub async fn insert_no_return<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "INSERT INTO \"users\"(\"id\", \"name\", \"details\", \"email\") VALUES ($1, $2, $3, $4);",
             self.id, self.name, self.details, self.email,
+        )
+            .execute(executor)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
+
+
+    "#;
+
+    compare_computed_to_expected(input_str, output);
+    Ok(())
+}
+
+#[test]
+fn can_parse_account_with_db_default_fields_into_insert_builder() -> Result<(), String> {
+    let input_str = r#"
+#[dbset(table_name = "accounts")]
+pub struct Account {
+    #[key]
+    #[auto]
+    id: uuid::Uuid,
+    email: String,
+    #[db_default]
+    created_at: chrono::DateTime<chrono::Utc>,
+    #[db_default]
+    note: Option<String>,
+}
+    "#;
+
+    let output = r#"
+pub struct AccountDbSetInsertBuilder<Email = NotSet> {
+    email: Option<String>,
+    created_at: Option<chrono::DateTime<chrono::Utc>>,
+    note: Option<String>,
+    _email: std::marker::PhantomData<Email>,
+}
+impl AccountDbSetInsertBuilder {
+    pub fn new() -> AccountDbSetInsertBuilder<NotSet> {
+        Self {
+            email: None,
+            created_at: None,
+            note: None,
+            _email: std::marker::PhantomData::<NotSet>,
+        }
+    }
+}
+impl AccountDbSetInsertBuilder<NotSet> {
+    pub fn email(self, email: String) -> AccountDbSetInsertBuilder<Set> {
+        AccountDbSetInsertBuilder {
+            email: Some(email),
+            created_at: self.created_at,
+            note: self.note,
+            _email: std::marker::PhantomData::<Set>,
+        }
+    }
+}
+impl<Email> AccountDbSetInsertBuilder<Email> {
+    pub fn created_at(
+        self,
+        created_at: chrono::DateTime<chrono::Utc>,
+    ) -> AccountDbSetInsertBuilder<Email> {
+        AccountDbSetInsertBuilder {
+            created_at: Some(created_at),
+            email: self.email,
+            note: self.note,
+            _email: self._email,
+        }
+    }
+}
+impl<Email> AccountDbSetInsertBuilder<Email> {
+    pub fn note(self, note: String) -> AccountDbSetInsertBuilder<Email> {
+        AccountDbSetInsertBuilder {
+            note: Some(note),
+            email: self.email,
+            created_at: self.created_at,
+            _email: self._email,
+        }
+    }
+}
+impl AccountDbSetInsertBuilder<Set> {
+    fn build_insert_query<'q>(
+        self,
+        returning: &str,
+    ) -> sqlx::QueryBuilder<'q, sqlx::Postgres> {
+        let mut query_builder = sqlx::QueryBuilder::<
+            sqlx::Postgres,
+        >::new("INSERT INTO \"accounts\"(");
+        let mut columns = query_builder.separated(", ");
+        columns.push("\"email\"");
+        if self.created_at.is_some() {
+            columns.push("\"created_at\"");
+        }
+        if self.note.is_some() {
+            columns.push("\"note\"");
+        }
+        query_builder.push(") VALUES (");
+        let mut values = query_builder.separated(", ");
+        values.push_bind(self.email);
+        if let Some(created_at) = self.created_at {
+            values.push_bind(created_at);
+        }
+        if let Some(note) = self.note {
+            values.push_bind(note);
+        }
+        query_builder.push(")");
+        query_builder.push(returning);
+        query_builder
+    }
+    pub async fn insert<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<Account, sqlx::Error> {
+        let mut query_builder = self
+            .build_insert_query(" RETURNING \"id\", \"email\", \"created_at\", \"note\";");
+        query_builder.build_query_as::<Account>().fetch_one(executor).await
+    }
+    pub async fn insert_returning_key<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<uuid::Uuid, sqlx::Error> {
+        let mut query_builder = self.build_insert_query(" RETURNING \"id\";");
+        query_builder.build_query_scalar::<uuid::Uuid>().fetch_one(executor).await
+    }
+    pub async fn insert_no_return<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<u64, sqlx::Error> {
+        let mut query_builder = self.build_insert_query(";");
+        let result = query_builder.build().execute(executor).await?;
+        Ok(result.rows_affected())
+    }
+}
+    "#;
+
+    compare_computed_to_expected(input_str, output);
+    Ok(())
+}
+
+#[test]
+fn can_parse_account_with_default_fields_into_insert_builder() -> Result<(), String> {
+    let input_str = r#"
+#[dbset(table_name = "accounts")]
+pub struct Account {
+    #[key]
+    #[dbset(default = "uuid::Uuid::new_v4()")]
+    id: uuid::Uuid,
+    email: String,
+    #[dbset(default = "chrono::Utc::now()")]
+    created_at: chrono::DateTime<chrono::Utc>,
+}
+    "#;
+
+    let output = r#"
+pub struct AccountDbSetInsertBuilder<Email = NotSet> {
+    id: Option<uuid::Uuid>,
+    email: Option<String>,
+    created_at: Option<chrono::DateTime<chrono::Utc>>,
+    _email: std::marker::PhantomData<Email>,
+}
+impl AccountDbSetInsertBuilder {
+    pub fn new() -> AccountDbSetInsertBuilder<NotSet> {
+        Self {
+            id: None,
+            email: None,
+            created_at: None,
+            _email: std::marker::PhantomData::<NotSet>,
+        }
+    }
+}
+impl<Email> AccountDbSetInsertBuilder<Email> {
+    pub fn id(self, id: uuid::Uuid) -> AccountDbSetInsertBuilder<Email> {
+        AccountDbSetInsertBuilder {
+            id: Some(id),
+            email: self.email,
+            created_at: self.created_at,
+            _email: self._email,
+        }
+    }
+}
+impl AccountDbSetInsertBuilder<NotSet> {
+    pub fn email(self, email: String) -> AccountDbSetInsertBuilder<Set> {
+        AccountDbSetInsertBuilder {
+            email: Some(email),
+            id: self.id,
+            created_at: self.created_at,
+            _email: std::marker::PhantomData::<Set>,
+        }
+    }
+}
+impl<Email> AccountDbSetInsertBuilder<Email> {
+    pub fn created_at(
+        self,
+        created_at: chrono::DateTime<chrono::Utc>,
+    ) -> AccountDbSetInsertBuilder<Email> {
+        AccountDbSetInsertBuilder {
+            created_at: Some(created_at),
+            id: self.id,
+            email: self.email,
+            _email: self._email,
+        }
+    }
+}
+impl AccountDbSetInsertBuilder<Set> {
+    pub async fn insert<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<Account, sqlx::Error> {
+        sqlx::query_as!(
+            Account,
+            "INSERT INTO \"accounts\"(\"id\", \"email\", \"created_at\") VALUES ($1, $2, $3) RETURNING \"id\", \"email\", \"created_at\";",
+            self.id.or_else(|| Some(uuid::Uuid::new_v4())), self.email, self.created_at
+            .or_else(|| Some(chrono::Utc::now())),
+        )
+            .fetch_one(executor)
+            .await
+    }
+    pub async fn insert_returning_key<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<uuid::Uuid, sqlx::Error> {
+        sqlx::query_scalar!(
+            "INSERT INTO \"accounts\"(\"id\", \"email\", \"created_at\") VALUES ($1, $2, $3) RETURNING \"id\";",
+            self.id.or_else(|| Some(uuid::Uuid::new_v4())), self.email, self.created_at
+            .or_else(|| Some(chrono::Utc::now())),
+        )
+            .fetch_one(executor)
+            .await
+    }
+    pub async fn insert_no_return<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "INSERT INTO \"accounts\"(\"id\", \"email\", \"created_at\") VALUES ($1, $2, $3);", self.id
+            .or_else(|| Some(uuid::Uuid::new_v4())), self.email, self.created_at
+            .or_else(|| Some(chrono::Utc::now())),
+        )
+            .execute(executor)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
+    "#;
+
+    compare_computed_to_expected(input_str, output);
+    Ok(())
+}
+
+#[test]
+fn can_parse_order_line_with_two_keys_into_insert_builder() -> Result<(), String> {
+    let input_str = r#"
+#[dbset(table_name = "order_lines")]
+pub struct OrderLine {
+    #[key]
+    order_id: String,
+    #[key]
+    line_no: i32,
+    product: String,
+}
+    "#;
+
+    let output = r#"
+pub struct OrderLineDbSetInsertBuilder<
+    OrderId = NotSet,
+    LineNo = NotSet,
+    Product = NotSet,
+> {
+    order_id: Option<String>,
+    line_no: Option<i32>,
+    product: Option<String>,
+    _order_id: std::marker::PhantomData<OrderId>,
+    _line_no: std::marker::PhantomData<LineNo>,
+    _product: std::marker::PhantomData<Product>,
+}
+impl OrderLineDbSetInsertBuilder {
+    pub fn new() -> OrderLineDbSetInsertBuilder<NotSet, NotSet, NotSet> {
+        Self {
+            order_id: None,
+            line_no: None,
+            product: None,
+            _order_id: std::marker::PhantomData::<NotSet>,
+            _line_no: std::marker::PhantomData::<NotSet>,
+            _product: std::marker::PhantomData::<NotSet>,
+        }
+    }
+}
+impl<LineNo, Product> OrderLineDbSetInsertBuilder<NotSet, LineNo, Product> {
+    pub fn order_id(
+        self,
+        order_id: String,
+    ) -> OrderLineDbSetInsertBuilder<Set, LineNo, Product> {
+        OrderLineDbSetInsertBuilder {
+            order_id: Some(order_id),
+            line_no: self.line_no,
+            product: self.product,
+            _order_id: std::marker::PhantomData::<Set>,
+            _line_no: self._line_no,
+            _product: self._product,
+        }
+    }
+}
+impl<OrderId, Product> OrderLineDbSetInsertBuilder<OrderId, NotSet, Product> {
+    pub fn line_no(
+        self,
+        line_no: i32,
+    ) -> OrderLineDbSetInsertBuilder<OrderId, Set, Product> {
+        OrderLineDbSetInsertBuilder {
+            line_no: Some(line_no),
+            order_id: self.order_id,
+            product: self.product,
+            _line_no: std::marker::PhantomData::<Set>,
+            _order_id: self._order_id,
+            _product: self._product,
+        }
+    }
+}
+impl<OrderId, LineNo> OrderLineDbSetInsertBuilder<OrderId, LineNo, NotSet> {
+    pub fn product(
+        self,
+        product: String,
+    ) -> OrderLineDbSetInsertBuilder<OrderId, LineNo, Set> {
+        OrderLineDbSetInsertBuilder {
+            product: Some(product),
+            order_id: self.order_id,
+            line_no: self.line_no,
+            _product: std::marker::PhantomData::<Set>,
+            _order_id: self._order_id,
+            _line_no: self._line_no,
+        }
+    }
+}
+impl OrderLineDbSetInsertBuilder<Set, Set, Set> {
+    pub async fn insert<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<OrderLine, sqlx::Error> {
+        sqlx::query_as!(
+            OrderLine,
+            "INSERT INTO \"order_lines\"(\"order_id\", \"line_no\", \"product\") VALUES ($1, $2, $3) RETURNING \"order_id\", \"line_no\", \"product\";",
+            self.order_id, self.line_no, self.product,
         )
             .fetch_one(executor)
             .await
     }
+    pub async fn insert_returning_key<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<(String, i32), sqlx::Error> {
+        let row = sqlx::query!(
+            "INSERT INTO \"order_lines\"(\"order_id\", \"line_no\", \"product\") VALUES ($1, $2, $3) RETURNING \"order_id\", \"line_no\";",
+            self.order_id, self.line_no, self.product,
+        )
+            .fetch_one(executor)
+            .await?;
+        Ok((row.order_id, row.line_no))
+    }
+    pub async fn insert_no_return<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "INSERT INTO \"order_lines\"(\"order_id\", \"line_no\", \"product\") VALUES ($1, $2, $3);",
+            self.order_id, self.line_no, self.product,
+        )
+            .execute(executor)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
+    "#;
+
+    compare_computed_to_expected(input_str, output);
+    Ok(())
 }
 
+#[test]
+fn can_parse_note_with_timestamp_fields_into_insert_builder() -> Result<(), String> {
+    let input_str = r#"
+        #[derive(DbSet, Debug)]
+        #[dbset(table_name = "notes")]
+        pub struct Note {
+            #[key]
+            id: i32,
+            text: String,
+            #[created_at]
+            created_at: DateTime<Utc>,
+            #[updated_at]
+            updated_at: DateTime<Utc>,
+        }
+    "#;
 
+    let output = r#"
+pub struct NoteDbSetInsertBuilder<Id = NotSet, Text = NotSet> {
+    id: Option<i32>,
+    text: Option<String>,
+    _id: std::marker::PhantomData<Id>,
+    _text: std::marker::PhantomData<Text>,
+}
+impl NoteDbSetInsertBuilder {
+    pub fn new() -> NoteDbSetInsertBuilder<NotSet, NotSet> {
+        Self {
+            id: None,
+            text: None,
+            _id: std::marker::PhantomData::<NotSet>,
+            _text: std::marker::PhantomData::<NotSet>,
+        }
+    }
+}
+impl<Text> NoteDbSetInsertBuilder<NotSet, Text> {
+    pub fn id(self, id: i32) -> NoteDbSetInsertBuilder<Set, Text> {
+        NoteDbSetInsertBuilder {
+            id: Some(id),
+            text: self.text,
+            _id: std::marker::PhantomData::<Set>,
+            _text: self._text,
+        }
+    }
+}
+impl<Id> NoteDbSetInsertBuilder<Id, NotSet> {
+    pub fn text(self, text: String) -> NoteDbSetInsertBuilder<Id, Set> {
+        NoteDbSetInsertBuilder {
+            text: Some(text),
+            id: self.id,
+            _text: std::marker::PhantomData::<Set>,
+            _id: self._id,
+        }
+    }
+}
+impl NoteDbSetInsertBuilder<Set, Set> {
+    pub async fn insert<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<Note, sqlx::Error> {
+        sqlx::query_as!(
+            Note,
+            "INSERT INTO \"notes\"(\"id\", \"text\", \"created_at\", \"updated_at\") VALUES ($1, $2, now(), now()) RETURNING \"id\", \"text\", \"created_at\", \"updated_at\";",
+            self.id, self.text,
+        )
+            .fetch_one(executor)
+            .await
+    }
+    pub async fn insert_returning_key<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<i32, sqlx::Error> {
+        sqlx::query_scalar!(
+            "INSERT INTO \"notes\"(\"id\", \"text\", \"created_at\", \"updated_at\") VALUES ($1, $2, now(), now()) RETURNING \"id\";",
+            self.id, self.text,
+        )
+            .fetch_one(executor)
+            .await
+    }
+    pub async fn insert_no_return<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "INSERT INTO \"notes\"(\"id\", \"text\", \"created_at\", \"updated_at\") VALUES ($1, $2, now(), now());",
+            self.id, self.text,
+        )
+            .execute(executor)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
+    "#;
+
+    compare_computed_to_expected(input_str, output);
+    Ok(())
+}
+
+#[test]
+fn can_parse_renamed_columns_into_insert_builder() -> Result<(), String> {
+    let input_str = r#"
+        #[derive(DbSet)]
+        #[dbset(table_name = "customers")]
+        #[dbset(rename_all = "camelCase")]
+        pub struct Customer {
+            #[key]
+            customer_id: i32,
+            display_name: String,
+            #[dbset(column = "kind")]
+            r#type: String,
+        }
+    "#;
+
+    let output = r#"
+pub struct CustomerDbSetInsertBuilder<
+    CustomerId = NotSet,
+    DisplayName = NotSet,
+    Type = NotSet,
+> {
+    customer_id: Option<i32>,
+    display_name: Option<String>,
+    r#type: Option<String>,
+    _customer_id: std::marker::PhantomData<CustomerId>,
+    _display_name: std::marker::PhantomData<DisplayName>,
+    _type: std::marker::PhantomData<Type>,
+}
+impl CustomerDbSetInsertBuilder {
+    pub fn new() -> CustomerDbSetInsertBuilder<NotSet, NotSet, NotSet> {
+        Self {
+            customer_id: None,
+            display_name: None,
+            r#type: None,
+            _customer_id: std::marker::PhantomData::<NotSet>,
+            _display_name: std::marker::PhantomData::<NotSet>,
+            _type: std::marker::PhantomData::<NotSet>,
+        }
+    }
+}
+impl<DisplayName, Type> CustomerDbSetInsertBuilder<NotSet, DisplayName, Type> {
+    pub fn customer_id(
+        self,
+        customer_id: i32,
+    ) -> CustomerDbSetInsertBuilder<Set, DisplayName, Type> {
+        CustomerDbSetInsertBuilder {
+            customer_id: Some(customer_id),
+            display_name: self.display_name,
+            r#type: self.r#type,
+            _customer_id: std::marker::PhantomData::<Set>,
+            _display_name: self._display_name,
+            _type: self._type,
+        }
+    }
+}
+impl<CustomerId, Type> CustomerDbSetInsertBuilder<CustomerId, NotSet, Type> {
+    pub fn display_name(
+        self,
+        display_name: String,
+    ) -> CustomerDbSetInsertBuilder<CustomerId, Set, Type> {
+        CustomerDbSetInsertBuilder {
+            display_name: Some(display_name),
+            customer_id: self.customer_id,
+            r#type: self.r#type,
+            _display_name: std::marker::PhantomData::<Set>,
+            _customer_id: self._customer_id,
+            _type: self._type,
+        }
+    }
+}
+impl<
+    CustomerId,
+    DisplayName,
+> CustomerDbSetInsertBuilder<CustomerId, DisplayName, NotSet> {
+    pub fn r#type(
+        self,
+        r#type: String,
+    ) -> CustomerDbSetInsertBuilder<CustomerId, DisplayName, Set> {
+        CustomerDbSetInsertBuilder {
+            r#type: Some(r#type),
+            customer_id: self.customer_id,
+            display_name: self.display_name,
+            _type: std::marker::PhantomData::<Set>,
+            _customer_id: self._customer_id,
+            _display_name: self._display_name,
+        }
+    }
+}
+impl CustomerDbSetInsertBuilder<Set, Set, Set> {
+    pub async fn insert<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<Customer, sqlx::Error> {
+        sqlx::query_as!(
+            Customer,
+            "INSERT INTO \"customers\"(\"customerId\", \"displayName\", \"kind\") VALUES ($1, $2, $3) RETURNING \"customerId\" AS \"customer_id\", \"displayName\" AS \"display_name\", \"kind\" AS \"type\";",
+            self.customer_id, self.display_name, self.r#type,
+        )
+            .fetch_one(executor)
+            .await
+    }
+    pub async fn insert_returning_key<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<i32, sqlx::Error> {
+        sqlx::query_scalar!(
+            "INSERT INTO \"customers\"(\"customerId\", \"displayName\", \"kind\") VALUES ($1, $2, $3) RETURNING \"customerId\" AS \"customer_id\";",
+            self.customer_id, self.display_name, self.r#type,
+        )
+            .fetch_one(executor)
+            .await
+    }
+    pub async fn insert_no_return<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "INSERT INTO \"customers\"(\"customerId\", \"displayName\", \"kind\") VALUES ($1, $2, $3);",
+            self.customer_id, self.display_name, self.r#type,
+        )
+            .execute(executor)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
+    "#;
+
+    compare_computed_to_expected(input_str, output);
+    Ok(())
+}
+
+#[test]
+fn can_parse_flattened_address_into_insert_builder() -> Result<(), String> {
+    let input_str = r#"
+    #[derive(DbSet, Debug, Clone)]
+    #[dbset(table_name = "venues")]
+    pub struct Venue {
+        #[key]
+        id: i32,
+        name: String,
+        #[dbset(flatten(street, city))]
+        address: Address,
+        #[dbset(flatten(street, city, prefix = "postal_"))]
+        mailing_address: Address,
+    }
+    "#;
+
+    let output = r#"
+pub struct VenueDbSetInsertBuilder<
+    Id = NotSet,
+    Name = NotSet,
+    AddressField = NotSet,
+    MailingAddress = NotSet,
+> {
+    id: Option<i32>,
+    name: Option<String>,
+    address: Option<Address>,
+    mailing_address: Option<Address>,
+    _id: std::marker::PhantomData<Id>,
+    _name: std::marker::PhantomData<Name>,
+    _address: std::marker::PhantomData<AddressField>,
+    _mailing_address: std::marker::PhantomData<MailingAddress>,
+}
+impl VenueDbSetInsertBuilder {
+    pub fn new() -> VenueDbSetInsertBuilder<NotSet, NotSet, NotSet, NotSet> {
+        Self {
+            id: None,
+            name: None,
+            address: None,
+            mailing_address: None,
+            _id: std::marker::PhantomData::<NotSet>,
+            _name: std::marker::PhantomData::<NotSet>,
+            _address: std::marker::PhantomData::<NotSet>,
+            _mailing_address: std::marker::PhantomData::<NotSet>,
+        }
+    }
+}
+impl<
+    Name,
+    AddressField,
+    MailingAddress,
+> VenueDbSetInsertBuilder<NotSet, Name, AddressField, MailingAddress> {
+    pub fn id(
+        self,
+        id: i32,
+    ) -> VenueDbSetInsertBuilder<Set, Name, AddressField, MailingAddress> {
+        VenueDbSetInsertBuilder {
+            id: Some(id),
+            name: self.name,
+            address: self.address,
+            mailing_address: self.mailing_address,
+            _id: std::marker::PhantomData::<Set>,
+            _name: self._name,
+            _address: self._address,
+            _mailing_address: self._mailing_address,
+        }
+    }
+}
+impl<
+    Id,
+    AddressField,
+    MailingAddress,
+> VenueDbSetInsertBuilder<Id, NotSet, AddressField, MailingAddress> {
+    pub fn name(
+        self,
+        name: String,
+    ) -> VenueDbSetInsertBuilder<Id, Set, AddressField, MailingAddress> {
+        VenueDbSetInsertBuilder {
+            name: Some(name),
+            id: self.id,
+            address: self.address,
+            mailing_address: self.mailing_address,
+            _name: std::marker::PhantomData::<Set>,
+            _id: self._id,
+            _address: self._address,
+            _mailing_address: self._mailing_address,
+        }
+    }
+}
+impl<
+    Id,
+    Name,
+    MailingAddress,
+> VenueDbSetInsertBuilder<Id, Name, NotSet, MailingAddress> {
+    pub fn address(
+        self,
+        address: Address,
+    ) -> VenueDbSetInsertBuilder<Id, Name, Set, MailingAddress> {
+        VenueDbSetInsertBuilder {
+            address: Some(address),
+            id: self.id,
+            name: self.name,
+            mailing_address: self.mailing_address,
+            _address: std::marker::PhantomData::<Set>,
+            _id: self._id,
+            _name: self._name,
+            _mailing_address: self._mailing_address,
+        }
+    }
+}
+impl<Id, Name, AddressField> VenueDbSetInsertBuilder<Id, Name, AddressField, NotSet> {
+    pub fn mailing_address(
+        self,
+        mailing_address: Address,
+    ) -> VenueDbSetInsertBuilder<Id, Name, AddressField, Set> {
+        VenueDbSetInsertBuilder {
+            mailing_address: Some(mailing_address),
+            id: self.id,
+            name: self.name,
+            address: self.address,
+            _mailing_address: std::marker::PhantomData::<Set>,
+            _id: self._id,
+            _name: self._name,
+            _address: self._address,
+        }
+    }
+}
+impl VenueDbSetInsertBuilder<Set, Set, Set, Set> {
+    pub async fn insert<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<Venue, sqlx::Error> {
+        let address = self.address.expect("address is set before inserting");
+        let mailing_address = self
+            .mailing_address
+            .expect("mailing_address is set before inserting");
+        sqlx::query!(
+            "INSERT INTO \"venues\"(\"id\", \"name\", \"address_street\", \"address_city\", \"postal_street\", \"postal_city\") VALUES ($1, $2, $3, $4, $5, $6) RETURNING \"id\", \"name\", \"address_street\", \"address_city\", \"postal_street\" AS \"mailing_address_street\", \"postal_city\" AS \"mailing_address_city\";",
+            self.id, self.name, address.street, address.city, mailing_address.street,
+            mailing_address.city,
+        )
+            .map(|row| Venue {
+                id: row.id,
+                name: row.name,
+                address: Address {
+                    street: row.address_street,
+                    city: row.address_city,
+                },
+                mailing_address: Address {
+                    street: row.mailing_address_street,
+                    city: row.mailing_address_city,
+                },
+            })
+            .fetch_one(executor)
+            .await
+    }
+    pub async fn insert_returning_key<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<i32, sqlx::Error> {
+        let address = self.address.expect("address is set before inserting");
+        let mailing_address = self
+            .mailing_address
+            .expect("mailing_address is set before inserting");
+        sqlx::query_scalar!(
+            "INSERT INTO \"venues\"(\"id\", \"name\", \"address_street\", \"address_city\", \"postal_street\", \"postal_city\") VALUES ($1, $2, $3, $4, $5, $6) RETURNING \"id\";",
+            self.id, self.name, address.street, address.city, mailing_address.street,
+            mailing_address.city,
+        )
+            .fetch_one(executor)
+            .await
+    }
+    pub async fn insert_no_return<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<u64, sqlx::Error> {
+        let address = self.address.expect("address is set before inserting");
+        let mailing_address = self
+            .mailing_address
+            .expect("mailing_address is set before inserting");
+        let result = sqlx::query!(
+            "INSERT INTO \"venues\"(\"id\", \"name\", \"address_street\", \"address_city\", \"postal_street\", \"postal_city\") VALUES ($1, $2, $3, $4, $5, $6);",
+            self.id, self.name, address.street, address.city, mailing_address.street,
+            mailing_address.city,
+        )
+            .execute(executor)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
+    "#;
+
+    compare_computed_to_expected(input_str, output);
+    Ok(())
+}
+
+#[test]
+#[should_panic(expected = "flattened field address on Venue can not be an Option")]
+fn optional_flattened_field_fails() {
+    let input_str = r#"
+        #[derive(DbSet)]
+        #[dbset(table_name = "venues")]
+        pub struct Venue {
+            #[key]
+            id: i32,
+            #[dbset(flatten(street, city))]
+            address: Option<Address>,
+        }
+    "#;
+
+    compare_computed_to_expected(input_str, "");
+}
+
+#[test]
+fn flattened_field_with_default_fails() {
+    for attr in [
+        "#[db_default]",
+        r#"#[dbset(default = "Address::default()")]"#,
+    ] {
+        let input_str = format!(
+            r#"
+            #[derive(DbSet)]
+            #[dbset(table_name = "venues")]
+            pub struct Venue {{
+                #[key]
+                id: i32,
+                #[dbset(flatten(street, city))]
+                {attr}
+                address: Address,
+            }}
+        "#
+        );
+        let input_tokens = derive_input_from_string(&input_str).expect("Could not get tokens");
+        let err = check_flattened_fields(&input_tokens).expect_err("flatten with a default");
+        assert_eq!(
+            err.to_string(),
+            "a flattened field can not have a default or #[db_default]"
+        );
+    }
+}
+
+#[test]
+fn can_parse_counter_with_only_db_default_fields_into_insert_builder() -> Result<(), String> {
+    let input_str = r#"
+#[dbset(table_name = "counters")]
+pub struct Counter {
+    #[key]
+    #[auto]
+    id: i32,
+    #[db_default]
+    hits: i32,
+}
+    "#;
+
+    let output = r#"
+pub struct CounterDbSetInsertBuilder {
+    hits: Option<i32>,
+}
+impl CounterDbSetInsertBuilder {
+    pub fn new() -> CounterDbSetInsertBuilder {
+        Self { hits: None }
+    }
+}
+impl CounterDbSetInsertBuilder {
+    pub fn hits(self, hits: i32) -> CounterDbSetInsertBuilder {
+        CounterDbSetInsertBuilder {
+            hits: Some(hits),
+        }
+    }
+}
+impl CounterDbSetInsertBuilder {
+    fn build_insert_query<'q>(
+        self,
+        returning: &str,
+    ) -> sqlx::QueryBuilder<'q, sqlx::Postgres> {
+        let mut query_builder = sqlx::QueryBuilder::<
+            sqlx::Postgres,
+        >::new("INSERT INTO \"counters\"");
+        if self.hits.is_none() {
+            query_builder.push(" DEFAULT VALUES");
+            query_builder.push(returning);
+            return query_builder;
+        }
+        query_builder.push("(");
+        let mut columns = query_builder.separated(", ");
+        if self.hits.is_some() {
+            columns.push("\"hits\"");
+        }
+        query_builder.push(") VALUES (");
+        let mut values = query_builder.separated(", ");
+        if let Some(hits) = self.hits {
+            values.push_bind(hits);
+        }
+        query_builder.push(")");
+        query_builder.push(returning);
+        query_builder
+    }
+    pub async fn insert<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<Counter, sqlx::Error> {
+        let mut query_builder = self.build_insert_query(" RETURNING \"id\", \"hits\";");
+        query_builder.build_query_as::<Counter>().fetch_one(executor).await
+    }
+    pub async fn insert_returning_key<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<i32, sqlx::Error> {
+        let mut query_builder = self.build_insert_query(" RETURNING \"id\";");
+        query_builder.build_query_scalar::<i32>().fetch_one(executor).await
+    }
+    pub async fn insert_no_return<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<u64, sqlx::Error> {
+        let mut query_builder = self.build_insert_query(";");
+        let result = query_builder.build().execute(executor).await?;
+        Ok(result.rows_affected())
+    }
+}
+    "#;
+
+    compare_computed_to_expected(input_str, output);
+    Ok(())
+}
+
+#[test]
+fn can_parse_function_path_default_into_insert_builder() -> Result<(), String> {
+    let input_str = r#"
+#[dbset(table_name = "accounts")]
+pub struct Account {
+    #[key]
+    #[dbset(default = "uuid::Uuid::new_v4")]
+    id: uuid::Uuid,
+    #[dbset(default = "i64::MAX")]
+    credits: i64,
+}
+    "#;
+
+    let output = r#"
+pub struct AccountDbSetInsertBuilder {
+    id: Option<uuid::Uuid>,
+    credits: Option<i64>,
+}
+impl AccountDbSetInsertBuilder {
+    pub fn new() -> AccountDbSetInsertBuilder {
+        Self { id: None, credits: None }
+    }
+}
+impl AccountDbSetInsertBuilder {
+    pub fn id(self, id: uuid::Uuid) -> AccountDbSetInsertBuilder {
+        AccountDbSetInsertBuilder {
+            id: Some(id),
+            credits: self.credits,
+        }
+    }
+}
+impl AccountDbSetInsertBuilder {
+    pub fn credits(self, credits: i64) -> AccountDbSetInsertBuilder {
+        AccountDbSetInsertBuilder {
+            credits: Some(credits),
+            id: self.id,
+        }
+    }
+}
+impl AccountDbSetInsertBuilder {
+    pub async fn insert<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<Account, sqlx::Error> {
+        sqlx::query_as!(
+            Account,
+            "INSERT INTO \"accounts\"(\"id\", \"credits\") VALUES ($1, $2) RETURNING \"id\", \"credits\";",
+            self.id.or_else(|| Some(uuid::Uuid::new_v4())), self.credits.or_else(||
+            Some(i64::MAX)),
+        )
+            .fetch_one(executor)
+            .await
+    }
+    pub async fn insert_returning_key<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<uuid::Uuid, sqlx::Error> {
+        sqlx::query_scalar!(
+            "INSERT INTO \"accounts\"(\"id\", \"credits\") VALUES ($1, $2) RETURNING \"id\";",
+            self.id.or_else(|| Some(uuid::Uuid::new_v4())), self.credits.or_else(||
+            Some(i64::MAX)),
+        )
+            .fetch_one(executor)
+            .await
+    }
+    pub async fn insert_no_return<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "INSERT INTO \"accounts\"(\"id\", \"credits\") VALUES ($1, $2);", self.id
+            .or_else(|| Some(uuid::Uuid::new_v4())), self.credits.or_else(||
+            Some(i64::MAX)),
+        )
+            .execute(executor)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
+    "#;
+
+    compare_computed_to_expected(input_str, output);
+    Ok(())
+}
+
+#[test]
+fn can_parse_optional_field_default_into_insert_builder() -> Result<(), String> {
+    let input_str = r#"
+#[dbset(table_name = "accounts")]
+pub struct Account {
+    #[key]
+    id: i32,
+    #[dbset(default = "\"guest\".to_string()")]
+    nickname: Option<String>,
+}
+    "#;
+
+    let output = r#"
+pub struct AccountDbSetInsertBuilder<Id = NotSet> {
+    id: Option<i32>,
+    nickname: Option<String>,
+    _id: std::marker::PhantomData<Id>,
+}
+impl AccountDbSetInsertBuilder {
+    pub fn new() -> AccountDbSetInsertBuilder<NotSet> {
+        Self {
+            id: None,
+            nickname: None,
+            _id: std::marker::PhantomData::<NotSet>,
+        }
+    }
+}
+impl AccountDbSetInsertBuilder<NotSet> {
+    pub fn id(self, id: i32) -> AccountDbSetInsertBuilder<Set> {
+        AccountDbSetInsertBuilder {
+            id: Some(id),
+            nickname: self.nickname,
+            _id: std::marker::PhantomData::<Set>,
+        }
+    }
+}
+impl<Id> AccountDbSetInsertBuilder<Id> {
+    pub fn nickname(self, nickname: String) -> AccountDbSetInsertBuilder<Id> {
+        AccountDbSetInsertBuilder {
+            nickname: Some(nickname),
+            id: self.id,
+            _id: self._id,
+        }
+    }
+}
+impl AccountDbSetInsertBuilder<Set> {
+    pub async fn insert<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<Account, sqlx::Error> {
+        sqlx::query_as!(
+            Account,
+            "INSERT INTO \"accounts\"(\"id\", \"nickname\") VALUES ($1, $2) RETURNING \"id\", \"nickname\";",
+            self.id, self.nickname.or_else(|| Some("guest".to_string())),
+        )
+            .fetch_one(executor)
+            .await
+    }
+    pub async fn insert_returning_key<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<i32, sqlx::Error> {
+        sqlx::query_scalar!(
+            "INSERT INTO \"accounts\"(\"id\", \"nickname\") VALUES ($1, $2) RETURNING \"id\";",
+            self.id, self.nickname.or_else(|| Some("guest".to_string())),
+        )
+            .fetch_one(executor)
+            .await
+    }
+    pub async fn insert_no_return<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "INSERT INTO \"accounts\"(\"id\", \"nickname\") VALUES ($1, $2);", self.id,
+            self.nickname.or_else(|| Some("guest".to_string())),
+        )
+            .execute(executor)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
+    "#;
+
+    compare_computed_to_expected(input_str, output);
+    Ok(())
+}
+
+#[test]
+fn can_parse_enum_variant_default_into_insert_builder() -> Result<(), String> {
+    let input_str = r#"
+#[dbset(table_name = "users")]
+pub struct User {
+    #[key]
+    id: String,
+    #[custom_enum]
+    #[dbset(default = "UserStatus::Unverified")]
+    status: UserStatus,
+}
+    "#;
+
+    let output = r#"
+pub struct UserDbSetInsertBuilder<Id = NotSet> {
+    id: Option<String>,
+    status: Option<UserStatus>,
+    _id: std::marker::PhantomData<Id>,
+}
+impl UserDbSetInsertBuilder {
+    pub fn new() -> UserDbSetInsertBuilder<NotSet> {
+        Self {
+            id: None,
+            status: None,
+            _id: std::marker::PhantomData::<NotSet>,
+        }
+    }
+}
+impl UserDbSetInsertBuilder<NotSet> {
+    pub fn id(self, id: String) -> UserDbSetInsertBuilder<Set> {
+        UserDbSetInsertBuilder {
+            id: Some(id),
+            status: self.status,
+            _id: std::marker::PhantomData::<Set>,
+        }
+    }
+}
+impl<Id> UserDbSetInsertBuilder<Id> {
+    pub fn status(self, status: UserStatus) -> UserDbSetInsertBuilder<Id> {
+        UserDbSetInsertBuilder {
+            status: Some(status),
+            id: self.id,
+            _id: self._id,
+        }
+    }
+}
+impl UserDbSetInsertBuilder<Set> {
+    pub async fn insert<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<User, sqlx::Error> {
+        sqlx::query_as!(
+            User,
+            "INSERT INTO \"users\"(\"id\", \"status\") VALUES ($1, $2) RETURNING \"id\", \"status\" AS \"status:UserStatus\";",
+            self.id, self.status.or_else(|| Some(UserStatus::Unverified)) as Option <
+            UserStatus >,
+        )
+            .fetch_one(executor)
+            .await
+    }
+    pub async fn insert_returning_key<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<String, sqlx::Error> {
+        sqlx::query_scalar!(
+            "INSERT INTO \"users\"(\"id\", \"status\") VALUES ($1, $2) RETURNING \"id\";",
+            self.id, self.status.or_else(|| Some(UserStatus::Unverified)) as Option <
+            UserStatus >,
+        )
+            .fetch_one(executor)
+            .await
+    }
+    pub async fn insert_no_return<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "INSERT INTO \"users\"(\"id\", \"status\") VALUES ($1, $2);", self.id, self
+            .status.or_else(|| Some(UserStatus::Unverified)) as Option < UserStatus >,
+        )
+            .execute(executor)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
     "#;
 
     compare_computed_to_expected(input_str, output);
@@ -4,18 +4,25 @@ use syn::{Attribute, DeriveInput, Type};
 
 use crate::{
     common::utils::{
-        get_all_fields, get_key_fields, get_query_fields_string, get_unique_fields,
-        is_custom_enum_attr,
+        get_all_fields, get_column_sql, get_json_arg, get_key_fields, get_query_fields_string,
+        get_row_fields_string, get_soft_delete_field, get_soft_delete_where_clause,
+        get_unique_fields, is_custom_enum_attr, is_flattened_field,
     },
     utils,
 };
 
+use crate::modules::from_row::get_query_as_parts;
+use crate::modules::update_query_builder::get_many_update_builder_struct_name;
+
 use super::utils::{
-    get_many_query_builder_methods, get_many_query_builder_struct_fields,
+    get_json_filters, get_many_query_builder_methods, get_many_query_builder_push_filter_fields,
+    get_many_query_builder_push_filters, get_many_query_builder_struct_fields,
     get_many_query_builder_struct_fields_initial, get_many_query_builder_struct_name,
+    has_relation_filters, JsonFilterKind,
 };
 
 pub fn get_query_builder(input: &DeriveInput) -> proc_macro2::TokenStream {
+    let (query_as, query_as_struct, query_as_map) = get_query_as_parts(input);
     let struct_name = utils::get_struct_name(input);
     let table_name = utils::get_table_name(input);
     let query_builder_struct_name = get_many_query_builder_struct_name(input);
@@ -26,10 +33,18 @@ pub fn get_query_builder(input: &DeriveInput) -> proc_macro2::TokenStream {
     let key_fields = get_key_fields(input);
     let all_fields = get_all_fields(input);
     let query_fields_string = get_query_fields_string(input);
+    let row_fields_string = get_row_fields_string(input);
+    let many_update_builder_struct_name = get_many_update_builder_struct_name(input);
+    let soft_delete_column = get_soft_delete_field(input)
+        .map(|(soft_delete_name, _)| get_column_sql(input, soft_delete_name));
+    let has_relation_filters = has_relation_filters(input);
 
     let fields_to_include: Vec<(&Ident, &Type, &Vec<Attribute>)> = {
         let mut fields_to_include = vec![];
         for field in all_fields.clone() {
+            if is_flattened_field(field.2) {
+                continue;
+            }
             if unique_fields.iter().any(|(ufn, _)| *ufn == field.0) {
                 continue;
             }
@@ -41,30 +56,21 @@ pub fn get_query_builder(input: &DeriveInput) -> proc_macro2::TokenStream {
         fields_to_include
     };
 
-    let query_builder_fetch = {
-        let query_builder_where_fields = fields_to_include
+    let (query_builder_where_fields, params_count, query_args) = {
+        let mut query_builder_where_fields = fields_to_include
             .iter()
             .enumerate()
             .map(|(index, (field_name, _, _))| {
                 format!(
                     "({} = ${} or ${} is null)",
-                    field_name,
+                    get_column_sql(input, field_name),
                     index + 1,
                     index + 1
                 )
             })
-            .collect::<Vec<_>>()
-            .join(" AND ");
-
-        let full_where_clause = if !query_builder_where_fields.is_empty() {
-            format!("WHERE {query_builder_where_fields}")
-        } else {
-            String::new()
-        };
-
-        let query = format!("SELECT {query_fields_string} FROM {table_name} {full_where_clause}");
+            .collect::<Vec<_>>();
 
-        let query_args = fields_to_include
+        let mut query_args = fields_to_include
             .iter()
             .map(|(field_name, field_type, attrs)| {
                 let is_custom_enum = attrs.iter().any(is_custom_enum_attr);
@@ -73,23 +79,130 @@ pub fn get_query_builder(input: &DeriveInput) -> proc_macro2::TokenStream {
                         self.#field_name as Option<#field_type>,
                     }
                 } else {
+                    let value = get_json_arg(attrs, quote! { self.#field_name }, true);
                     quote! {
-                        self.#field_name,
+                        #value,
                     }
                 }
-            });
+            })
+            .collect::<Vec<_>>();
+
+        // JSONB filters are bound after the field filters, path_eq taking two params
+        let mut param_index = fields_to_include.len();
+        for json_filter in get_json_filters(input) {
+            let name = &json_filter.name;
+            let column = get_column_sql(input, json_filter.field_name);
+            param_index += 1;
+            match json_filter.kind {
+                JsonFilterKind::Contains => {
+                    query_builder_where_fields.push(format!(
+                        "({column} @> ${param_index} or ${param_index} is null)"
+                    ));
+                    query_args.push(quote! { self.#name, });
+                }
+                JsonFilterKind::HasKey => {
+                    query_builder_where_fields.push(format!(
+                        "({column} ? ${param_index} or ${param_index} is null)"
+                    ));
+                    query_args.push(quote! { self.#name, });
+                }
+                JsonFilterKind::PathEq => {
+                    let value_index = param_index + 1;
+                    query_builder_where_fields.push(format!(
+                        "({column} #> ${param_index} = ${value_index} or ${param_index} is null)"
+                    ));
+                    query_args.push(quote! {
+                        self.#name.as_ref().map(|(path, _)| path.as_slice()),
+                        self.#name.as_ref().map(|(_, value)| value),
+                    });
+                    param_index = value_index;
+                }
+            }
+        }
+
+        (query_builder_where_fields, param_index, query_args)
+    };
+
+    let get_full_where_clause = |extra_where_field: Option<String>| {
+        let where_fields = query_builder_where_fields
+            .iter()
+            .cloned()
+            .chain(extra_where_field)
+            .collect::<Vec<_>>()
+            .join(" AND ");
+
+        if !where_fields.is_empty() {
+            format!("WHERE {where_fields}")
+        } else {
+            String::new()
+        }
+    };
+
+    // Relation filters are closures pushing subqueries, so with any of them set the
+    // query is built at runtime instead of going through the query! macros
+    let push_filters = get_many_query_builder_push_filters(input, "");
+    let get_relation_filtered_query =
+        |query_start: String,
+         query_end: Option<String>,
+         deleted_filter: Option<bool>,
+         run_query: proc_macro2::TokenStream| {
+            if !has_relation_filters {
+                return quote! {};
+            }
+            let take_filters = match deleted_filter {
+                Some(deleted_filter) => quote! {
+                    let mut filters = self;
+                    filters.deleted_filter = Some(#deleted_filter);
+                },
+                None => quote! { let filters = self; },
+            };
+            let push_query_end =
+                query_end.map(|query_end| quote! { query_builder.push(#query_end); });
+            quote! {
+                if !self.relation_filters.is_empty() {
+                    let mut query_builder = sqlx::QueryBuilder::<sqlx::Postgres>::new(#query_start);
+                    #take_filters
+                    #push_filters
+                    #push_query_end
+                    #run_query
+                }
+            }
+        };
+
+    let query_builder_fetch = {
+        // Soft deleted rows are filtered on deleted_filter, bound after the field filters
+        let full_where_clause = get_full_where_clause(
+            soft_delete_column
+                .as_deref()
+                .map(|column| get_soft_delete_where_clause(column, params_count + 1)),
+        );
+        let query = format!("SELECT {query_fields_string} FROM {table_name} {full_where_clause}");
+        let deleted_filter_arg = soft_delete_column
+            .as_ref()
+            .map(|_| quote! { self.deleted_filter, });
+        let relation_filtered_fetch = get_relation_filtered_query(
+            format!("SELECT {row_fields_string} FROM {table_name}"),
+            None,
+            None,
+            quote! {
+                return query_builder.build_query_as::<#struct_name>().fetch_all(executor).await;
+            },
+        );
 
         let res = quote! {
             pub async fn fetch_all<'e, E: sqlx::PgExecutor<'e>>(
                 self,
                 executor: E,
             ) -> Result<Vec<#struct_name>, sqlx::Error> {
+                #relation_filtered_fetch
 
-                sqlx::query_as!(
-                    #struct_name,
+                #query_as(
+                    #query_as_struct
                     #query,
                     #(#query_args)*
+                    #deleted_filter_arg
                 )
+                #query_as_map
                 .fetch_all(executor)
                 .await
             }
@@ -99,8 +212,114 @@ pub fn get_query_builder(input: &DeriveInput) -> proc_macro2::TokenStream {
         res
     };
 
+    let query_builder_delete = {
+        // With #[soft_delete] rows are marked as deleted rather than removed
+        let query = match &soft_delete_column {
+            Some(soft_delete_column) => {
+                let full_where_clause =
+                    get_full_where_clause(Some(format!("{soft_delete_column} IS NULL")));
+                format!("UPDATE {table_name} SET {soft_delete_column} = now() {full_where_clause}")
+            }
+            None => format!("DELETE FROM {table_name} {}", get_full_where_clause(None)),
+        };
+        let returning_query = format!("{query} RETURNING {query_fields_string}");
+        let (delete_start, deleted_filter) = match &soft_delete_column {
+            Some(soft_delete_column) => (
+                format!("UPDATE {table_name} SET {soft_delete_column} = now()"),
+                Some(false),
+            ),
+            None => (format!("DELETE FROM {table_name}"), None),
+        };
+        let relation_filtered_delete = get_relation_filtered_query(
+            delete_start.clone(),
+            None,
+            deleted_filter,
+            quote! {
+                let result = query_builder.build().execute(executor).await?;
+                return Ok(result.rows_affected());
+            },
+        );
+        let relation_filtered_delete_returning = get_relation_filtered_query(
+            delete_start,
+            Some(format!(" RETURNING {row_fields_string}")),
+            deleted_filter,
+            quote! {
+                return query_builder.build_query_as::<#struct_name>().fetch_all(executor).await;
+            },
+        );
+
+        quote! {
+            pub async fn delete<'e, E: sqlx::PgExecutor<'e>>(
+                self,
+                executor: E,
+            ) -> Result<u64, sqlx::Error> {
+                #relation_filtered_delete
+
+                let result = sqlx::query!(
+                    #query,
+                    #(#query_args)*
+                )
+                .execute(executor)
+                .await?;
+                Ok(result.rows_affected())
+            }
+
+            pub async fn delete_returning<'e, E: sqlx::PgExecutor<'e>>(
+                self,
+                executor: E,
+            ) -> Result<Vec<#struct_name>, sqlx::Error> {
+                #relation_filtered_delete_returning
+
+                #query_as(
+                    #query_as_struct
+                    #returning_query,
+                    #(#query_args)*
+                )
+                #query_as_map
+                .fetch_all(executor)
+                .await
+            }
+        }
+    };
+
+    let query_builder_restore = match &soft_delete_column {
+        Some(soft_delete_column) => {
+            let full_where_clause =
+                get_full_where_clause(Some(format!("{soft_delete_column} IS NOT NULL")));
+            let query =
+                format!("UPDATE {table_name} SET {soft_delete_column} = NULL {full_where_clause}");
+            let relation_filtered_restore = get_relation_filtered_query(
+                format!("UPDATE {table_name} SET {soft_delete_column} = NULL"),
+                None,
+                Some(true),
+                quote! {
+                    let result = query_builder.build().execute(executor).await?;
+                    return Ok(result.rows_affected());
+                },
+            );
+
+            quote! {
+                pub async fn restore<'e, E: sqlx::PgExecutor<'e>>(
+                    self,
+                    executor: E,
+                ) -> Result<u64, sqlx::Error> {
+                    #relation_filtered_restore
+
+                    let result = sqlx::query!(
+                        #query,
+                        #(#query_args)*
+                    )
+                    .execute(executor)
+                    .await?;
+                    Ok(result.rows_affected())
+                }
+            }
+        }
+        None => quote! {},
+    };
+
     quote! {
-        pub struct #query_builder_struct_name {
+        pub struct #query_builder_struct_name<Filtered = NotSet> {
             #(#query_builder_struct_fields),*
         }
 
@@ -110,8 +329,54 @@ pub fn get_query_builder(input: &DeriveInput) -> proc_macro2::TokenStream {
                 #(#query_builder_struct_fields_initial),*
                 }
             }
+        }
+
+        impl<Filtered> #query_builder_struct_name<Filtered> {
             #(#query_builder_methods)*
             #query_builder_fetch
         }
+
+        // Bulk writes need a filter, or an explicit all_rows_i_am_sure()
+        impl #query_builder_struct_name<Set> {
+            #query_builder_delete
+            #query_builder_restore
+
+            pub fn update(self) -> #many_update_builder_struct_name {
+                #many_update_builder_struct_name::new(self)
+            }
+        }
+    }
+}
+
+// Lets another set's many() filter on this one through `column IN (SELECT key FROM table WHERE ..)`,
+// with `key` unquoted as given by the `{FIELD}_COLUMN` consts. Any set can be the target of a
+// belongs_to filter, so this is generated whether or not the set has relations of its own.
+pub fn get_subquery_filter(input: &DeriveInput) -> proc_macro2::TokenStream {
+    let table_name = utils::get_table_name(input);
+    let query_builder_struct_name = get_many_query_builder_struct_name(input);
+    let subquery_start = format!("{{}} IN (SELECT \"{{}}\" FROM {table_name} WHERE TRUE");
+    let push_subquery_filter_fields =
+        get_many_query_builder_push_filter_fields(input, "", quote! { &mut *where_fields });
+    let take_subquery_filters = if push_subquery_filter_fields.is_empty() {
+        quote! {}
+    } else {
+        quote! { let filters = self; }
+    };
+
+    quote! {
+        impl<Filtered> #query_builder_struct_name<Filtered> {
+            #[doc(hidden)]
+            pub fn push_as_subquery(
+                self,
+                where_fields: &mut sqlx::query_builder::Separated<'_, '_, sqlx::Postgres, &'static str>,
+                column: &str,
+                key: &str,
+            ) {
+                where_fields.push(format!(#subquery_start, column, key));
+                #take_subquery_filters
+                #push_subquery_filter_fields
+                where_fields.push_unseparated(")");
+            }
+        }
     }
 }
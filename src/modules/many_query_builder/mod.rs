@@ -3,3 +3,4 @@ mod many_query_builder;
 pub mod test_many_query_builder;
 pub mod utils;
 pub use many_query_builder::get_query_builder;
+pub use many_query_builder::get_subquery_filter;
@@ -2,7 +2,7 @@ use pretty_assertions::assert_eq;
 
 use crate::{
     common::utils::{derive_input_from_string, pretty_print_tokenstream, tokenstream_from_string},
-    modules::many_query_builder::get_query_builder,
+    modules::many_query_builder::{get_query_builder, get_subquery_filter},
 };
 
 pub fn compare_computed_to_expected(input_string: &str, output_string: &str) {
@@ -18,6 +18,19 @@ pub fn compare_computed_to_expected(input_string: &str, output_string: &str) {
     assert_eq!(pretty_out.to_string(), pretty_expected);
 }
 
+pub fn compare_subquery_filter_computed_to_expected(input_string: &str, output_string: &str) {
+    let input_tokens = derive_input_from_string(input_string).expect("Could not get tokens");
+    let out_tokens = get_subquery_filter(&input_tokens);
+    let pretty_out = pretty_print_tokenstream(out_tokens);
+    let tokenstream = tokenstream_from_string(output_string);
+    assert!(
+        tokenstream.is_ok(),
+        "Could not parse output: {output_string}"
+    );
+    let pretty_expected = pretty_print_tokenstream(tokenstream.expect("couldnt unw"));
+    assert_eq!(pretty_out.to_string(), pretty_expected);
+}
+
 #[test]
 fn can_parse_user_struct_with_unique_and_key_into_one_builder() -> Result<(), String> {
     let input_str = r#"
@@ -34,21 +47,41 @@ pub struct User {
 
     let output = r#"
 
-pub struct UserDbSetManyQueryBuilder {
+pub struct UserDbSetManyQueryBuilder<Filtered = NotSet> {
     name: Option<String>,
     details: Option<String>,
+    _filtered: std::marker::PhantomData<Filtered>,
 }
 impl UserDbSetManyQueryBuilder {
     pub fn new() -> Self {
-        Self { name: None, details: None }
+        Self {
+            name: None,
+            details: None,
+            _filtered: std::marker::PhantomData::<NotSet>,
+        }
     }
-    pub fn name_eq(mut self, value: String) -> Self {
-        self.name = Some(value);
-        self
+}
+impl<Filtered> UserDbSetManyQueryBuilder<Filtered> {
+    pub fn name_eq(self, value: String) -> UserDbSetManyQueryBuilder<Set> {
+        UserDbSetManyQueryBuilder {
+            name: Some(value),
+            details: self.details,
+            _filtered: std::marker::PhantomData::<Set>,
+        }
     }
-    pub fn details_eq(mut self, value: String) -> Self {
-        self.details = Some(value);
-        self
+    pub fn details_eq(self, value: String) -> UserDbSetManyQueryBuilder<Set> {
+        UserDbSetManyQueryBuilder {
+            details: Some(value),
+            name: self.name,
+            _filtered: std::marker::PhantomData::<Set>,
+        }
+    }
+    pub fn all_rows_i_am_sure(self) -> UserDbSetManyQueryBuilder<Set> {
+        UserDbSetManyQueryBuilder {
+            name: self.name,
+            details: self.details,
+            _filtered: std::marker::PhantomData::<Set>,
+        }
     }
     pub async fn fetch_all<'e, E: sqlx::PgExecutor<'e>>(
         self,
@@ -56,12 +89,41 @@ impl UserDbSetManyQueryBuilder {
     ) -> Result<Vec<User>, sqlx::Error> {
         sqlx::query_as!(
             User,
-            "SELECT id, name, details, email FROM users WHERE (name = $1 or $1 is null) AND (details = $2 or $2 is null)",
+            "SELECT \"id\", \"name\", \"details\", \"email\" FROM \"users\" WHERE (\"name\" = $1 or $1 is null) AND (\"details\" = $2 or $2 is null)",
             self.name, self.details,
         )
             .fetch_all(executor)
             .await
     }
+}
+impl UserDbSetManyQueryBuilder<Set> {
+    pub async fn delete<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM \"users\" WHERE (\"name\" = $1 or $1 is null) AND (\"details\" = $2 or $2 is null)",
+            self.name, self.details,
+        )
+            .execute(executor)
+            .await?;
+        Ok(result.rows_affected())
+    }
+    pub async fn delete_returning<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<Vec<User>, sqlx::Error> {
+        sqlx::query_as!(
+            User,
+            "DELETE FROM \"users\" WHERE (\"name\" = $1 or $1 is null) AND (\"details\" = $2 or $2 is null) RETURNING \"id\", \"name\", \"details\", \"email\"",
+            self.name, self.details,
+        )
+            .fetch_all(executor)
+            .await
+    }
+    pub fn update(self) -> UserDbSetManyUpdateBuilder {
+        UserDbSetManyUpdateBuilder::new(self)
+    }
 }
     "#;
 
@@ -87,26 +149,54 @@ pub struct User {
 
     let output = r#"
 
-pub struct UserDbSetManyQueryBuilder {
+pub struct UserDbSetManyQueryBuilder<Filtered = NotSet> {
     name: Option<String>,
     details: Option<String>,
     status: Option<UserStatus>,
+    _filtered: std::marker::PhantomData<Filtered>,
 }
 impl UserDbSetManyQueryBuilder {
     pub fn new() -> Self {
-        Self { name: None, details: None, status: None }
+        Self {
+            name: None,
+            details: None,
+            status: None,
+            _filtered: std::marker::PhantomData::<NotSet>,
+        }
     }
-    pub fn name_eq(mut self, value: String) -> Self {
-        self.name = Some(value);
-        self
+}
+impl<Filtered> UserDbSetManyQueryBuilder<Filtered> {
+    pub fn name_eq(self, value: String) -> UserDbSetManyQueryBuilder<Set> {
+        UserDbSetManyQueryBuilder {
+            name: Some(value),
+            details: self.details,
+            status: self.status,
+            _filtered: std::marker::PhantomData::<Set>,
+        }
     }
-    pub fn details_eq(mut self, value: String) -> Self {
-        self.details = Some(value);
-        self
+    pub fn details_eq(self, value: String) -> UserDbSetManyQueryBuilder<Set> {
+        UserDbSetManyQueryBuilder {
+            details: Some(value),
+            name: self.name,
+            status: self.status,
+            _filtered: std::marker::PhantomData::<Set>,
+        }
     }
-    pub fn status_eq(mut self, value: UserStatus) -> Self {
-        self.status = Some(value);
-        self
+    pub fn status_eq(self, value: UserStatus) -> UserDbSetManyQueryBuilder<Set> {
+        UserDbSetManyQueryBuilder {
+            status: Some(value),
+            name: self.name,
+            details: self.details,
+            _filtered: std::marker::PhantomData::<Set>,
+        }
+    }
+    pub fn all_rows_i_am_sure(self) -> UserDbSetManyQueryBuilder<Set> {
+        UserDbSetManyQueryBuilder {
+            name: self.name,
+            details: self.details,
+            status: self.status,
+            _filtered: std::marker::PhantomData::<Set>,
+        }
     }
     pub async fn fetch_all<'e, E: sqlx::PgExecutor<'e>>(
         self,
@@ -114,12 +204,41 @@ impl UserDbSetManyQueryBuilder {
     ) -> Result<Vec<User>, sqlx::Error> {
         sqlx::query_as!(
             User,
-            "SELECT id, name, details, email, status AS \"status:UserStatus\" FROM users WHERE (name = $1 or $1 is null) AND (details = $2 or $2 is null) AND (status = $3 or $3 is null)",
+            "SELECT \"id\", \"name\", \"details\", \"email\", \"status\" AS \"status:UserStatus\" FROM \"users\" WHERE (\"name\" = $1 or $1 is null) AND (\"details\" = $2 or $2 is null) AND (\"status\" = $3 or $3 is null)",
             self.name, self.details, self.status as Option<UserStatus>,
         )
             .fetch_all(executor)
             .await
     }
+}
+impl UserDbSetManyQueryBuilder<Set> {
+    pub async fn delete<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM \"users\" WHERE (\"name\" = $1 or $1 is null) AND (\"details\" = $2 or $2 is null) AND (\"status\" = $3 or $3 is null)",
+            self.name, self.details, self.status as Option < UserStatus >,
+        )
+            .execute(executor)
+            .await?;
+        Ok(result.rows_affected())
+    }
+    pub async fn delete_returning<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<Vec<User>, sqlx::Error> {
+        sqlx::query_as!(
+            User,
+            "DELETE FROM \"users\" WHERE (\"name\" = $1 or $1 is null) AND (\"details\" = $2 or $2 is null) AND (\"status\" = $3 or $3 is null) RETURNING \"id\", \"name\", \"details\", \"email\", \"status\" AS \"status:UserStatus\"",
+            self.name, self.details, self.status as Option < UserStatus >,
+        )
+            .fetch_all(executor)
+            .await
+    }
+    pub fn update(self) -> UserDbSetManyUpdateBuilder {
+        UserDbSetManyUpdateBuilder::new(self)
+    }
 }
     "#;
 
@@ -141,37 +260,835 @@ pub struct FavouritedProduct {
 
     let output = r#"
 
-pub struct FavouritedProductDbSetManyQueryBuilder {
+pub struct FavouritedProductDbSetManyQueryBuilder<Filtered = NotSet> {
     product_id: Option<uuid::Uuid>,
     user_id: Option<uuid::Uuid>,
+    _filtered: std::marker::PhantomData<Filtered>,
 }
-
 impl FavouritedProductDbSetManyQueryBuilder {
     pub fn new() -> Self {
         Self {
-    product_id: None,
-    user_id: None,
+            product_id: None,
+            user_id: None,
+            _filtered: std::marker::PhantomData::<NotSet>,
+        }
+    }
+}
+impl<Filtered> FavouritedProductDbSetManyQueryBuilder<Filtered> {
+    pub fn all_rows_i_am_sure(self) -> FavouritedProductDbSetManyQueryBuilder<Set> {
+        FavouritedProductDbSetManyQueryBuilder {
+            product_id: self.product_id,
+            user_id: self.user_id,
+            _filtered: std::marker::PhantomData::<Set>,
         }
     }
-
     pub async fn fetch_all<'e, E: sqlx::PgExecutor<'e>>(
         self,
         executor: E,
     ) -> Result<Vec<FavouritedProduct>, sqlx::Error> {
         sqlx::query_as!(
             FavouritedProduct,
-            "SELECT product_id, user_id FROM favourite_products WHERE (product_id = $1 or $1 is null) AND (user_id = $2 or $2 is null)",
+            "SELECT \"product_id\", \"user_id\" FROM \"favourite_products\" WHERE (\"product_id\" = $1 or $1 is null) AND (\"user_id\" = $2 or $2 is null)",
             self.product_id, self.user_id,
         )
             .fetch_all(executor)
             .await
+    }
+}
+impl FavouritedProductDbSetManyQueryBuilder<Set> {
+    pub async fn delete<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM \"favourite_products\" WHERE (\"product_id\" = $1 or $1 is null) AND (\"user_id\" = $2 or $2 is null)",
+            self.product_id, self.user_id,
+        )
+            .execute(executor)
+            .await?;
+        Ok(result.rows_affected())
+    }
+    pub async fn delete_returning<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<Vec<FavouritedProduct>, sqlx::Error> {
+        sqlx::query_as!(
+            FavouritedProduct,
+            "DELETE FROM \"favourite_products\" WHERE (\"product_id\" = $1 or $1 is null) AND (\"user_id\" = $2 or $2 is null) RETURNING \"product_id\", \"user_id\"",
+            self.product_id, self.user_id,
+        )
+            .fetch_all(executor)
+            .await
+    }
+    pub fn update(self) -> FavouritedProductDbSetManyUpdateBuilder {
+        FavouritedProductDbSetManyUpdateBuilder::new(self)
+    }
+}
+    "#;
+
+    compare_computed_to_expected(input_str, output);
+    Ok(())
+}
+
+#[test]
+fn can_parse_soft_delete_post_into_many_builder() -> Result<(), String> {
+    let input_str = r#"
+        #[derive(DbSet)]
+        #[dbset(table_name = "posts")]
+        pub struct Post {
+            #[key]
+            id: i32,
+            #[unique]
+            slug: String,
+            title: String,
+            #[soft_delete]
+            deleted_at: Option<DateTime<Utc>>,
+        }
+    "#;
+
+    let output = r#"
+pub struct PostDbSetManyQueryBuilder<Filtered = NotSet> {
+    title: Option<String>,
+    deleted_at: Option<DateTime<Utc>>,
+    deleted_filter: Option<bool>,
+    _filtered: std::marker::PhantomData<Filtered>,
+}
+impl PostDbSetManyQueryBuilder {
+    pub fn new() -> Self {
+        Self {
+            title: None,
+            deleted_at: None,
+            deleted_filter: Some(false),
+            _filtered: std::marker::PhantomData::<NotSet>,
+        }
+    }
+}
+impl<Filtered> PostDbSetManyQueryBuilder<Filtered> {
+    pub fn title_eq(self, value: String) -> PostDbSetManyQueryBuilder<Set> {
+        PostDbSetManyQueryBuilder {
+            title: Some(value),
+            deleted_at: self.deleted_at,
+            deleted_filter: self.deleted_filter,
+            _filtered: std::marker::PhantomData::<Set>,
+        }
+    }
+    pub fn deleted_at_eq(self, value: DateTime<Utc>) -> PostDbSetManyQueryBuilder<Set> {
+        PostDbSetManyQueryBuilder {
+            deleted_at: Some(value),
+            title: self.title,
+            deleted_filter: self.deleted_filter,
+            _filtered: std::marker::PhantomData::<Set>,
+        }
+    }
+    pub fn with_deleted(mut self) -> Self {
+        self.deleted_filter = None;
+        self
+    }
+    pub fn only_deleted(mut self) -> Self {
+        self.deleted_filter = Some(true);
+        self
+    }
+    pub fn all_rows_i_am_sure(self) -> PostDbSetManyQueryBuilder<Set> {
+        PostDbSetManyQueryBuilder {
+            title: self.title,
+            deleted_at: self.deleted_at,
+            deleted_filter: self.deleted_filter,
+            _filtered: std::marker::PhantomData::<Set>,
+        }
+    }
+    pub async fn fetch_all<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<Vec<Post>, sqlx::Error> {
+        sqlx::query_as!(
+            Post,
+            "SELECT \"id\", \"slug\", \"title\", \"deleted_at\" FROM \"posts\" WHERE (\"title\" = $1 or $1 is null) AND (\"deleted_at\" = $2 or $2 is null) AND ((\"deleted_at\" IS NOT NULL) = $3 OR $3 IS NULL)",
+            self.title, self.deleted_at, self.deleted_filter,
+        )
+            .fetch_all(executor)
+            .await
+    }
+}
+impl PostDbSetManyQueryBuilder<Set> {
+    pub async fn delete<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "UPDATE \"posts\" SET \"deleted_at\" = now() WHERE (\"title\" = $1 or $1 is null) AND (\"deleted_at\" = $2 or $2 is null) AND \"deleted_at\" IS NULL",
+            self.title, self.deleted_at,
+        )
+            .execute(executor)
+            .await?;
+        Ok(result.rows_affected())
+    }
+    pub async fn delete_returning<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<Vec<Post>, sqlx::Error> {
+        sqlx::query_as!(
+            Post,
+            "UPDATE \"posts\" SET \"deleted_at\" = now() WHERE (\"title\" = $1 or $1 is null) AND (\"deleted_at\" = $2 or $2 is null) AND \"deleted_at\" IS NULL RETURNING \"id\", \"slug\", \"title\", \"deleted_at\"",
+            self.title, self.deleted_at,
+        )
+            .fetch_all(executor)
+            .await
+    }
+    pub async fn restore<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "UPDATE \"posts\" SET \"deleted_at\" = NULL WHERE (\"title\" = $1 or $1 is null) AND (\"deleted_at\" = $2 or $2 is null) AND \"deleted_at\" IS NOT NULL",
+            self.title, self.deleted_at,
+        )
+            .execute(executor)
+            .await?;
+        Ok(result.rows_affected())
+    }
+    pub fn update(self) -> PostDbSetManyUpdateBuilder {
+        PostDbSetManyUpdateBuilder::new(self)
+    }
+}
+    "#;
+
+    compare_computed_to_expected(input_str, output);
+    Ok(())
+}
+
+#[test]
+fn can_parse_player_relation_filters_into_many_builder() -> Result<(), String> {
+    let input_str = r#"
+        #[derive(DbSet)]
+        #[dbset(table_name = "players")]
+        #[relation(belongs_to = "Team", foreign_key = "team_id")]
+        pub struct Player {
+            #[key]
+            id: i32,
+            name: String,
+            team_id: i32,
+        }
+    "#;
+
+    let output = r#"
+pub struct PlayerDbSetManyQueryBuilder<Filtered = NotSet> {
+    name: Option<String>,
+    team_id: Option<i32>,
+    relation_filters: Vec<
+        Box<
+            dyn for<'qb, 'args> FnOnce(
+                &mut sqlx::query_builder::Separated<
+                    'qb,
+                    'args,
+                    sqlx::Postgres,
+                    &'static str,
+                >,
+                &str,
+            ) + Send,
+        >,
+    >,
+    _filtered: std::marker::PhantomData<Filtered>,
+}
+impl PlayerDbSetManyQueryBuilder {
+    pub fn new() -> Self {
+        Self {
+            name: None,
+            team_id: None,
+            relation_filters: Vec::new(),
+            _filtered: std::marker::PhantomData::<NotSet>,
+        }
+    }
+}
+impl<Filtered> PlayerDbSetManyQueryBuilder<Filtered> {
+    pub fn name_eq(self, value: String) -> PlayerDbSetManyQueryBuilder<Set> {
+        PlayerDbSetManyQueryBuilder {
+            name: Some(value),
+            team_id: self.team_id,
+            relation_filters: self.relation_filters,
+            _filtered: std::marker::PhantomData::<Set>,
+        }
+    }
+    pub fn team_id_eq(self, value: i32) -> PlayerDbSetManyQueryBuilder<Set> {
+        PlayerDbSetManyQueryBuilder {
+            team_id: Some(value),
+            name: self.name,
+            relation_filters: self.relation_filters,
+            _filtered: std::marker::PhantomData::<Set>,
+        }
+    }
+    pub fn team<RelatedFiltered: Send + 'static>(
+        self,
+        filter: impl FnOnce(
+            TeamDbSetManyQueryBuilder,
+        ) -> TeamDbSetManyQueryBuilder<RelatedFiltered>,
+    ) -> PlayerDbSetManyQueryBuilder<Set> {
+        let related_filters = filter(TeamDbSet::many());
+        let mut relation_filters = self.relation_filters;
+        relation_filters
+            .push(
+                Box::new(move |where_fields, column_prefix| {
+                    related_filters
+                        .push_as_subquery(
+                            where_fields,
+                            &format!("{}{}", column_prefix, "\"team_id\""),
+                            TeamDbSet::ID_COLUMN,
+                        )
+                }),
+            );
+        PlayerDbSetManyQueryBuilder {
+            name: self.name,
+            team_id: self.team_id,
+            relation_filters,
+            _filtered: std::marker::PhantomData::<Set>,
+        }
+    }
+    pub fn all_rows_i_am_sure(self) -> PlayerDbSetManyQueryBuilder<Set> {
+        PlayerDbSetManyQueryBuilder {
+            name: self.name,
+            team_id: self.team_id,
+            relation_filters: self.relation_filters,
+            _filtered: std::marker::PhantomData::<Set>,
+        }
+    }
+    pub async fn fetch_all<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<Vec<Player>, sqlx::Error> {
+        if !self.relation_filters.is_empty() {
+            let mut query_builder = sqlx::QueryBuilder::<
+                sqlx::Postgres,
+            >::new("SELECT \"id\", \"name\", \"team_id\" FROM \"players\"");
+            let filters = self;
+            if filters.name.is_some() || filters.team_id.is_some()
+                || !filters.relation_filters.is_empty()
+            {
+                query_builder.push(" WHERE ");
+            }
+            let mut where_fields = query_builder.separated(" AND ");
+            if let Some(name) = filters.name {
+                where_fields.push("\"name\" = ");
+                where_fields.push_bind_unseparated(name);
+            }
+            if let Some(team_id) = filters.team_id {
+                where_fields.push("\"team_id\" = ");
+                where_fields.push_bind_unseparated(team_id);
+            }
+            for relation_filter in filters.relation_filters {
+                relation_filter(&mut where_fields, "");
+            }
+            return query_builder.build_query_as::<Player>().fetch_all(executor).await;
+        }
+        sqlx::query_as!(
+            Player,
+            "SELECT \"id\", \"name\", \"team_id\" FROM \"players\" WHERE (\"name\" = $1 or $1 is null) AND (\"team_id\" = $2 or $2 is null)",
+            self.name, self.team_id,
+        )
+            .fetch_all(executor)
+            .await
+    }
+}
+impl PlayerDbSetManyQueryBuilder<Set> {
+    pub async fn delete<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<u64, sqlx::Error> {
+        if !self.relation_filters.is_empty() {
+            let mut query_builder = sqlx::QueryBuilder::<
+                sqlx::Postgres,
+            >::new("DELETE FROM \"players\"");
+            let filters = self;
+            if filters.name.is_some() || filters.team_id.is_some()
+                || !filters.relation_filters.is_empty()
+            {
+                query_builder.push(" WHERE ");
+            }
+            let mut where_fields = query_builder.separated(" AND ");
+            if let Some(name) = filters.name {
+                where_fields.push("\"name\" = ");
+                where_fields.push_bind_unseparated(name);
             }
+            if let Some(team_id) = filters.team_id {
+                where_fields.push("\"team_id\" = ");
+                where_fields.push_bind_unseparated(team_id);
+            }
+            for relation_filter in filters.relation_filters {
+                relation_filter(&mut where_fields, "");
+            }
+            let result = query_builder.build().execute(executor).await?;
+            return Ok(result.rows_affected());
+        }
+        let result = sqlx::query!(
+            "DELETE FROM \"players\" WHERE (\"name\" = $1 or $1 is null) AND (\"team_id\" = $2 or $2 is null)",
+            self.name, self.team_id,
+        )
+            .execute(executor)
+            .await?;
+        Ok(result.rows_affected())
+    }
+    pub async fn delete_returning<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<Vec<Player>, sqlx::Error> {
+        if !self.relation_filters.is_empty() {
+            let mut query_builder = sqlx::QueryBuilder::<
+                sqlx::Postgres,
+            >::new("DELETE FROM \"players\"");
+            let filters = self;
+            if filters.name.is_some() || filters.team_id.is_some()
+                || !filters.relation_filters.is_empty()
+            {
+                query_builder.push(" WHERE ");
+            }
+            let mut where_fields = query_builder.separated(" AND ");
+            if let Some(name) = filters.name {
+                where_fields.push("\"name\" = ");
+                where_fields.push_bind_unseparated(name);
+            }
+            if let Some(team_id) = filters.team_id {
+                where_fields.push("\"team_id\" = ");
+                where_fields.push_bind_unseparated(team_id);
+            }
+            for relation_filter in filters.relation_filters {
+                relation_filter(&mut where_fields, "");
+            }
+            query_builder.push(" RETURNING \"id\", \"name\", \"team_id\"");
+            return query_builder.build_query_as::<Player>().fetch_all(executor).await;
+        }
+        sqlx::query_as!(
+            Player,
+            "DELETE FROM \"players\" WHERE (\"name\" = $1 or $1 is null) AND (\"team_id\" = $2 or $2 is null) RETURNING \"id\", \"name\", \"team_id\"",
+            self.name, self.team_id,
+        )
+            .fetch_all(executor)
+            .await
+    }
+    pub fn update(self) -> PlayerDbSetManyUpdateBuilder {
+        PlayerDbSetManyUpdateBuilder::new(self)
+    }
+}
+    "#;
 
+    compare_computed_to_expected(input_str, output);
+    Ok(())
+}
 
+#[test]
+fn can_parse_renamed_columns_into_many_builder() -> Result<(), String> {
+    let input_str = r#"
+        #[derive(DbSet)]
+        #[dbset(table_name = "customers")]
+        #[dbset(rename_all = "camelCase")]
+        pub struct Customer {
+            #[key]
+            customer_id: i32,
+            display_name: String,
+            #[dbset(column = "kind")]
+            r#type: String,
+        }
+    "#;
+
+    let output = r#"
+pub struct CustomerDbSetManyQueryBuilder<Filtered = NotSet> {
+    display_name: Option<String>,
+    r#type: Option<String>,
+    _filtered: std::marker::PhantomData<Filtered>,
+}
+impl CustomerDbSetManyQueryBuilder {
+    pub fn new() -> Self {
+        Self {
+            display_name: None,
+            r#type: None,
+            _filtered: std::marker::PhantomData::<NotSet>,
+        }
+    }
+}
+impl<Filtered> CustomerDbSetManyQueryBuilder<Filtered> {
+    pub fn display_name_eq(self, value: String) -> CustomerDbSetManyQueryBuilder<Set> {
+        CustomerDbSetManyQueryBuilder {
+            display_name: Some(value),
+            r#type: self.r#type,
+            _filtered: std::marker::PhantomData::<Set>,
+        }
+    }
+    pub fn type_eq(self, value: String) -> CustomerDbSetManyQueryBuilder<Set> {
+        CustomerDbSetManyQueryBuilder {
+            r#type: Some(value),
+            display_name: self.display_name,
+            _filtered: std::marker::PhantomData::<Set>,
+        }
+    }
+    pub fn all_rows_i_am_sure(self) -> CustomerDbSetManyQueryBuilder<Set> {
+        CustomerDbSetManyQueryBuilder {
+            display_name: self.display_name,
+            r#type: self.r#type,
+            _filtered: std::marker::PhantomData::<Set>,
+        }
+    }
+    pub async fn fetch_all<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<Vec<Customer>, sqlx::Error> {
+        sqlx::query_as!(
+            Customer,
+            "SELECT \"customerId\" AS \"customer_id\", \"displayName\" AS \"display_name\", \"kind\" AS \"type\" FROM \"customers\" WHERE (\"displayName\" = $1 or $1 is null) AND (\"kind\" = $2 or $2 is null)",
+            self.display_name, self.r#type,
+        )
+            .fetch_all(executor)
+            .await
+    }
 }
+impl CustomerDbSetManyQueryBuilder<Set> {
+    pub async fn delete<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM \"customers\" WHERE (\"displayName\" = $1 or $1 is null) AND (\"kind\" = $2 or $2 is null)",
+            self.display_name, self.r#type,
+        )
+            .execute(executor)
+            .await?;
+        Ok(result.rows_affected())
+    }
+    pub async fn delete_returning<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<Vec<Customer>, sqlx::Error> {
+        sqlx::query_as!(
+            Customer,
+            "DELETE FROM \"customers\" WHERE (\"displayName\" = $1 or $1 is null) AND (\"kind\" = $2 or $2 is null) RETURNING \"customerId\" AS \"customer_id\", \"displayName\" AS \"display_name\", \"kind\" AS \"type\"",
+            self.display_name, self.r#type,
+        )
+            .fetch_all(executor)
+            .await
+    }
+    pub fn update(self) -> CustomerDbSetManyUpdateBuilder {
+        CustomerDbSetManyUpdateBuilder::new(self)
+    }
+}
+    "#;
 
+    compare_computed_to_expected(input_str, output);
+    Ok(())
+}
+
+#[test]
+#[should_panic(expected = "unknown rename_all \"camel\" on Customer")]
+fn unknown_rename_all_fails() {
+    let input_str = r#"
+        #[derive(DbSet)]
+        #[dbset(table_name = "customers")]
+        #[dbset(rename_all = "camel")]
+        pub struct Customer {
+            #[key]
+            customer_id: i32,
+            display_name: String,
+        }
+    "#;
+
+    compare_computed_to_expected(input_str, "");
+}
+
+#[test]
+fn can_parse_json_profile_into_many_builder() -> Result<(), String> {
+    let input_str = r#"
+    #[derive(DbSet, Debug, Clone)]
+    #[dbset(table_name = "profiles")]
+    pub struct Profile {
+        #[key]
+        id: i32,
+        #[json]
+        settings: Settings,
+        #[json]
+        metadata: Option<HashMap<String, String>>,
+    }
+    "#;
+
+    let output = r#"
+pub struct ProfileDbSetManyQueryBuilder<Filtered = NotSet> {
+    settings: Option<Settings>,
+    metadata: Option<HashMap<String, String>>,
+    settings_contains: Option<sqlx::types::JsonValue>,
+    settings_has_key: Option<String>,
+    settings_path_eq: Option<(Vec<String>, sqlx::types::JsonValue)>,
+    metadata_contains: Option<sqlx::types::JsonValue>,
+    metadata_has_key: Option<String>,
+    metadata_path_eq: Option<(Vec<String>, sqlx::types::JsonValue)>,
+    _filtered: std::marker::PhantomData<Filtered>,
+}
+impl ProfileDbSetManyQueryBuilder {
+    pub fn new() -> Self {
+        Self {
+            settings: None,
+            metadata: None,
+            settings_contains: None,
+            settings_has_key: None,
+            settings_path_eq: None,
+            metadata_contains: None,
+            metadata_has_key: None,
+            metadata_path_eq: None,
+            _filtered: std::marker::PhantomData::<NotSet>,
+        }
+    }
+}
+impl<Filtered> ProfileDbSetManyQueryBuilder<Filtered> {
+    pub fn settings_eq(self, value: Settings) -> ProfileDbSetManyQueryBuilder<Set> {
+        ProfileDbSetManyQueryBuilder {
+            settings: Some(value),
+            metadata: self.metadata,
+            settings_contains: self.settings_contains,
+            settings_has_key: self.settings_has_key,
+            settings_path_eq: self.settings_path_eq,
+            metadata_contains: self.metadata_contains,
+            metadata_has_key: self.metadata_has_key,
+            metadata_path_eq: self.metadata_path_eq,
+            _filtered: std::marker::PhantomData::<Set>,
+        }
+    }
+    pub fn metadata_eq(
+        self,
+        value: HashMap<String, String>,
+    ) -> ProfileDbSetManyQueryBuilder<Set> {
+        ProfileDbSetManyQueryBuilder {
+            metadata: Some(value),
+            settings: self.settings,
+            settings_contains: self.settings_contains,
+            settings_has_key: self.settings_has_key,
+            settings_path_eq: self.settings_path_eq,
+            metadata_contains: self.metadata_contains,
+            metadata_has_key: self.metadata_has_key,
+            metadata_path_eq: self.metadata_path_eq,
+            _filtered: std::marker::PhantomData::<Set>,
+        }
+    }
+    pub fn settings_contains(
+        self,
+        value: sqlx::types::JsonValue,
+    ) -> ProfileDbSetManyQueryBuilder<Set> {
+        ProfileDbSetManyQueryBuilder {
+            settings_contains: Some(value),
+            settings: self.settings,
+            metadata: self.metadata,
+            settings_has_key: self.settings_has_key,
+            settings_path_eq: self.settings_path_eq,
+            metadata_contains: self.metadata_contains,
+            metadata_has_key: self.metadata_has_key,
+            metadata_path_eq: self.metadata_path_eq,
+            _filtered: std::marker::PhantomData::<Set>,
+        }
+    }
+    pub fn settings_has_key(self, key: &str) -> ProfileDbSetManyQueryBuilder<Set> {
+        ProfileDbSetManyQueryBuilder {
+            settings_has_key: Some(key.to_string()),
+            settings: self.settings,
+            metadata: self.metadata,
+            settings_contains: self.settings_contains,
+            settings_path_eq: self.settings_path_eq,
+            metadata_contains: self.metadata_contains,
+            metadata_has_key: self.metadata_has_key,
+            metadata_path_eq: self.metadata_path_eq,
+            _filtered: std::marker::PhantomData::<Set>,
+        }
+    }
+    pub fn settings_path_eq(
+        self,
+        path: &[&str],
+        value: sqlx::types::JsonValue,
+    ) -> ProfileDbSetManyQueryBuilder<Set> {
+        ProfileDbSetManyQueryBuilder {
+            settings_path_eq: Some((
+                path.iter().map(|key| key.to_string()).collect(),
+                value,
+            )),
+            settings: self.settings,
+            metadata: self.metadata,
+            settings_contains: self.settings_contains,
+            settings_has_key: self.settings_has_key,
+            metadata_contains: self.metadata_contains,
+            metadata_has_key: self.metadata_has_key,
+            metadata_path_eq: self.metadata_path_eq,
+            _filtered: std::marker::PhantomData::<Set>,
+        }
+    }
+    pub fn metadata_contains(
+        self,
+        value: sqlx::types::JsonValue,
+    ) -> ProfileDbSetManyQueryBuilder<Set> {
+        ProfileDbSetManyQueryBuilder {
+            metadata_contains: Some(value),
+            settings: self.settings,
+            metadata: self.metadata,
+            settings_contains: self.settings_contains,
+            settings_has_key: self.settings_has_key,
+            settings_path_eq: self.settings_path_eq,
+            metadata_has_key: self.metadata_has_key,
+            metadata_path_eq: self.metadata_path_eq,
+            _filtered: std::marker::PhantomData::<Set>,
+        }
+    }
+    pub fn metadata_has_key(self, key: &str) -> ProfileDbSetManyQueryBuilder<Set> {
+        ProfileDbSetManyQueryBuilder {
+            metadata_has_key: Some(key.to_string()),
+            settings: self.settings,
+            metadata: self.metadata,
+            settings_contains: self.settings_contains,
+            settings_has_key: self.settings_has_key,
+            settings_path_eq: self.settings_path_eq,
+            metadata_contains: self.metadata_contains,
+            metadata_path_eq: self.metadata_path_eq,
+            _filtered: std::marker::PhantomData::<Set>,
+        }
+    }
+    pub fn metadata_path_eq(
+        self,
+        path: &[&str],
+        value: sqlx::types::JsonValue,
+    ) -> ProfileDbSetManyQueryBuilder<Set> {
+        ProfileDbSetManyQueryBuilder {
+            metadata_path_eq: Some((
+                path.iter().map(|key| key.to_string()).collect(),
+                value,
+            )),
+            settings: self.settings,
+            metadata: self.metadata,
+            settings_contains: self.settings_contains,
+            settings_has_key: self.settings_has_key,
+            settings_path_eq: self.settings_path_eq,
+            metadata_contains: self.metadata_contains,
+            metadata_has_key: self.metadata_has_key,
+            _filtered: std::marker::PhantomData::<Set>,
+        }
+    }
+    pub fn all_rows_i_am_sure(self) -> ProfileDbSetManyQueryBuilder<Set> {
+        ProfileDbSetManyQueryBuilder {
+            settings: self.settings,
+            metadata: self.metadata,
+            settings_contains: self.settings_contains,
+            settings_has_key: self.settings_has_key,
+            settings_path_eq: self.settings_path_eq,
+            metadata_contains: self.metadata_contains,
+            metadata_has_key: self.metadata_has_key,
+            metadata_path_eq: self.metadata_path_eq,
+            _filtered: std::marker::PhantomData::<Set>,
+        }
+    }
+    pub async fn fetch_all<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<Vec<Profile>, sqlx::Error> {
+        sqlx::query!(
+            "SELECT \"id\", \"settings\" AS \"settings: SettingsJson\", \"metadata\" AS \"metadata: MetadataJson\" FROM \"profiles\" WHERE (\"settings\" = $1 or $1 is null) AND (\"metadata\" = $2 or $2 is null) AND (\"settings\" @> $3 or $3 is null) AND (\"settings\" ? $4 or $4 is null) AND (\"settings\" #> $5 = $6 or $5 is null) AND (\"metadata\" @> $7 or $7 is null) AND (\"metadata\" ? $8 or $8 is null) AND (\"metadata\" #> $9 = $10 or $9 is null)",
+            self.settings.map(sqlx::types::Json) as _, self.metadata
+            .map(sqlx::types::Json) as _, self.settings_contains, self.settings_has_key,
+            self.settings_path_eq.as_ref().map(| (path, _) | path.as_slice()), self.settings_path_eq
+            .as_ref().map(| (_, value) | value), self.metadata_contains, self
+            .metadata_has_key, self.metadata_path_eq.as_ref().map(| (path, _) | path.as_slice()),
+            self.metadata_path_eq.as_ref().map(| (_, value) | value),
+        )
+            .map(|row| Profile {
+                id: row.id,
+                settings: row.settings.0,
+                metadata: row.metadata.map(|json| json.0),
+            })
+            .fetch_all(executor)
+            .await
+    }
+}
+impl ProfileDbSetManyQueryBuilder<Set> {
+    pub async fn delete<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM \"profiles\" WHERE (\"settings\" = $1 or $1 is null) AND (\"metadata\" = $2 or $2 is null) AND (\"settings\" @> $3 or $3 is null) AND (\"settings\" ? $4 or $4 is null) AND (\"settings\" #> $5 = $6 or $5 is null) AND (\"metadata\" @> $7 or $7 is null) AND (\"metadata\" ? $8 or $8 is null) AND (\"metadata\" #> $9 = $10 or $9 is null)",
+            self.settings.map(sqlx::types::Json) as _, self.metadata
+            .map(sqlx::types::Json) as _, self.settings_contains, self.settings_has_key,
+            self.settings_path_eq.as_ref().map(| (path, _) | path.as_slice()), self.settings_path_eq
+            .as_ref().map(| (_, value) | value), self.metadata_contains, self
+            .metadata_has_key, self.metadata_path_eq.as_ref().map(| (path, _) | path.as_slice()),
+            self.metadata_path_eq.as_ref().map(| (_, value) | value),
+        )
+            .execute(executor)
+            .await?;
+        Ok(result.rows_affected())
+    }
+    pub async fn delete_returning<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<Vec<Profile>, sqlx::Error> {
+        sqlx::query!(
+            "DELETE FROM \"profiles\" WHERE (\"settings\" = $1 or $1 is null) AND (\"metadata\" = $2 or $2 is null) AND (\"settings\" @> $3 or $3 is null) AND (\"settings\" ? $4 or $4 is null) AND (\"settings\" #> $5 = $6 or $5 is null) AND (\"metadata\" @> $7 or $7 is null) AND (\"metadata\" ? $8 or $8 is null) AND (\"metadata\" #> $9 = $10 or $9 is null) RETURNING \"id\", \"settings\" AS \"settings: SettingsJson\", \"metadata\" AS \"metadata: MetadataJson\"",
+            self.settings.map(sqlx::types::Json) as _, self.metadata
+            .map(sqlx::types::Json) as _, self.settings_contains, self.settings_has_key,
+            self.settings_path_eq.as_ref().map(| (path, _) | path.as_slice()), self.settings_path_eq
+            .as_ref().map(| (_, value) | value), self.metadata_contains, self
+            .metadata_has_key, self.metadata_path_eq.as_ref().map(| (path, _) | path.as_slice()),
+            self.metadata_path_eq.as_ref().map(| (_, value) | value),
+        )
+            .map(|row| Profile {
+                id: row.id,
+                settings: row.settings.0,
+                metadata: row.metadata.map(|json| json.0),
+            })
+            .fetch_all(executor)
+            .await
+    }
+    pub fn update(self) -> ProfileDbSetManyUpdateBuilder {
+        ProfileDbSetManyUpdateBuilder::new(self)
+    }
+}
     "#;
 
     compare_computed_to_expected(input_str, output);
     Ok(())
 }
+
+#[test]
+fn can_generate_subquery_filter_for_set_without_relations() -> Result<(), String> {
+    let input_str = r#"
+#[dbset(table_name = "users")]
+pub struct User {
+    #[key]
+    id: String,
+    name: String,
+    details: Option<String>,
+    #[unique]
+    email: String,
+}
+    "#;
+
+    let output = r#"
+impl<Filtered> UserDbSetManyQueryBuilder<Filtered> {
+    #[doc(hidden)]
+    pub fn push_as_subquery(
+        self,
+        where_fields: &mut sqlx::query_builder::Separated<
+            '_,
+            '_,
+            sqlx::Postgres,
+            &'static str,
+        >,
+        column: &str,
+        key: &str,
+    ) {
+        where_fields
+            .push(
+                format!("{} IN (SELECT \"{}\" FROM \"users\" WHERE TRUE", column, key),
+            );
+        let filters = self;
+        if let Some(name) = filters.name {
+            where_fields.push("\"name\" = ");
+            where_fields.push_bind_unseparated(name);
+        }
+        if let Some(details) = filters.details {
+            where_fields.push("\"details\" = ");
+            where_fields.push_bind_unseparated(details);
+        }
+        where_fields.push_unseparated(")");
+    }
+}
+    "#;
+
+    compare_subquery_filter_computed_to_expected(input_str, output);
+    Ok(())
+}
@@ -1,12 +1,15 @@
 use proc_macro2::Ident;
 use quote::quote;
-use syn::{DeriveInput, Type};
+use syn::{Attribute, DeriveInput, Type};
 
 use crate::{
-    common::utils::get_key_fields,
+    common::utils::{
+        get_all_fields, get_belongs_to_relations, get_column_const_name, get_column_sql,
+        get_json_bind, get_key_fields, get_soft_delete_field, get_unique_fields, is_json_attr,
+    },
     utils::{
-        get_dbset_name, get_fields, get_inner_option_type, is_custom_enum_attr, is_key_attr,
-        is_unique_attr,
+        get_dbset_name, get_fields, get_inner_option_type, is_custom_enum_attr, is_flattened_field,
+        is_key_attr, is_unique_attr,
     },
 };
 
@@ -15,6 +18,12 @@ pub fn get_many_query_builder_struct_name(input: &DeriveInput) -> Ident {
     quote::format_ident!("{}ManyQueryBuilder", dbset_name)
 }
 
+// Alias for the many builder once a filter is set, so other sets' relations can name it
+pub fn get_filtered_many_query_builder_name(input: &DeriveInput) -> Ident {
+    let dbset_name = get_dbset_name(input);
+    quote::format_ident!("{}FilteredManyQueryBuilder", dbset_name)
+}
+
 pub fn get_many_query_builder_fields(input: &DeriveInput) -> Vec<(&Ident, &Type)> {
     let fields = get_fields(input);
 
@@ -53,8 +62,9 @@ pub fn get_many_query_builder_struct_fields(input: &DeriveInput) -> Vec<proc_mac
             let field_type = &field.ty;
             let is_unique = field.attrs.iter().any(is_unique_attr);
             let is_key = field.attrs.iter().any(is_key_attr);
+            let is_flattened = is_flattened_field(&field.attrs);
 
-            if !(is_unique || is_key && keys.len() == 1) {
+            if !(is_unique || is_key && keys.len() == 1 || is_flattened) {
                 let inner_type = get_inner_option_type(field_type);
                 if let Some(inner_type) = inner_type {
                     query_builder_struct_fields.push(quote! { #field_name: Option<#inner_type> });
@@ -64,6 +74,21 @@ pub fn get_many_query_builder_struct_fields(input: &DeriveInput) -> Vec<proc_mac
             }
         }
     }
+
+    for json_filter in get_json_filters(input) {
+        let name = &json_filter.name;
+        let value_type = json_filter.kind.value_type();
+        query_builder_struct_fields.push(quote! { #name: Option<#value_type> });
+    }
+
+    if get_soft_delete_field(input).is_some() {
+        query_builder_struct_fields.push(quote! { deleted_filter: Option<bool> });
+    }
+    if has_relation_filters(input) {
+        let relation_filters_type = get_relation_filters_type();
+        query_builder_struct_fields.push(quote! { relation_filters: #relation_filters_type });
+    }
+    query_builder_struct_fields.push(quote! { _filtered: std::marker::PhantomData<Filtered> });
     query_builder_struct_fields
 }
 
@@ -79,19 +104,47 @@ pub fn get_many_query_builder_struct_fields_initial(
         if let Some(field_name) = field_name_maybe {
             let is_unique = field.attrs.iter().any(is_unique_attr);
             let is_key = field.attrs.iter().any(is_key_attr);
+            let is_flattened = is_flattened_field(&field.attrs);
 
-            if !(is_unique || is_key && keys.len() == 1) {
+            if !(is_unique || is_key && keys.len() == 1 || is_flattened) {
                 query_builder_struct_fields_initial.push(quote! { #field_name: None });
             }
         }
     }
+
+    for json_filter in get_json_filters(input) {
+        let name = &json_filter.name;
+        query_builder_struct_fields_initial.push(quote! { #name: None });
+    }
+
+    if get_soft_delete_field(input).is_some() {
+        query_builder_struct_fields_initial.push(quote! { deleted_filter: Some(false) });
+    }
+    if has_relation_filters(input) {
+        query_builder_struct_fields_initial.push(quote! { relation_filters: Vec::new() });
+    }
+    query_builder_struct_fields_initial
+        .push(quote! { _filtered: std::marker::PhantomData::<NotSet> });
     query_builder_struct_fields_initial
 }
 
 pub fn get_many_query_builder_methods(input: &DeriveInput) -> Vec<proc_macro2::TokenStream> {
     let fields = get_fields(input);
+    let query_builder_struct_name = get_many_query_builder_struct_name(input);
     let mut query_builder_methods = Vec::new();
 
+    // Setting a filter moves the builder to `Filtered = Set`, so every other field is carried over
+    let deleted_filter_name = quote::format_ident!("deleted_filter");
+    let relation_filters_name = quote::format_ident!("relation_filters");
+    let json_filters = get_json_filters(input);
+    let all_struct_field_names = get_many_query_builder_filter_fields(input)
+        .into_iter()
+        .map(|(field_name, _, _)| field_name)
+        .chain(json_filters.iter().map(|json_filter| &json_filter.name))
+        .chain(get_soft_delete_field(input).map(|_| &deleted_filter_name))
+        .chain(has_relation_filters(input).then_some(&relation_filters_name))
+        .collect::<Vec<_>>();
+
     for field in fields {
         let field_name_maybe = field.ident.as_ref();
         if let Some(field_name) = field_name_maybe {
@@ -99,28 +152,336 @@ pub fn get_many_query_builder_methods(input: &DeriveInput) -> Vec<proc_macro2::T
             let is_unique = field.attrs.iter().any(is_unique_attr);
             let is_custom_enum = field.attrs.iter().any(is_custom_enum_attr);
             let is_key = field.attrs.iter().any(is_key_attr);
+            let is_flattened = is_flattened_field(&field.attrs);
 
-            if !is_unique && !is_key {
+            if !is_unique && !is_key && !is_flattened {
                 let inner_type = get_inner_option_type(field_type);
                 let method_name = quote::format_ident!("{}_eq", field_name);
+                let remaining_fill = all_struct_field_names
+                    .iter()
+                    .filter(|other_field_name| **other_field_name != field_name)
+                    .map(|other_field_name| quote! { #other_field_name: self.#other_field_name, });
 
-                if let Some(inner_type) = inner_type {
-                    query_builder_methods.push(quote! {
-                            pub fn #method_name(mut self, value: #inner_type) -> Self {
-                                self.#field_name = Some(value);
-                                self
-                            }
-                    });
-                } else {
-                    query_builder_methods.push(quote! {
-                            pub fn #method_name(mut self, value: #field_type) -> Self {
-                                self.#field_name = Some(value);
-                                self
+                let value_type = match inner_type {
+                    Some(inner_type) => inner_type,
+                    None => field_type,
+                };
+
+                query_builder_methods.push(quote! {
+                        pub fn #method_name(self, value: #value_type) -> #query_builder_struct_name<Set> {
+                            #query_builder_struct_name {
+                                #field_name: Some(value),
+                                #(#remaining_fill)*
+                                _filtered: std::marker::PhantomData::<Set>,
                             }
-                    });
-                }
+                        }
+                });
             }
         }
     }
+
+    // #[json] fields filter on their JSONB value rather than by equality
+    for json_filter in &json_filters {
+        let name = &json_filter.name;
+        let remaining_fill = all_struct_field_names
+            .iter()
+            .filter(|other_field_name| **other_field_name != name)
+            .map(|other_field_name| quote! { #other_field_name: self.#other_field_name, });
+        let (params, value) = match json_filter.kind {
+            JsonFilterKind::Contains => {
+                (quote! { value: sqlx::types::JsonValue }, quote! { value })
+            }
+            JsonFilterKind::HasKey => (quote! { key: &str }, quote! { key.to_string() }),
+            JsonFilterKind::PathEq => (
+                quote! { path: &[&str], value: sqlx::types::JsonValue },
+                quote! { (path.iter().map(|key| key.to_string()).collect(), value) },
+            ),
+        };
+
+        query_builder_methods.push(quote! {
+                pub fn #name(self, #params) -> #query_builder_struct_name<Set> {
+                    #query_builder_struct_name {
+                        #name: Some(#value),
+                        #(#remaining_fill)*
+                        _filtered: std::marker::PhantomData::<Set>,
+                    }
+                }
+        });
+    }
+
+    if get_soft_delete_field(input).is_some() {
+        query_builder_methods.push(quote! {
+                pub fn with_deleted(mut self) -> Self {
+                    self.deleted_filter = None;
+                    self
+                }
+
+                pub fn only_deleted(mut self) -> Self {
+                    self.deleted_filter = Some(true);
+                    self
+                }
+        });
+    }
+
+    // belongs_to relations filter on the related set's fields through a subquery,
+    // e.g. `author(|users| users.status_eq(..))` gives `author_id IN (SELECT id FROM users WHERE ..)`
+    for relation in get_belongs_to_relations(input) {
+        let method_name = &relation.name;
+        let related_dbset_name = &relation.related_dbset;
+        let related_many_query_builder_name =
+            quote::format_ident!("{}ManyQueryBuilder", related_dbset_name);
+        let foreign_key_column = get_column_sql(input, relation.foreign_key);
+        let references_column = get_column_const_name(&relation.references);
+        let remaining_fill = all_struct_field_names
+            .iter()
+            .filter(|field_name| ***field_name != relation_filters_name)
+            .map(|field_name| quote! { #field_name: self.#field_name, });
+
+        query_builder_methods.push(quote! {
+                pub fn #method_name<RelatedFiltered: Send + 'static>(
+                    self,
+                    filter: impl FnOnce(#related_many_query_builder_name) -> #related_many_query_builder_name<RelatedFiltered>,
+                ) -> #query_builder_struct_name<Set> {
+                    let related_filters = filter(#related_dbset_name::many());
+                    let mut relation_filters = self.relation_filters;
+                    relation_filters.push(Box::new(move |where_fields, column_prefix| {
+                        related_filters.push_as_subquery(
+                            where_fields,
+                            &format!("{}{}", column_prefix, #foreign_key_column),
+                            #related_dbset_name::#references_column,
+                        )
+                    }));
+                    #query_builder_struct_name {
+                        #(#remaining_fill)*
+                        relation_filters,
+                        _filtered: std::marker::PhantomData::<Set>,
+                    }
+                }
+        });
+    }
+
+    // Escape hatch for bulk writes that really are meant to hit the whole table
+    let all_fill = all_struct_field_names
+        .iter()
+        .map(|field_name| quote! { #field_name: self.#field_name, });
+    query_builder_methods.push(quote! {
+            pub fn all_rows_i_am_sure(self) -> #query_builder_struct_name<Set> {
+                #query_builder_struct_name {
+                    #(#all_fill)*
+                    _filtered: std::marker::PhantomData::<Set>,
+                }
+            }
+    });
     query_builder_methods
 }
+
+// Fields the many builder can filter on, in the same order as its struct fields.
+// Flattened fields span several columns, so they are not filters.
+pub fn get_many_query_builder_filter_fields(
+    input: &DeriveInput,
+) -> Vec<(&Ident, &Type, &Vec<Attribute>)> {
+    let unique_fields = get_unique_fields(input);
+    let key_fields = get_key_fields(input);
+
+    get_all_fields(input)
+        .into_iter()
+        .filter(|(_, _, attrs)| !is_flattened_field(attrs))
+        .filter(|(field_name, _, _)| !unique_fields.iter().any(|(ufn, _)| ufn == field_name))
+        .filter(|(field_name, _, _)| {
+            !(key_fields.len() == 1 && key_fields.iter().any(|(kfn, _)| kfn == field_name))
+        })
+        .collect()
+}
+
+// JSONB filters of a #[json] field, named `{field}_contains`, `{field}_has_key` and `{field}_path_eq`
+pub enum JsonFilterKind {
+    // `column @> value`
+    Contains,
+    // `column ? key`
+    HasKey,
+    // `column #> path = value`
+    PathEq,
+}
+
+impl JsonFilterKind {
+    pub fn value_type(&self) -> proc_macro2::TokenStream {
+        match self {
+            JsonFilterKind::Contains => quote! { sqlx::types::JsonValue },
+            JsonFilterKind::HasKey => quote! { String },
+            JsonFilterKind::PathEq => quote! { (Vec<String>, sqlx::types::JsonValue) },
+        }
+    }
+}
+
+pub struct JsonFilter<'a> {
+    pub name: Ident,
+    pub field_name: &'a Ident,
+    pub kind: JsonFilterKind,
+}
+
+pub fn get_json_filters(input: &DeriveInput) -> Vec<JsonFilter<'_>> {
+    get_many_query_builder_filter_fields(input)
+        .into_iter()
+        .filter(|(_, _, attrs)| attrs.iter().any(is_json_attr))
+        .flat_map(|(field_name, _, _)| {
+            [
+                ("contains", JsonFilterKind::Contains),
+                ("has_key", JsonFilterKind::HasKey),
+                ("path_eq", JsonFilterKind::PathEq),
+            ]
+            .into_iter()
+            .map(move |(suffix, kind)| JsonFilter {
+                name: quote::format_ident!("{}_{}", field_name, suffix),
+                field_name,
+                kind,
+            })
+        })
+        .collect()
+}
+
+// Relation filters such as `author(|users| ...)` are boxed pushes of a subquery condition,
+// given the `where_fields` to push onto and the column prefix of the outer query
+pub fn get_relation_filters_type() -> proc_macro2::TokenStream {
+    quote! {
+        Vec<
+            Box<
+                dyn for<'qb, 'args> FnOnce(
+                        &mut sqlx::query_builder::Separated<'qb, 'args, sqlx::Postgres, &'static str>,
+                        &str,
+                    ) + Send,
+            >,
+        >
+    }
+}
+
+// Whether the many builder carries relation filters, which it does for each belongs_to relation
+pub fn has_relation_filters(input: &DeriveInput) -> bool {
+    !get_belongs_to_relations(input).is_empty()
+}
+
+// Pushes a condition onto `where_fields` for each filter set on a `filters` many builder.
+// `where_fields_ref` is how to reborrow `where_fields` for the relation filters.
+pub fn get_many_query_builder_push_filter_fields(
+    input: &DeriveInput,
+    column_prefix: &str,
+    where_fields_ref: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let filter_fields = get_many_query_builder_filter_fields(input);
+    let soft_delete_field = get_soft_delete_field(input);
+
+    let push_filters = filter_fields.iter().map(|(field_name, _, attrs)| {
+        let filter_str = format!("{column_prefix}{} = ", get_column_sql(input, field_name));
+        let bind = get_json_bind(attrs, quote! { #field_name }, false);
+        quote! {
+            if let Some(#field_name) = filters.#field_name {
+                where_fields.push(#filter_str);
+                where_fields.push_bind_unseparated(#bind);
+            }
+        }
+    });
+
+    let push_json_filters = get_json_filters(input).into_iter().map(|json_filter| {
+        let name = &json_filter.name;
+        let column = get_column_sql(input, json_filter.field_name);
+        match json_filter.kind {
+            JsonFilterKind::Contains => {
+                let filter_str = format!("{column_prefix}{column} @> ");
+                quote! {
+                    if let Some(value) = filters.#name {
+                        where_fields.push(#filter_str);
+                        where_fields.push_bind_unseparated(value);
+                    }
+                }
+            }
+            JsonFilterKind::HasKey => {
+                let filter_str = format!("{column_prefix}{column} ? ");
+                quote! {
+                    if let Some(key) = filters.#name {
+                        where_fields.push(#filter_str);
+                        where_fields.push_bind_unseparated(key);
+                    }
+                }
+            }
+            JsonFilterKind::PathEq => {
+                let filter_str = format!("{column_prefix}{column} #> ");
+                quote! {
+                    if let Some((path, value)) = filters.#name {
+                        where_fields.push(#filter_str);
+                        where_fields.push_bind_unseparated(path);
+                        where_fields.push_unseparated(" = ");
+                        where_fields.push_bind_unseparated(value);
+                    }
+                }
+            }
+        }
+    });
+
+    let push_deleted_filter = soft_delete_field.map(|(soft_delete_name, _)| {
+        let soft_delete_column = get_column_sql(input, soft_delete_name);
+        let only_deleted_str = format!("{column_prefix}{soft_delete_column} IS NOT NULL");
+        let not_deleted_str = format!("{column_prefix}{soft_delete_column} IS NULL");
+        quote! {
+            match filters.deleted_filter {
+                Some(true) => { where_fields.push(#only_deleted_str); }
+                Some(false) => { where_fields.push(#not_deleted_str); }
+                None => {}
+            }
+        }
+    });
+
+    let push_relation_filters = has_relation_filters(input).then(|| {
+        quote! {
+            for relation_filter in filters.relation_filters {
+                relation_filter(#where_fields_ref, #column_prefix);
+            }
+        }
+    });
+
+    quote! {
+        #(#push_filters)*
+        #(#push_json_filters)*
+        #push_deleted_filter
+        #push_relation_filters
+    }
+}
+
+// Pushes a WHERE clause onto `query_builder` for each filter set on a `filters` many builder,
+// for the paths that build their queries at runtime. `column_prefix` qualifies the columns
+// (e.g. `"posts."`) when other tables are joined in.
+pub fn get_many_query_builder_push_filters(
+    input: &DeriveInput,
+    column_prefix: &str,
+) -> proc_macro2::TokenStream {
+    let filter_fields = get_many_query_builder_filter_fields(input);
+    let json_filters = get_json_filters(input);
+    let soft_delete_field = get_soft_delete_field(input);
+    let has_relation_filters = has_relation_filters(input);
+
+    if filter_fields.is_empty() && soft_delete_field.is_none() && !has_relation_filters {
+        return quote! {};
+    }
+
+    let any_filter_set_checks = filter_fields
+        .iter()
+        .map(|(field_name, _, _)| *field_name)
+        .chain(json_filters.iter().map(|json_filter| &json_filter.name))
+        .map(|field_name| {
+            quote! { filters.#field_name.is_some() }
+        })
+        .chain(soft_delete_field.map(|_| quote! { filters.deleted_filter.is_some() }))
+        .chain(has_relation_filters.then(|| quote! { !filters.relation_filters.is_empty() }));
+
+    let push_filter_fields = get_many_query_builder_push_filter_fields(
+        input,
+        column_prefix,
+        quote! { &mut where_fields },
+    );
+
+    quote! {
+        if #(#any_filter_set_checks)||* {
+            query_builder.push(" WHERE ");
+        }
+        let mut where_fields = query_builder.separated(" AND ");
+        #push_filter_fields
+    }
+}
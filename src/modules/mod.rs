@@ -4,4 +4,5 @@ pub mod from_row;
 pub mod insert_query_builder;
 pub mod many_query_builder;
 pub mod one_query_builder;
+pub mod relations;
 pub mod update_query_builder;
@@ -1,24 +1,28 @@
-use convert_case::{Case, Casing};
 use proc_macro2::Ident;
 use quote::quote;
 use syn::DeriveInput;
 
 use crate::common::utils::{
-    get_all_fields, get_dbset_name, get_inner_option_type, get_key_fields, get_query_fields_string,
-    get_struct_name, get_table_name, get_unique_fields,
+    get_all_fields, get_column_sql, get_dbset_name, get_inner_option_type, get_key_fields,
+    get_query_fields_string, get_soft_delete_field, get_soft_delete_where_clause, get_struct_name,
+    get_table_name, get_type_state_name, get_unique_fields, has_dbset_flag,
 };
+use crate::modules::from_row::get_query_as_parts;
+use crate::modules::update_query_builder::get_tracked_struct_name;
 pub fn get_one_builder_struct_name(input: &DeriveInput) -> Ident {
     let dbset_name = get_dbset_name(input);
     quote::format_ident!("{}OneQueryBuilder", dbset_name)
 }
 
 pub fn get_query_builder(input: &DeriveInput) -> proc_macro2::TokenStream {
+    let (query_as, query_as_struct, query_as_map) = get_query_as_parts(input);
     let table_name = get_table_name(input);
     let struct_name = get_struct_name(input);
     let builder_struct_name = get_one_builder_struct_name(input);
     let key_fields = get_key_fields(input);
     let unique_fields = get_unique_fields(input);
     let all_fields = get_all_fields(input);
+    let soft_delete_field = get_soft_delete_field(input);
 
     let non_nullable_fields = key_fields
         .iter()
@@ -32,13 +36,7 @@ pub fn get_query_builder(input: &DeriveInput) -> proc_macro2::TokenStream {
     let builder_struct_generics = all_required_insert_fields
         .clone()
         .map(|(field_name, _)| {
-            let gen_name_pascal = quote::format_ident!(
-                "{}",
-                field_name
-                    .to_string()
-                    .from_case(Case::Snake)
-                    .to_case(Case::Pascal)
-            );
+            let gen_name_pascal = get_type_state_name(input, field_name);
             quote! {
                 #gen_name_pascal = NotSet,
             }
@@ -61,15 +59,11 @@ pub fn get_query_builder(input: &DeriveInput) -> proc_macro2::TokenStream {
         })
         .chain(vec![quote! {
             _unique_fields: std::marker::PhantomData::<UniqueFields>,
-        }]);
+        }])
+        .chain(soft_delete_field.map(|_| quote! { deleted_filter: Option<bool>, }));
 
     let phantom_struct_fields = all_required_insert_fields.clone().map(|(name, _)| {
-        let gen_name_pascal = quote::format_ident!(
-            "{}",
-            name.to_string()
-                .from_case(Case::Snake)
-                .to_case(Case::Pascal)
-        );
+        let gen_name_pascal = get_type_state_name(input, name);
         let ph_name = quote::format_ident!("_{}", name);
         quote! { #ph_name: std::marker::PhantomData::<#gen_name_pascal>, }
     });
@@ -99,7 +93,8 @@ pub fn get_query_builder(input: &DeriveInput) -> proc_macro2::TokenStream {
         })
         .chain(vec![quote! {
                 _unique_fields: std::marker::PhantomData::<NotSet>,
-        }]);
+        }])
+        .chain(soft_delete_field.map(|_| quote! { deleted_filter: Some(false), }));
 
     let initial_phantom_struct_fields = all_required_insert_fields.clone().map(|(name, _)| {
         let ph_name = quote::format_ident!("_{}", name);
@@ -116,9 +111,12 @@ pub fn get_query_builder(input: &DeriveInput) -> proc_macro2::TokenStream {
     };
 
     // Create add value functions
+    let deleted_filter_name = quote::format_ident!("deleted_filter");
     let fill_other_fields = all_query_one_fields
         .clone()
-        .map(|(name, _)| (name, quote! { #name: self.#name, }));
+        .map(|(name, _)| *name)
+        .chain(soft_delete_field.map(|_| &deleted_filter_name))
+        .map(|name| (name, quote! { #name: self.#name, }));
 
     let fill_other_phantom_fields = all_required_insert_fields.clone().map(|(name, _)| {
         let ph_name = quote::format_ident!("_{}", name);
@@ -143,14 +141,14 @@ pub fn get_query_builder(input: &DeriveInput) -> proc_macro2::TokenStream {
             } else { quote!{} };
             let remaining_fill = fill_other_fields
                 .clone()
-                .filter(|(other_field_name, _)| *other_field_name != field_name)
+                .filter(|(other_field_name, _)| other_field_name != field_name)
                 .map(|(_, value)| value);
 
 
             let pre_impl_generics_in = all_required_insert_fields.clone().map(|(gen_name, _)|{
                 if gen_name != field_name {
                     if !is_unique_field  {
-                    let gen_name_pascal = quote::format_ident!("{}", gen_name.to_string().from_case(Case::Snake).to_case(Case::Pascal));
+                    let gen_name_pascal = get_type_state_name(input, gen_name);
                         return quote!{ #gen_name_pascal, }
                     } else {
                         return quote!{  }
@@ -163,7 +161,7 @@ pub fn get_query_builder(input: &DeriveInput) -> proc_macro2::TokenStream {
             let generics_in = all_required_insert_fields.clone().map(|(gen_name, _)|{
                 if gen_name != field_name {
                     if !is_unique_field  {
-                    let gen_name_pascal = quote::format_ident!("{}", gen_name.to_string().from_case(Case::Snake).to_case(Case::Pascal));
+                    let gen_name_pascal = get_type_state_name(input, gen_name);
                         return quote!{ #gen_name_pascal, }
                     } else {
                         return quote!{ NotSet, }
@@ -174,7 +172,7 @@ pub fn get_query_builder(input: &DeriveInput) -> proc_macro2::TokenStream {
             let generics_out = all_required_insert_fields.clone().map(|(gen_name, _)|{
                 if gen_name != field_name {
                     if !is_unique_field  {
-                    let gen_name_pascal = quote::format_ident!("{}", gen_name.to_string().from_case(Case::Snake).to_case(Case::Pascal));
+                    let gen_name_pascal = get_type_state_name(input, gen_name);
                         return quote!{ #gen_name_pascal, }
                     } else {
                         return quote!{ NotSet, }
@@ -219,6 +217,31 @@ pub fn get_query_builder(input: &DeriveInput) -> proc_macro2::TokenStream {
             }
         });
 
+    // With #[soft_delete] the deleted rows are hidden unless asked for
+    let soft_delete_methods = if soft_delete_field.is_some() {
+        let generic_names = all_required_insert_fields
+            .clone()
+            .map(|(name, _)| get_type_state_name(input, name))
+            .chain(vec![quote::format_ident!("UniqueFields")])
+            .collect::<Vec<_>>();
+
+        quote! {
+            impl <#(#generic_names),*> #builder_struct_name <#(#generic_names),*> {
+                pub fn with_deleted(mut self) -> Self {
+                    self.deleted_filter = None;
+                    self
+                }
+
+                pub fn only_deleted(mut self) -> Self {
+                    self.deleted_filter = Some(true);
+                    self
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+
     // Create complete impl
 
     let key_fetch_one_method_generics = all_required_insert_fields
@@ -244,7 +267,9 @@ pub fn get_query_builder(input: &DeriveInput) -> proc_macro2::TokenStream {
     let key_query_builder_fields_where_clause = key_fields
         .iter()
         .enumerate()
-        .map(|(index, (field_name, _))| format!("{} = ${}", field_name, index + 1,))
+        .map(|(index, (field_name, _))| {
+            format!("{} = ${}", get_column_sql(input, field_name), index + 1,)
+        })
         .collect::<Vec<_>>()
         .join(" AND ");
 
@@ -254,7 +279,7 @@ pub fn get_query_builder(input: &DeriveInput) -> proc_macro2::TokenStream {
         .map(|(index, (field_name, _))| {
             format!(
                 "({} = ${} OR ${} is null)",
-                field_name,
+                get_column_sql(input, field_name),
                 index + 1,
                 index + 1,
             )
@@ -264,14 +289,88 @@ pub fn get_query_builder(input: &DeriveInput) -> proc_macro2::TokenStream {
 
     // let query = format!("INSERT INTO {table_name}({all_insert_fields_str}) VALUES ({all_params}) RETURNING {all_fields_str};");
 
-    let unique_query_args = unique_fields.clone().into_iter().map(|(name, _)| {
-        quote! { self.#name, }
-    });
+    // Soft deleted rows are filtered on the builder's deleted_filter, bound after the key or unique fields
+    let (key_soft_delete_where, unique_soft_delete_where, deleted_filter_arg) =
+        match soft_delete_field {
+            Some((soft_delete_name, _)) => (
+                format!(
+                    " AND {}",
+                    get_soft_delete_where_clause(
+                        &get_column_sql(input, soft_delete_name),
+                        key_fields.len() + 1
+                    )
+                ),
+                format!(
+                    " AND {}",
+                    get_soft_delete_where_clause(
+                        &get_column_sql(input, soft_delete_name),
+                        unique_fields.len() + 1
+                    )
+                ),
+                quote! { self.deleted_filter, },
+            ),
+            None => (String::new(), String::new(), quote! {}),
+        };
+
+    let unique_query_args = unique_fields
+        .clone()
+        .into_iter()
+        .map(|(name, _)| {
+            quote! { self.#name, }
+        })
+        .chain(vec![deleted_filter_arg.clone()]);
 
     let unique_query_args_2 = unique_query_args.clone();
 
+    // With #[dbset(tracked)] the row can be fetched with a snapshot for dirty-checked updates
+    let fetch_one_tracked = if has_dbset_flag(&input.attrs, "tracked") {
+        let tracked_struct_name = get_tracked_struct_name(input);
+        quote! {
+                pub async fn fetch_one_tracked<'e, E: sqlx::PgExecutor<'e>>(
+                    self,
+                    executor: E,
+                ) -> Result<#tracked_struct_name, sqlx::Error> {
+                    self.fetch_one(executor).await.map(#tracked_struct_name::new)
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    // With #[soft_delete] the matched row can be restored by clearing its deleted timestamp
+    let get_restore_method = |where_clause: &str, args: Vec<proc_macro2::TokenStream>| {
+        match soft_delete_field {
+            Some((soft_delete_name, _)) => {
+                let soft_delete_column = get_column_sql(input, soft_delete_name);
+                let query = format!("UPDATE {table_name} SET {soft_delete_column} = NULL WHERE {where_clause} AND {soft_delete_column} IS NOT NULL");
+                quote! {
+                    pub async fn restore<'e, E: sqlx::PgExecutor<'e>>(
+                        self,
+                        executor: E,
+                    ) -> Result<u64, sqlx::Error> {
+                        let result = sqlx::query!(
+                            #query,
+                            #(#args)*
+                        )
+                            .execute(executor)
+                            .await?;
+                        Ok(result.rows_affected())
+                    }
+                }
+            }
+            None => quote! {},
+        }
+    };
+
     let unique_fetch_one = if !unique_fields.is_empty() {
-        let query = format!("SELECT {all_fields_str} FROM {table_name} WHERE {unique_query_builder_fields_where_clause}");
+        let query = format!("SELECT {all_fields_str} FROM {table_name} WHERE {unique_query_builder_fields_where_clause}{unique_soft_delete_where}");
+        let restore = get_restore_method(
+            &unique_query_builder_fields_where_clause,
+            unique_fields
+                .iter()
+                .map(|(name, _)| quote! { self.#name, })
+                .collect(),
+        );
 
         quote! {
 
@@ -280,11 +379,12 @@ pub fn get_query_builder(input: &DeriveInput) -> proc_macro2::TokenStream {
                     self,
                     executor: E,
                 ) -> Result<Option<#struct_name>, sqlx::Error> {
-                    sqlx::query_as!(
-                        #struct_name,
+                    #query_as(
+                        #query_as_struct
                         #query,
                         #(#unique_query_args)*
                     )
+                        #query_as_map
                         .fetch_optional(executor)
                         .await
             }
@@ -292,27 +392,42 @@ pub fn get_query_builder(input: &DeriveInput) -> proc_macro2::TokenStream {
                     self,
                     executor: E,
                 ) -> Result<#struct_name, sqlx::Error> {
-                    sqlx::query_as!(
-                        #struct_name,
+                    #query_as(
+                        #query_as_struct
                         #query,
                         #(#unique_query_args_2)*
                     )
+                        #query_as_map
                         .fetch_one(executor)
                         .await
             }
+
+            #fetch_one_tracked
+            #restore
         }
         }
     } else {
         quote! {}
     };
 
-    let key_query_args = key_fields.clone().into_iter().map(|(name, _)| {
-        quote! { self.#name, }
-    });
+    let key_query_args = key_fields
+        .clone()
+        .into_iter()
+        .map(|(name, _)| {
+            quote! { self.#name, }
+        })
+        .chain(vec![deleted_filter_arg]);
     let key_query_args_2 = key_query_args.clone();
 
     let key_fetch_one = if !key_fields.is_empty() {
-        let query = format!("SELECT {all_fields_str} FROM {table_name} WHERE {key_query_builder_fields_where_clause}");
+        let query = format!("SELECT {all_fields_str} FROM {table_name} WHERE {key_query_builder_fields_where_clause}{key_soft_delete_where}");
+        let restore = get_restore_method(
+            &key_query_builder_fields_where_clause,
+            key_fields
+                .iter()
+                .map(|(name, _)| quote! { self.#name, })
+                .collect(),
+        );
 
         quote! {
             impl  #builder_struct_name <#(#key_fetch_one_method_generics)*> {
@@ -320,11 +435,12 @@ pub fn get_query_builder(input: &DeriveInput) -> proc_macro2::TokenStream {
                         self,
                         executor: E,
                     ) -> Result<Option<#struct_name>, sqlx::Error> {
-                        sqlx::query_as!(
-                            #struct_name,
+                        #query_as(
+                            #query_as_struct
                             #query,
                             #(#key_query_args)*
                         )
+                            #query_as_map
                             .fetch_optional(executor)
                             .await
                 }
@@ -333,14 +449,18 @@ pub fn get_query_builder(input: &DeriveInput) -> proc_macro2::TokenStream {
                         self,
                         executor: E,
                     ) -> Result<#struct_name, sqlx::Error> {
-                        sqlx::query_as!(
-                            #struct_name,
+                        #query_as(
+                            #query_as_struct
                             #query,
                             #(#key_query_args_2)*
                         )
+                            #query_as_map
                             .fetch_one(executor)
                             .await
                 }
+
+                #fetch_one_tracked
+                #restore
             }
         }
     } else {
@@ -355,6 +475,7 @@ pub fn get_query_builder(input: &DeriveInput) -> proc_macro2::TokenStream {
         }
 
         #(#builder_methods)*
+        #soft_delete_methods
 
         #key_fetch_one
         #unique_fetch_one
@@ -73,7 +73,7 @@ impl UserDbSetOneQueryBuilder<Set, NotSet> {
         executor: E,
     ) -> Result<Option<User>, sqlx::Error> {
         sqlx::query_as!(
-            User, "SELECT id, name, details, email FROM users WHERE id = $1", self.id,
+            User, "SELECT \"id\", \"name\", \"details\", \"email\" FROM \"users\" WHERE \"id\" = $1", self.id,
         )
             .fetch_optional(executor)
             .await
@@ -83,7 +83,7 @@ impl UserDbSetOneQueryBuilder<Set, NotSet> {
         executor: E,
     ) -> Result<User, sqlx::Error> {
         sqlx::query_as!(
-            User, "SELECT id, name, details, email FROM users WHERE id = $1", self.id,
+            User, "SELECT \"id\", \"name\", \"details\", \"email\" FROM \"users\" WHERE \"id\" = $1", self.id,
         )
             .fetch_one(executor)
             .await
@@ -96,7 +96,7 @@ impl UserDbSetOneQueryBuilder<NotSet, Set> {
     ) -> Result<Option<User>, sqlx::Error> {
         sqlx::query_as!(
             User,
-            "SELECT id, name, details, email FROM users WHERE (email = $1 OR $1 is null)",
+            "SELECT \"id\", \"name\", \"details\", \"email\" FROM \"users\" WHERE (\"email\" = $1 OR $1 is null)",
             self.email,
         )
             .fetch_optional(executor)
@@ -108,7 +108,7 @@ impl UserDbSetOneQueryBuilder<NotSet, Set> {
     ) -> Result<User, sqlx::Error> {
         sqlx::query_as!(
             User,
-            "SELECT id, name, details, email FROM users WHERE (email = $1 OR $1 is null)",
+            "SELECT \"id\", \"name\", \"details\", \"email\" FROM \"users\" WHERE (\"email\" = $1 OR $1 is null)",
             self.email,
         )
             .fetch_one(executor)
@@ -181,7 +181,7 @@ impl UserDbSetOneQueryBuilder<Set, NotSet> {
         executor: E,
     ) -> Result<Option<User>, sqlx::Error> {
         sqlx::query_as!(
-            User, "SELECT id, name, details, email, status AS \"status:UserStatus\" FROM users WHERE id = $1", self.id,
+            User, "SELECT \"id\", \"name\", \"details\", \"email\", \"status\" AS \"status:UserStatus\" FROM \"users\" WHERE \"id\" = $1", self.id,
         )
             .fetch_optional(executor)
             .await
@@ -191,7 +191,7 @@ impl UserDbSetOneQueryBuilder<Set, NotSet> {
         executor: E,
     ) -> Result<User, sqlx::Error> {
         sqlx::query_as!(
-            User, "SELECT id, name, details, email, status AS \"status:UserStatus\" FROM users WHERE id = $1", self.id,
+            User, "SELECT \"id\", \"name\", \"details\", \"email\", \"status\" AS \"status:UserStatus\" FROM \"users\" WHERE \"id\" = $1", self.id,
         )
             .fetch_one(executor)
             .await
@@ -204,7 +204,7 @@ impl UserDbSetOneQueryBuilder<NotSet, Set> {
     ) -> Result<Option<User>, sqlx::Error> {
         sqlx::query_as!(
             User,
-            "SELECT id, name, details, email, status AS \"status:UserStatus\" FROM users WHERE (email = $1 OR $1 is null)",
+            "SELECT \"id\", \"name\", \"details\", \"email\", \"status\" AS \"status:UserStatus\" FROM \"users\" WHERE (\"email\" = $1 OR $1 is null)",
             self.email,
         )
             .fetch_optional(executor)
@@ -216,7 +216,7 @@ impl UserDbSetOneQueryBuilder<NotSet, Set> {
     ) -> Result<User, sqlx::Error> {
         sqlx::query_as!(
             User,
-            "SELECT id, name, details, email, status AS \"status:UserStatus\" FROM users WHERE (email = $1 OR $1 is null)",
+            "SELECT \"id\", \"name\", \"details\", \"email\", \"status\" AS \"status:UserStatus\" FROM \"users\" WHERE (\"email\" = $1 OR $1 is null)",
             self.email,
         )
             .fetch_one(executor)
@@ -320,7 +320,7 @@ impl TagDbSetOneQueryBuilder<Set> {
     ) -> Result<Option<Tag>, sqlx::Error> {
         sqlx::query_as!(
             Tag,
-            "SELECT tag_name FROM tags WHERE (tag_name = $1 OR $1 is null)",
+            "SELECT \"tag_name\" FROM \"tags\" WHERE (\"tag_name\" = $1 OR $1 is null)",
             self.tag_name,
         )
             .fetch_optional(executor)
@@ -332,7 +332,7 @@ impl TagDbSetOneQueryBuilder<Set> {
     ) -> Result<Tag, sqlx::Error> {
         sqlx::query_as!(
             Tag,
-            "SELECT tag_name FROM tags WHERE (tag_name = $1 OR $1 is null)",
+            "SELECT \"tag_name\" FROM \"tags\" WHERE (\"tag_name\" = $1 OR $1 is null)",
             self.tag_name,
         )
             .fetch_one(executor)
@@ -413,7 +413,7 @@ impl FavouritedProductDbSetOneQueryBuilder<Set,Set, NotSet> {
     ) -> Result<Option<FavouritedProduct>, sqlx::Error> {
         sqlx::query_as!(
             FavouritedProduct,
-            "SELECT product_id, user_id FROM favourite_products WHERE product_id = $1 AND user_id = $2",
+            "SELECT \"product_id\", \"user_id\" FROM \"favourite_products\" WHERE \"product_id\" = $1 AND \"user_id\" = $2",
             self.product_id, self.user_id,
         )
             .fetch_optional(executor)
@@ -426,7 +426,7 @@ impl FavouritedProductDbSetOneQueryBuilder<Set,Set, NotSet> {
     ) -> Result<FavouritedProduct, sqlx::Error> {
         sqlx::query_as!(
             FavouritedProduct,
-            "SELECT product_id, user_id FROM favourite_products WHERE product_id = $1 AND user_id = $2",
+            "SELECT \"product_id\", \"user_id\" FROM \"favourite_products\" WHERE \"product_id\" = $1 AND \"user_id\" = $2",
             self.product_id,
             self.user_id,
         )
@@ -440,3 +440,506 @@ impl FavouritedProductDbSetOneQueryBuilder<Set,Set, NotSet> {
     compare_computed_to_expected(input_str, output);
     Ok(())
 }
+
+#[test]
+fn can_parse_tracked_note_into_one_builder() -> Result<(), String> {
+    let input_str = r#"
+        #[derive(DbSet, Debug, Clone)]
+        #[dbset(table_name = "notes")]
+        #[dbset(tracked)]
+        pub struct Note {
+            #[key]
+            id: i32,
+            text: String,
+            pinned: bool,
+        }
+    "#;
+
+    let output = r#"
+pub struct NoteDbSetOneQueryBuilder<Id = NotSet, UniqueFields = NotSet> {
+    id: Option<i32>,
+    _unique_fields: std::marker::PhantomData<UniqueFields>,
+    _id: std::marker::PhantomData<Id>,
+}
+impl NoteDbSetOneQueryBuilder {
+    pub fn new() -> NoteDbSetOneQueryBuilder<NotSet, NotSet> {
+        Self {
+            id: None,
+            _unique_fields: std::marker::PhantomData::<NotSet>,
+            _id: std::marker::PhantomData::<NotSet>,
+        }
+    }
+}
+impl NoteDbSetOneQueryBuilder<NotSet, NotSet> {
+    pub fn id_eq(self, id: i32) -> NoteDbSetOneQueryBuilder<Set, NotSet> {
+        NoteDbSetOneQueryBuilder {
+            id: Some(id),
+            _id: std::marker::PhantomData::<Set>,
+            _unique_fields: std::marker::PhantomData::<NotSet>,
+        }
+    }
+}
+impl NoteDbSetOneQueryBuilder<Set, NotSet> {
+    pub async fn fetch_optional<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<Option<Note>, sqlx::Error> {
+        sqlx::query_as!(
+            Note, "SELECT \"id\", \"text\", \"pinned\" FROM \"notes\" WHERE \"id\" = $1", self.id,
+        )
+            .fetch_optional(executor)
+            .await
+    }
+    pub async fn fetch_one<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<Note, sqlx::Error> {
+        sqlx::query_as!(
+            Note, "SELECT \"id\", \"text\", \"pinned\" FROM \"notes\" WHERE \"id\" = $1", self.id,
+        )
+            .fetch_one(executor)
+            .await
+    }
+    pub async fn fetch_one_tracked<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<NoteDbSetTracked, sqlx::Error> {
+        self.fetch_one(executor).await.map(NoteDbSetTracked::new)
+    }
+}
+    "#;
+
+    compare_computed_to_expected(input_str, output);
+    Ok(())
+}
+
+#[test]
+fn can_parse_soft_delete_post_into_one_builder() -> Result<(), String> {
+    let input_str = r#"
+        #[derive(DbSet)]
+        #[dbset(table_name = "posts")]
+        pub struct Post {
+            #[key]
+            id: i32,
+            #[unique]
+            slug: String,
+            title: String,
+            #[soft_delete]
+            deleted_at: Option<DateTime<Utc>>,
+        }
+    "#;
+
+    let output = r#"
+pub struct PostDbSetOneQueryBuilder<Id = NotSet, UniqueFields = NotSet> {
+    id: Option<i32>,
+    slug: Option<String>,
+    _unique_fields: std::marker::PhantomData<UniqueFields>,
+    deleted_filter: Option<bool>,
+    _id: std::marker::PhantomData<Id>,
+}
+impl PostDbSetOneQueryBuilder {
+    pub fn new() -> PostDbSetOneQueryBuilder<NotSet, NotSet> {
+        Self {
+            id: None,
+            slug: None,
+            _unique_fields: std::marker::PhantomData::<NotSet>,
+            deleted_filter: Some(false),
+            _id: std::marker::PhantomData::<NotSet>,
+        }
+    }
+}
+impl PostDbSetOneQueryBuilder<NotSet, NotSet> {
+    pub fn id_eq(self, id: i32) -> PostDbSetOneQueryBuilder<Set, NotSet> {
+        PostDbSetOneQueryBuilder {
+            id: Some(id),
+            slug: self.slug,
+            deleted_filter: self.deleted_filter,
+            _id: std::marker::PhantomData::<Set>,
+            _unique_fields: std::marker::PhantomData::<NotSet>,
+        }
+    }
+}
+impl PostDbSetOneQueryBuilder<NotSet, NotSet> {
+    pub fn slug_eq(self, slug: String) -> PostDbSetOneQueryBuilder<NotSet, Set> {
+        PostDbSetOneQueryBuilder {
+            slug: Some(slug),
+            id: self.id,
+            deleted_filter: self.deleted_filter,
+            _unique_fields: std::marker::PhantomData::<Set>,
+            _id: self._id,
+        }
+    }
+}
+impl<Id, UniqueFields> PostDbSetOneQueryBuilder<Id, UniqueFields> {
+    pub fn with_deleted(mut self) -> Self {
+        self.deleted_filter = None;
+        self
+    }
+    pub fn only_deleted(mut self) -> Self {
+        self.deleted_filter = Some(true);
+        self
+    }
+}
+impl PostDbSetOneQueryBuilder<Set, NotSet> {
+    pub async fn fetch_optional<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<Option<Post>, sqlx::Error> {
+        sqlx::query_as!(
+            Post,
+            "SELECT \"id\", \"slug\", \"title\", \"deleted_at\" FROM \"posts\" WHERE \"id\" = $1 AND ((\"deleted_at\" IS NOT NULL) = $2 OR $2 IS NULL)",
+            self.id, self.deleted_filter,
+        )
+            .fetch_optional(executor)
+            .await
+    }
+    pub async fn fetch_one<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<Post, sqlx::Error> {
+        sqlx::query_as!(
+            Post,
+            "SELECT \"id\", \"slug\", \"title\", \"deleted_at\" FROM \"posts\" WHERE \"id\" = $1 AND ((\"deleted_at\" IS NOT NULL) = $2 OR $2 IS NULL)",
+            self.id, self.deleted_filter,
+        )
+            .fetch_one(executor)
+            .await
+    }
+    pub async fn restore<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "UPDATE \"posts\" SET \"deleted_at\" = NULL WHERE \"id\" = $1 AND \"deleted_at\" IS NOT NULL",
+            self.id,
+        )
+            .execute(executor)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
+impl PostDbSetOneQueryBuilder<NotSet, Set> {
+    pub async fn fetch_optional<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<Option<Post>, sqlx::Error> {
+        sqlx::query_as!(
+            Post,
+            "SELECT \"id\", \"slug\", \"title\", \"deleted_at\" FROM \"posts\" WHERE (\"slug\" = $1 OR $1 is null) AND ((\"deleted_at\" IS NOT NULL) = $2 OR $2 IS NULL)",
+            self.slug, self.deleted_filter,
+        )
+            .fetch_optional(executor)
+            .await
+    }
+    pub async fn fetch_one<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<Post, sqlx::Error> {
+        sqlx::query_as!(
+            Post,
+            "SELECT \"id\", \"slug\", \"title\", \"deleted_at\" FROM \"posts\" WHERE (\"slug\" = $1 OR $1 is null) AND ((\"deleted_at\" IS NOT NULL) = $2 OR $2 IS NULL)",
+            self.slug, self.deleted_filter,
+        )
+            .fetch_one(executor)
+            .await
+    }
+    pub async fn restore<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "UPDATE \"posts\" SET \"deleted_at\" = NULL WHERE (\"slug\" = $1 OR $1 is null) AND \"deleted_at\" IS NOT NULL",
+            self.slug,
+        )
+            .execute(executor)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
+    "#;
+
+    compare_computed_to_expected(input_str, output);
+    Ok(())
+}
+
+#[test]
+fn can_parse_invoice_with_schema_into_one_builder() -> Result<(), String> {
+    let input_str = r#"
+        #[derive(DbSet)]
+        #[dbset(table_name = "invoices", schema = "billing")]
+        pub struct Invoice {
+            #[key]
+            id: i32,
+            #[unique]
+            number: String,
+            order: i32,
+        }
+    "#;
+
+    let output = r#"
+pub struct InvoiceDbSetOneQueryBuilder<Id = NotSet, UniqueFields = NotSet> {
+    id: Option<i32>,
+    number: Option<String>,
+    _unique_fields: std::marker::PhantomData<UniqueFields>,
+    _id: std::marker::PhantomData<Id>,
+}
+impl InvoiceDbSetOneQueryBuilder {
+    pub fn new() -> InvoiceDbSetOneQueryBuilder<NotSet, NotSet> {
+        Self {
+            id: None,
+            number: None,
+            _unique_fields: std::marker::PhantomData::<NotSet>,
+            _id: std::marker::PhantomData::<NotSet>,
+        }
+    }
+}
+impl InvoiceDbSetOneQueryBuilder<NotSet, NotSet> {
+    pub fn id_eq(self, id: i32) -> InvoiceDbSetOneQueryBuilder<Set, NotSet> {
+        InvoiceDbSetOneQueryBuilder {
+            id: Some(id),
+            number: self.number,
+            _id: std::marker::PhantomData::<Set>,
+            _unique_fields: std::marker::PhantomData::<NotSet>,
+        }
+    }
+}
+impl InvoiceDbSetOneQueryBuilder<NotSet, NotSet> {
+    pub fn number_eq(self, number: String) -> InvoiceDbSetOneQueryBuilder<NotSet, Set> {
+        InvoiceDbSetOneQueryBuilder {
+            number: Some(number),
+            id: self.id,
+            _unique_fields: std::marker::PhantomData::<Set>,
+            _id: self._id,
+        }
+    }
+}
+impl InvoiceDbSetOneQueryBuilder<Set, NotSet> {
+    pub async fn fetch_optional<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<Option<Invoice>, sqlx::Error> {
+        sqlx::query_as!(
+            Invoice,
+            "SELECT \"id\", \"number\", \"order\" FROM \"billing\".\"invoices\" WHERE \"id\" = $1",
+            self.id,
+        )
+            .fetch_optional(executor)
+            .await
+    }
+    pub async fn fetch_one<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<Invoice, sqlx::Error> {
+        sqlx::query_as!(
+            Invoice,
+            "SELECT \"id\", \"number\", \"order\" FROM \"billing\".\"invoices\" WHERE \"id\" = $1",
+            self.id,
+        )
+            .fetch_one(executor)
+            .await
+    }
+}
+impl InvoiceDbSetOneQueryBuilder<NotSet, Set> {
+    pub async fn fetch_optional<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<Option<Invoice>, sqlx::Error> {
+        sqlx::query_as!(
+            Invoice,
+            "SELECT \"id\", \"number\", \"order\" FROM \"billing\".\"invoices\" WHERE (\"number\" = $1 OR $1 is null)",
+            self.number,
+        )
+            .fetch_optional(executor)
+            .await
+    }
+    pub async fn fetch_one<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<Invoice, sqlx::Error> {
+        sqlx::query_as!(
+            Invoice,
+            "SELECT \"id\", \"number\", \"order\" FROM \"billing\".\"invoices\" WHERE (\"number\" = $1 OR $1 is null)",
+            self.number,
+        )
+            .fetch_one(executor)
+            .await
+    }
+}
+    "#;
+
+    compare_computed_to_expected(input_str, output);
+    Ok(())
+}
+
+#[test]
+fn can_parse_book_with_skipped_field_into_one_builder() -> Result<(), String> {
+    let input_str = r#"
+    #[derive(DbSet, Debug, Clone)]
+    #[dbset(table_name = "books")]
+    pub struct Book {
+        #[key]
+        id: i32,
+        title: String,
+        #[dbset(skip)]
+        word_count: Option<usize>,
+    }
+    "#;
+
+    let output = r#"
+pub struct BookDbSetOneQueryBuilder<Id = NotSet, UniqueFields = NotSet> {
+    id: Option<i32>,
+    _unique_fields: std::marker::PhantomData<UniqueFields>,
+    _id: std::marker::PhantomData<Id>,
+}
+impl BookDbSetOneQueryBuilder {
+    pub fn new() -> BookDbSetOneQueryBuilder<NotSet, NotSet> {
+        Self {
+            id: None,
+            _unique_fields: std::marker::PhantomData::<NotSet>,
+            _id: std::marker::PhantomData::<NotSet>,
+        }
+    }
+}
+impl BookDbSetOneQueryBuilder<NotSet, NotSet> {
+    pub fn id_eq(self, id: i32) -> BookDbSetOneQueryBuilder<Set, NotSet> {
+        BookDbSetOneQueryBuilder {
+            id: Some(id),
+            _id: std::marker::PhantomData::<Set>,
+            _unique_fields: std::marker::PhantomData::<NotSet>,
+        }
+    }
+}
+impl BookDbSetOneQueryBuilder<Set, NotSet> {
+    pub async fn fetch_optional<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<Option<Book>, sqlx::Error> {
+        sqlx::query!(
+            "SELECT \"id\", \"title\" FROM \"books\" WHERE \"id\" = $1", self.id,
+        )
+            .map(|row| Book {
+                id: row.id,
+                title: row.title,
+                word_count: Default::default(),
+            })
+            .fetch_optional(executor)
+            .await
+    }
+    pub async fn fetch_one<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<Book, sqlx::Error> {
+        sqlx::query!(
+            "SELECT \"id\", \"title\" FROM \"books\" WHERE \"id\" = $1", self.id,
+        )
+            .map(|row| Book {
+                id: row.id,
+                title: row.title,
+                word_count: Default::default(),
+            })
+            .fetch_one(executor)
+            .await
+    }
+}
+    "#;
+
+    compare_computed_to_expected(input_str, output);
+    Ok(())
+}
+
+#[test]
+fn can_parse_key_named_after_its_type_into_one_builder() -> Result<(), String> {
+    let input_str = r#"
+#[dbset(table_name = "memberships")]
+pub struct Membership {
+    #[key]
+    user_id: String,
+    #[key]
+    #[custom_enum]
+    group: Group,
+    role: String,
+}
+    "#;
+
+    let output = r#"
+pub struct MembershipDbSetOneQueryBuilder<
+    UserId = NotSet,
+    GroupField = NotSet,
+    UniqueFields = NotSet,
+> {
+    user_id: Option<String>,
+    group: Option<Group>,
+    _unique_fields: std::marker::PhantomData<UniqueFields>,
+    _user_id: std::marker::PhantomData<UserId>,
+    _group: std::marker::PhantomData<GroupField>,
+}
+impl MembershipDbSetOneQueryBuilder {
+    pub fn new() -> MembershipDbSetOneQueryBuilder<NotSet, NotSet, NotSet> {
+        Self {
+            user_id: None,
+            group: None,
+            _unique_fields: std::marker::PhantomData::<NotSet>,
+            _user_id: std::marker::PhantomData::<NotSet>,
+            _group: std::marker::PhantomData::<NotSet>,
+        }
+    }
+}
+impl<GroupField> MembershipDbSetOneQueryBuilder<NotSet, GroupField, NotSet> {
+    pub fn user_id_eq(
+        self,
+        user_id: String,
+    ) -> MembershipDbSetOneQueryBuilder<Set, GroupField, NotSet> {
+        MembershipDbSetOneQueryBuilder {
+            user_id: Some(user_id),
+            group: self.group,
+            _user_id: std::marker::PhantomData::<Set>,
+            _group: self._group,
+            _unique_fields: std::marker::PhantomData::<NotSet>,
+        }
+    }
+}
+impl<UserId> MembershipDbSetOneQueryBuilder<UserId, NotSet, NotSet> {
+    pub fn group_eq(
+        self,
+        group: Group,
+    ) -> MembershipDbSetOneQueryBuilder<UserId, Set, NotSet> {
+        MembershipDbSetOneQueryBuilder {
+            group: Some(group),
+            user_id: self.user_id,
+            _group: std::marker::PhantomData::<Set>,
+            _user_id: self._user_id,
+            _unique_fields: std::marker::PhantomData::<NotSet>,
+        }
+    }
+}
+impl MembershipDbSetOneQueryBuilder<Set, Set, NotSet> {
+    pub async fn fetch_optional<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<Option<Membership>, sqlx::Error> {
+        sqlx::query_as!(
+            Membership,
+            "SELECT \"user_id\", \"group\" AS \"group:Group\", \"role\" FROM \"memberships\" WHERE \"user_id\" = $1 AND \"group\" = $2",
+            self.user_id, self.group,
+        )
+            .fetch_optional(executor)
+            .await
+    }
+    pub async fn fetch_one<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<Membership, sqlx::Error> {
+        sqlx::query_as!(
+            Membership,
+            "SELECT \"user_id\", \"group\" AS \"group:Group\", \"role\" FROM \"memberships\" WHERE \"user_id\" = $1 AND \"group\" = $2",
+            self.user_id, self.group,
+        )
+            .fetch_one(executor)
+            .await
+    }
+}
+    "#;
+
+    compare_computed_to_expected(input_str, output);
+    Ok(())
+}
@@ -0,0 +1,4 @@
+mod relations;
+#[cfg(test)]
+mod test_relations;
+pub use relations::get_relations_impl;
@@ -0,0 +1,350 @@
+use convert_case::{Case, Casing};
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::DeriveInput;
+
+use crate::modules::many_query_builder::utils::{
+    get_many_query_builder_push_filters, get_many_query_builder_struct_name,
+};
+
+use crate::common::utils::{
+    get_belongs_to_relations, get_column_const_name, get_column_names, get_column_sql,
+    get_dbset_name, get_has_many_relations, get_inner_option_type, get_key_fields,
+    get_many_to_many_relations, get_struct_name, get_table_name, pluralize, quote_identifier,
+    quote_table_path, ManyToManyRelation,
+};
+
+pub fn get_relations_impl(input: &DeriveInput) -> proc_macro2::TokenStream {
+    let struct_name = get_struct_name(input);
+    let dbset_name = get_dbset_name(input);
+    let belongs_to_relations = get_belongs_to_relations(input);
+    let has_many_relations = get_has_many_relations(input);
+    let many_to_many_relations = get_many_to_many_relations(input);
+
+    if belongs_to_relations.is_empty()
+        && has_many_relations.is_empty()
+        && many_to_many_relations.is_empty()
+    {
+        return quote! {};
+    }
+
+    let belongs_to_methods = belongs_to_relations.iter().map(|relation| {
+        let related = &relation.related;
+        let related_dbset_name = &relation.related_dbset;
+        let foreign_key = relation.foreign_key;
+        let key_eq_method = quote::format_ident!("{}_eq", relation.references);
+        let method_name = &relation.name;
+
+        // An optional foreign key may point at nothing
+        if get_inner_option_type(relation.foreign_key_type).is_some() {
+            quote! {
+                pub async fn #method_name<'e, E: sqlx::PgExecutor<'e>>(
+                    &self,
+                    executor: E,
+                ) -> Result<Option<#related>, sqlx::Error> {
+                    match &self.#foreign_key {
+                        Some(key) => #related_dbset_name::one()
+                            .#key_eq_method(key.clone())
+                            .fetch_optional(executor)
+                            .await,
+                        None => Ok(None),
+                    }
+                }
+            }
+        } else {
+            quote! {
+                pub async fn #method_name<'e, E: sqlx::PgExecutor<'e>>(
+                    &self,
+                    executor: E,
+                ) -> Result<#related, sqlx::Error> {
+                    #related_dbset_name::one()
+                        .#key_eq_method(self.#foreign_key.clone())
+                        .fetch_one(executor)
+                        .await
+                }
+            }
+        }
+    });
+
+    // Loads the related rows for a whole slice in one query, keyed by the referenced field
+    let belongs_to_loaders = belongs_to_relations.iter().map(|relation| {
+        let related = &relation.related;
+        let related_dbset_name = &relation.related_dbset;
+        let foreign_key = relation.foreign_key;
+        let references = &relation.references;
+        let references_column = get_column_const_name(references);
+        let loader_name =
+            quote::format_ident!("load_{}", pluralize(&relation.name.unraw().to_string()));
+
+        let (key_type, collect_keys) = match get_inner_option_type(relation.foreign_key_type) {
+            Some(inner_type) => (
+                inner_type,
+                quote! { rows.iter().filter_map(|row| row.#foreign_key.clone()) },
+            ),
+            None => (
+                relation.foreign_key_type,
+                quote! { rows.iter().map(|row| row.#foreign_key.clone()) },
+            ),
+        };
+
+        quote! {
+            pub async fn #loader_name<'e, E: sqlx::PgExecutor<'e>>(
+                rows: &[#struct_name],
+                executor: E,
+            ) -> Result<std::collections::HashMap<#key_type, #related>, sqlx::Error> {
+                let keys = #collect_keys.collect::<Vec<_>>();
+                let related_rows = #related_dbset_name::fetch_by_column_any(
+                    #related_dbset_name::#references_column,
+                    keys,
+                    executor,
+                )
+                .await?;
+                Ok(related_rows
+                    .into_iter()
+                    .map(|related_row| (related_row.#references.clone(), related_row))
+                    .collect())
+            }
+        }
+    });
+
+    // has_many and many_to_many relations are found through this struct's key
+    let parent_key = if has_many_relations.is_empty() && many_to_many_relations.is_empty() {
+        None
+    } else {
+        match get_key_fields(input).as_slice() {
+            [(key_name, key_type)] => Some((*key_name, *key_type)),
+            _ => panic!(
+                "has_many and many_to_many relations on {struct_name} need exactly one #[key] field"
+            ),
+        }
+    };
+
+    let has_many_methods = has_many_relations.iter().map(|relation| {
+        let (key_name, _) = parent_key.expect("has_many needs a key");
+        let related_dbset_name = &relation.related_dbset;
+        let related_filtered_many_builder_name =
+            quote::format_ident!("{}FilteredManyQueryBuilder", related_dbset_name);
+        let foreign_key_eq_method = quote::format_ident!("{}_eq", relation.foreign_key);
+        let method_name = &relation.name;
+
+        quote! {
+            pub fn #method_name(&self) -> #related_filtered_many_builder_name {
+                #related_dbset_name::many().#foreign_key_eq_method(self.#key_name.clone())
+            }
+        }
+    });
+
+    // Loads the related rows for a whole slice in one query, grouped by this struct's key
+    let has_many_loaders = has_many_relations.iter().map(|relation| {
+        let (key_name, key_type) = parent_key.expect("has_many needs a key");
+        let related = &relation.related;
+        let related_dbset_name = &relation.related_dbset;
+        let foreign_key = &relation.foreign_key;
+        let foreign_key_column = get_column_const_name(foreign_key);
+        let loader_name = quote::format_ident!("load_{}", relation.name);
+
+        quote! {
+            pub async fn #loader_name<'e, E: sqlx::PgExecutor<'e>>(
+                rows: &[#struct_name],
+                executor: E,
+            ) -> Result<std::collections::HashMap<#key_type, Vec<#related>>, sqlx::Error> {
+                let keys = rows.iter().map(|row| row.#key_name.clone()).collect::<Vec<_>>();
+                let mut grouped = keys
+                    .iter()
+                    .map(|key| (key.clone(), Vec::new()))
+                    .collect::<std::collections::HashMap<#key_type, Vec<#related>>>();
+                let related_rows =
+                    #related_dbset_name::fetch_by_column_any(
+                        #related_dbset_name::#foreign_key_column,
+                        keys,
+                        executor,
+                    )
+                    .await?;
+                for related_row in related_rows {
+                    // The foreign key may or may not be optional on the related struct
+                    let key: Option<#key_type> = related_row.#foreign_key.clone().into();
+                    if let Some(group) = key.and_then(|key| grouped.get_mut(&key)) {
+                        group.push(related_row);
+                    }
+                }
+                Ok(grouped)
+            }
+        }
+    });
+
+    let many_to_many_methods = many_to_many_relations.iter().map(|relation| {
+        let (key_name, _) = parent_key.expect("many_to_many needs a key");
+        let ManyToManyRelation {
+            related,
+            related_dbset: related_dbset_name,
+            name: method_name,
+            through,
+            local,
+            remote,
+            ..
+        } = relation;
+        let related_key_alias_name = quote::format_ident!("{}Key", related_dbset_name);
+        let related_snake = related
+            .to_string()
+            .from_case(Case::Pascal)
+            .to_case(Case::Snake);
+        // `tags()` links with `add_tag()`, a custom `name = "labels"` with `add_to_labels()`
+        let (add_method_name, remove_method_name) = if relation.has_custom_name {
+            (
+                quote::format_ident!("add_to_{}", method_name),
+                quote::format_ident!("remove_from_{}", method_name),
+            )
+        } else {
+            (
+                quote::format_ident!("add_{}", related_snake),
+                quote::format_ident!("remove_{}", related_snake),
+            )
+        };
+        let key_param = quote::format_ident!("{}_key", related_snake);
+
+        let through = quote_table_path(through);
+        let local = quote_identifier(local);
+        let remote = quote_identifier(remote);
+        let select_keys = format!("SELECT {remote} FROM {through} WHERE {local} = ");
+        let add_query = format!(
+            "INSERT INTO {through} ({local}, {remote}) VALUES ($1, $2) ON CONFLICT DO NOTHING"
+        );
+        let remove_query = format!("DELETE FROM {through} WHERE {local} = $1 AND {remote} = $2");
+
+        quote! {
+            pub async fn #method_name<'e, E: sqlx::PgExecutor<'e>>(
+                &self,
+                executor: E,
+            ) -> Result<Vec<#related>, sqlx::Error> {
+                #related_dbset_name::fetch_by_keys_in(#select_keys, self.#key_name.clone(), executor).await
+            }
+
+            pub async fn #add_method_name<'e, E: sqlx::PgExecutor<'e>>(
+                &self,
+                #key_param: #related_key_alias_name,
+                executor: E,
+            ) -> Result<u64, sqlx::Error> {
+                let result = sqlx::query!(
+                    #add_query,
+                    self.#key_name,
+                    #key_param,
+                )
+                    .execute(executor)
+                    .await?;
+                Ok(result.rows_affected())
+            }
+
+            pub async fn #remove_method_name<'e, E: sqlx::PgExecutor<'e>>(
+                &self,
+                #key_param: #related_key_alias_name,
+                executor: E,
+            ) -> Result<u64, sqlx::Error> {
+                let result = sqlx::query!(
+                    #remove_query,
+                    self.#key_name,
+                    #key_param,
+                )
+                    .execute(executor)
+                    .await?;
+                Ok(result.rows_affected())
+            }
+        }
+    });
+
+    // belongs_to relations can also be fetched alongside many() in a single LEFT JOIN
+    let table_name = get_table_name(input);
+    let many_query_builder_name = get_many_query_builder_struct_name(input);
+    let row_fields = get_column_names(input)
+        .iter()
+        .map(|column| format!("{table_name}.{}", quote_identifier(column)))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let push_filters = get_many_query_builder_push_filters(input, &format!("{table_name}."));
+    let take_filters = if push_filters.is_empty() {
+        quote! {}
+    } else {
+        quote! { let filters = self.filters; }
+    };
+
+    let belongs_to_joins = belongs_to_relations.iter().map(|relation| {
+        let related = &relation.related;
+        let related_dbset_name = &relation.related_dbset;
+        let name = &relation.name;
+        let with_method_name = quote::format_ident!("with_{}", name);
+        let with_builder_name = quote::format_ident!(
+            "{}ManyWith{}QueryBuilder",
+            dbset_name,
+            name.unraw().to_string().from_case(Case::Snake).to_case(Case::Pascal)
+        );
+        let alias = name.to_string();
+        let select_start = format!("SELECT {row_fields}, {{}} FROM {table_name}");
+        // `references` is a field on the related set, so its column is looked up there
+        let references_column = get_column_const_name(&relation.references);
+        let join_on = format!(
+            "\"{alias}\".\"{{}}\" = {table_name}.{}",
+            get_column_sql(input, relation.foreign_key)
+        );
+        let related_key_column = format!("{alias}__{{}}");
+        let related_prefix = format!("{alias}__");
+
+        quote! {
+            pub struct #with_builder_name<Filtered = NotSet> {
+                filters: #many_query_builder_name<Filtered>,
+            }
+
+            impl<Filtered> #many_query_builder_name<Filtered> {
+                pub fn #with_method_name(self) -> #with_builder_name<Filtered> {
+                    #with_builder_name { filters: self }
+                }
+            }
+
+            impl<Filtered> #with_builder_name<Filtered> {
+                pub async fn fetch_all<'e, E: sqlx::PgExecutor<'e>>(
+                    self,
+                    executor: E,
+                ) -> Result<Vec<(#struct_name, Option<#related>)>, sqlx::Error> {
+                    let mut query_builder = sqlx::QueryBuilder::<sqlx::Postgres>::new(format!(
+                        #select_start,
+                        #related_dbset_name::aliased_row_fields(#alias)
+                    ));
+                    let related_key = #related_dbset_name::#references_column;
+                    query_builder.push(#related_dbset_name::left_join(
+                        #alias,
+                        &format!(#join_on, related_key),
+                    ));
+                    #take_filters
+                    #push_filters
+                    let rows = query_builder.build().fetch_all(executor).await?;
+                    rows.iter()
+                        .map(|row| {
+                            let value = <#struct_name as sqlx::FromRow<sqlx::postgres::PgRow>>::from_row(row)?;
+                            // Nothing joined when the related key came back NULL
+                            let related_key_column = format!(#related_key_column, related_key);
+                            let related_value = if sqlx::ValueRef::is_null(&sqlx::Row::try_get_raw(row, related_key_column.as_str())?) {
+                                None
+                            } else {
+                                Some(#related::from_row_prefixed(row, #related_prefix)?)
+                            };
+                            Ok((value, related_value))
+                        })
+                        .collect()
+                }
+            }
+        }
+    });
+
+    quote! {
+        #(#belongs_to_joins)*
+
+        impl #struct_name {
+            #(#belongs_to_methods)*
+            #(#has_many_methods)*
+            #(#many_to_many_methods)*
+        }
+
+        impl #dbset_name {
+            #(#belongs_to_loaders)*
+            #(#has_many_loaders)*
+        }
+    }
+}
@@ -0,0 +1,779 @@
+use pretty_assertions::assert_eq;
+
+use crate::common::utils::{
+    derive_input_from_string, pretty_print_tokenstream, tokenstream_from_string,
+};
+
+use super::relations;
+
+pub fn compare_computed_to_expected(input_string: &str, output_string: &str) {
+    let input_tokens = derive_input_from_string(input_string).expect("Could not get tokens");
+    let out_tokens = relations::get_relations_impl(&input_tokens);
+    let pretty_out = pretty_print_tokenstream(out_tokens);
+    let pretty_expected =
+        pretty_print_tokenstream(tokenstream_from_string(output_string).expect("coudnt"));
+    assert_eq!(pretty_out.to_string(), pretty_expected);
+}
+
+#[test]
+fn can_parse_belongs_to_relations_on_player() -> Result<(), String> {
+    let input_str = r#"
+        #[derive(DbSet, Debug, Clone)]
+        #[dbset(table_name = "players")]
+        #[relation(belongs_to = "Team", foreign_key = "team_id")]
+        #[relation(belongs_to = "Team", foreign_key = "coach_team_id")]
+        pub struct Player {
+            #[key]
+            id: i32,
+            name: String,
+            team_id: i32,
+            coach_team_id: Option<i32>,
+        }
+    "#;
+
+    let output = r#"
+pub struct PlayerDbSetManyWithTeamQueryBuilder<Filtered = NotSet> {
+    filters: PlayerDbSetManyQueryBuilder<Filtered>,
+}
+impl<Filtered> PlayerDbSetManyQueryBuilder<Filtered> {
+    pub fn with_team(self) -> PlayerDbSetManyWithTeamQueryBuilder<Filtered> {
+        PlayerDbSetManyWithTeamQueryBuilder {
+            filters: self,
+        }
+    }
+}
+impl<Filtered> PlayerDbSetManyWithTeamQueryBuilder<Filtered> {
+    pub async fn fetch_all<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<Vec<(Player, Option<Team>)>, sqlx::Error> {
+        let mut query_builder = sqlx::QueryBuilder::<
+            sqlx::Postgres,
+        >::new(
+            format!(
+                "SELECT \"players\".\"id\", \"players\".\"name\", \"players\".\"team_id\", \"players\".\"coach_team_id\", {} FROM \"players\"",
+                TeamDbSet::aliased_row_fields("team")
+            ),
+        );
+        let related_key = TeamDbSet::ID_COLUMN;
+        query_builder
+            .push(
+                TeamDbSet::left_join(
+                    "team",
+                    &format!("\"team\".\"{}\" = \"players\".\"team_id\"", related_key),
+                ),
+            );
+        let filters = self.filters;
+        if filters.name.is_some() || filters.team_id.is_some()
+            || filters.coach_team_id.is_some() || !filters.relation_filters.is_empty()
+        {
+            query_builder.push(" WHERE ");
+        }
+        let mut where_fields = query_builder.separated(" AND ");
+        if let Some(name) = filters.name {
+            where_fields.push("\"players\".\"name\" = ");
+            where_fields.push_bind_unseparated(name);
+        }
+        if let Some(team_id) = filters.team_id {
+            where_fields.push("\"players\".\"team_id\" = ");
+            where_fields.push_bind_unseparated(team_id);
+        }
+        if let Some(coach_team_id) = filters.coach_team_id {
+            where_fields.push("\"players\".\"coach_team_id\" = ");
+            where_fields.push_bind_unseparated(coach_team_id);
+        }
+        for relation_filter in filters.relation_filters {
+            relation_filter(&mut where_fields, "\"players\".");
+        }
+        let rows = query_builder.build().fetch_all(executor).await?;
+        rows.iter()
+            .map(|row| {
+                let value = <Player as sqlx::FromRow<
+                    sqlx::postgres::PgRow,
+                >>::from_row(row)?;
+                let related_key_column = format!("team__{}", related_key);
+                let related_value = if sqlx::ValueRef::is_null(
+                    &sqlx::Row::try_get_raw(row, related_key_column.as_str())?,
+                ) {
+                    None
+                } else {
+                    Some(Team::from_row_prefixed(row, "team__")?)
+                };
+                Ok((value, related_value))
+            })
+            .collect()
+    }
+}
+pub struct PlayerDbSetManyWithCoachTeamQueryBuilder<Filtered = NotSet> {
+    filters: PlayerDbSetManyQueryBuilder<Filtered>,
+}
+impl<Filtered> PlayerDbSetManyQueryBuilder<Filtered> {
+    pub fn with_coach_team(self) -> PlayerDbSetManyWithCoachTeamQueryBuilder<Filtered> {
+        PlayerDbSetManyWithCoachTeamQueryBuilder {
+            filters: self,
+        }
+    }
+}
+impl<Filtered> PlayerDbSetManyWithCoachTeamQueryBuilder<Filtered> {
+    pub async fn fetch_all<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<Vec<(Player, Option<Team>)>, sqlx::Error> {
+        let mut query_builder = sqlx::QueryBuilder::<
+            sqlx::Postgres,
+        >::new(
+            format!(
+                "SELECT \"players\".\"id\", \"players\".\"name\", \"players\".\"team_id\", \"players\".\"coach_team_id\", {} FROM \"players\"",
+                TeamDbSet::aliased_row_fields("coach_team")
+            ),
+        );
+        let related_key = TeamDbSet::ID_COLUMN;
+        query_builder
+            .push(
+                TeamDbSet::left_join(
+                    "coach_team",
+                    &format!("\"coach_team\".\"{}\" = \"players\".\"coach_team_id\"", related_key),
+                ),
+            );
+        let filters = self.filters;
+        if filters.name.is_some() || filters.team_id.is_some()
+            || filters.coach_team_id.is_some() || !filters.relation_filters.is_empty()
+        {
+            query_builder.push(" WHERE ");
+        }
+        let mut where_fields = query_builder.separated(" AND ");
+        if let Some(name) = filters.name {
+            where_fields.push("\"players\".\"name\" = ");
+            where_fields.push_bind_unseparated(name);
+        }
+        if let Some(team_id) = filters.team_id {
+            where_fields.push("\"players\".\"team_id\" = ");
+            where_fields.push_bind_unseparated(team_id);
+        }
+        if let Some(coach_team_id) = filters.coach_team_id {
+            where_fields.push("\"players\".\"coach_team_id\" = ");
+            where_fields.push_bind_unseparated(coach_team_id);
+        }
+        for relation_filter in filters.relation_filters {
+            relation_filter(&mut where_fields, "\"players\".");
+        }
+        let rows = query_builder.build().fetch_all(executor).await?;
+        rows.iter()
+            .map(|row| {
+                let value = <Player as sqlx::FromRow<
+                    sqlx::postgres::PgRow,
+                >>::from_row(row)?;
+                let related_key_column = format!("coach_team__{}", related_key);
+                let related_value = if sqlx::ValueRef::is_null(
+                    &sqlx::Row::try_get_raw(row, related_key_column.as_str())?,
+                ) {
+                    None
+                } else {
+                    Some(Team::from_row_prefixed(row, "coach_team__")?)
+                };
+                Ok((value, related_value))
+            })
+            .collect()
+    }
+}
+impl Player {
+    pub async fn team<'e, E: sqlx::PgExecutor<'e>>(
+        &self,
+        executor: E,
+    ) -> Result<Team, sqlx::Error> {
+        TeamDbSet::one().id_eq(self.team_id.clone()).fetch_one(executor).await
+    }
+    pub async fn coach_team<'e, E: sqlx::PgExecutor<'e>>(
+        &self,
+        executor: E,
+    ) -> Result<Option<Team>, sqlx::Error> {
+        match &self.coach_team_id {
+            Some(key) => {
+                TeamDbSet::one().id_eq(key.clone()).fetch_optional(executor).await
+            }
+            None => Ok(None),
+        }
+    }
+}
+impl PlayerDbSet {
+    pub async fn load_teams<'e, E: sqlx::PgExecutor<'e>>(
+        rows: &[Player],
+        executor: E,
+    ) -> Result<std::collections::HashMap<i32, Team>, sqlx::Error> {
+        let keys = rows.iter().map(|row| row.team_id.clone()).collect::<Vec<_>>();
+        let related_rows = TeamDbSet::fetch_by_column_any(TeamDbSet::ID_COLUMN, keys, executor).await?;
+        Ok(
+            related_rows
+                .into_iter()
+                .map(|related_row| (related_row.id.clone(), related_row))
+                .collect(),
+        )
+    }
+    pub async fn load_coach_teams<'e, E: sqlx::PgExecutor<'e>>(
+        rows: &[Player],
+        executor: E,
+    ) -> Result<std::collections::HashMap<i32, Team>, sqlx::Error> {
+        let keys = rows
+            .iter()
+            .filter_map(|row| row.coach_team_id.clone())
+            .collect::<Vec<_>>();
+        let related_rows = TeamDbSet::fetch_by_column_any(TeamDbSet::ID_COLUMN, keys, executor).await?;
+        Ok(
+            related_rows
+                .into_iter()
+                .map(|related_row| (related_row.id.clone(), related_row))
+                .collect(),
+        )
+    }
+}
+    "#;
+
+    compare_computed_to_expected(input_str, output);
+    Ok(())
+}
+
+#[test]
+#[should_panic(expected = "foreign_key team is not a field on Player")]
+fn belongs_to_with_unknown_foreign_key_fails() {
+    let input_str = r#"
+        #[derive(DbSet, Debug)]
+        #[dbset(table_name = "players")]
+        #[relation(belongs_to = "Team", foreign_key = "team")]
+        pub struct Player {
+            #[key]
+            id: i32,
+            team_id: i32,
+        }
+    "#;
+
+    compare_computed_to_expected(input_str, "");
+}
+
+#[test]
+fn can_parse_has_many_relation_on_user() -> Result<(), String> {
+    let input_str = r#"
+        #[derive(DbSet, Debug, Clone)]
+        #[dbset(table_name = "users")]
+        #[dbset(has_many(Post, foreign_key = "author_id"))]
+        pub struct User {
+            #[key]
+            id: String,
+            name: String,
+        }
+    "#;
+
+    let output = r#"
+impl User {
+    pub fn posts(&self) -> PostDbSetFilteredManyQueryBuilder {
+        PostDbSet::many().author_id_eq(self.id.clone())
+    }
+}
+impl UserDbSet {
+    pub async fn load_posts<'e, E: sqlx::PgExecutor<'e>>(
+        rows: &[User],
+        executor: E,
+    ) -> Result<std::collections::HashMap<String, Vec<Post>>, sqlx::Error> {
+        let keys = rows.iter().map(|row| row.id.clone()).collect::<Vec<_>>();
+        let mut grouped = keys
+            .iter()
+            .map(|key| (key.clone(), Vec::new()))
+            .collect::<std::collections::HashMap<String, Vec<Post>>>();
+        let related_rows = PostDbSet::fetch_by_column_any(
+                PostDbSet::AUTHOR_ID_COLUMN,
+                keys,
+                executor,
+            )
+            .await?;
+        for related_row in related_rows {
+            let key: Option<String> = related_row.author_id.clone().into();
+            if let Some(group) = key.and_then(|key| grouped.get_mut(&key)) {
+                group.push(related_row);
+            }
+        }
+        Ok(grouped)
+    }
+}
+    "#;
+
+    compare_computed_to_expected(input_str, output);
+    Ok(())
+}
+
+#[test]
+fn can_parse_many_to_many_relation_on_post() -> Result<(), String> {
+    let input_str = r#"
+        #[derive(DbSet, Debug, Clone)]
+        #[dbset(table_name = "posts")]
+        #[dbset(many_to_many(Tag, through = "post_tags", local = "post_id", remote = "tag_id"))]
+        pub struct Post {
+            #[key]
+            id: i32,
+            title: String,
+        }
+    "#;
+
+    let output = r#"
+impl Post {
+    pub async fn tags<'e, E: sqlx::PgExecutor<'e>>(
+        &self,
+        executor: E,
+    ) -> Result<Vec<Tag>, sqlx::Error> {
+        TagDbSet::fetch_by_keys_in(
+                "SELECT \"tag_id\" FROM \"post_tags\" WHERE \"post_id\" = ",
+                self.id.clone(),
+                executor,
+            )
+            .await
+    }
+    pub async fn add_tag<'e, E: sqlx::PgExecutor<'e>>(
+        &self,
+        tag_key: TagDbSetKey,
+        executor: E,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "INSERT INTO \"post_tags\" (\"post_id\", \"tag_id\") VALUES ($1, $2) ON CONFLICT DO NOTHING",
+            self.id, tag_key,
+        )
+            .execute(executor)
+            .await?;
+        Ok(result.rows_affected())
+    }
+    pub async fn remove_tag<'e, E: sqlx::PgExecutor<'e>>(
+        &self,
+        tag_key: TagDbSetKey,
+        executor: E,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM \"post_tags\" WHERE \"post_id\" = $1 AND \"tag_id\" = $2", self.id, tag_key,
+        )
+            .execute(executor)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
+impl PostDbSet {}
+    "#;
+
+    compare_computed_to_expected(input_str, output);
+    Ok(())
+}
+
+#[test]
+#[should_panic(expected = "many_to_many relation to Tag on Post needs a through")]
+fn many_to_many_without_through_table_fails() {
+    let input_str = r#"
+        #[derive(DbSet, Debug)]
+        #[dbset(table_name = "posts")]
+        #[dbset(many_to_many(Tag, local = "post_id", remote = "tag_id"))]
+        pub struct Post {
+            #[key]
+            id: i32,
+        }
+    "#;
+
+    compare_computed_to_expected(input_str, "");
+}
+
+#[test]
+fn can_parse_named_has_many_relations_on_shop() -> Result<(), String> {
+    let input_str = r#"
+        #[derive(DbSet, Debug, Clone)]
+        #[dbset(table_name = "shops")]
+        #[dbset(has_many(Category, foreign_key = "shop_id"))]
+        #[dbset(has_many(Address, foreign_key = "shop_id", name = "locations", set_name = "AddressBook"))]
+        pub struct Shop {
+            #[key]
+            id: i32,
+            name: String,
+        }
+    "#;
+
+    let output = r#"
+impl Shop {
+    pub fn categories(&self) -> CategoryDbSetFilteredManyQueryBuilder {
+        CategoryDbSet::many().shop_id_eq(self.id.clone())
+    }
+    pub fn locations(&self) -> AddressBookFilteredManyQueryBuilder {
+        AddressBook::many().shop_id_eq(self.id.clone())
+    }
+}
+impl ShopDbSet {
+    pub async fn load_categories<'e, E: sqlx::PgExecutor<'e>>(
+        rows: &[Shop],
+        executor: E,
+    ) -> Result<std::collections::HashMap<i32, Vec<Category>>, sqlx::Error> {
+        let keys = rows.iter().map(|row| row.id.clone()).collect::<Vec<_>>();
+        let mut grouped = keys
+            .iter()
+            .map(|key| (key.clone(), Vec::new()))
+            .collect::<std::collections::HashMap<i32, Vec<Category>>>();
+        let related_rows = CategoryDbSet::fetch_by_column_any(
+                CategoryDbSet::SHOP_ID_COLUMN,
+                keys,
+                executor,
+            )
+            .await?;
+        for related_row in related_rows {
+            let key: Option<i32> = related_row.shop_id.clone().into();
+            if let Some(group) = key.and_then(|key| grouped.get_mut(&key)) {
+                group.push(related_row);
+            }
+        }
+        Ok(grouped)
+    }
+    pub async fn load_locations<'e, E: sqlx::PgExecutor<'e>>(
+        rows: &[Shop],
+        executor: E,
+    ) -> Result<std::collections::HashMap<i32, Vec<Address>>, sqlx::Error> {
+        let keys = rows.iter().map(|row| row.id.clone()).collect::<Vec<_>>();
+        let mut grouped = keys
+            .iter()
+            .map(|key| (key.clone(), Vec::new()))
+            .collect::<std::collections::HashMap<i32, Vec<Address>>>();
+        let related_rows = AddressBook::fetch_by_column_any(
+                AddressBook::SHOP_ID_COLUMN,
+                keys,
+                executor,
+            )
+            .await?;
+        for related_row in related_rows {
+            let key: Option<i32> = related_row.shop_id.clone().into();
+            if let Some(group) = key.and_then(|key| grouped.get_mut(&key)) {
+                group.push(related_row);
+            }
+        }
+        Ok(grouped)
+    }
+}
+    "#;
+
+    compare_computed_to_expected(input_str, output);
+    Ok(())
+}
+
+#[test]
+fn can_parse_named_many_to_many_relations_on_article() -> Result<(), String> {
+    let input_str = r#"
+        #[derive(DbSet, Debug, Clone)]
+        #[dbset(table_name = "articles")]
+        #[dbset(many_to_many(Category, through = "article_categories", local = "article_id", remote = "category_id"))]
+        #[dbset(many_to_many(Tag, through = "article_labels", local = "article_id", remote = "label_id", name = "labels", set_name = "LabelSet"))]
+        pub struct Article {
+            #[key]
+            id: i32,
+            title: String,
+        }
+    "#;
+
+    let output = r#"
+impl Article {
+    pub async fn categories<'e, E: sqlx::PgExecutor<'e>>(
+        &self,
+        executor: E,
+    ) -> Result<Vec<Category>, sqlx::Error> {
+        CategoryDbSet::fetch_by_keys_in(
+                "SELECT \"category_id\" FROM \"article_categories\" WHERE \"article_id\" = ",
+                self.id.clone(),
+                executor,
+            )
+            .await
+    }
+    pub async fn add_category<'e, E: sqlx::PgExecutor<'e>>(
+        &self,
+        category_key: CategoryDbSetKey,
+        executor: E,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "INSERT INTO \"article_categories\" (\"article_id\", \"category_id\") VALUES ($1, $2) ON CONFLICT DO NOTHING",
+            self.id, category_key,
+        )
+            .execute(executor)
+            .await?;
+        Ok(result.rows_affected())
+    }
+    pub async fn remove_category<'e, E: sqlx::PgExecutor<'e>>(
+        &self,
+        category_key: CategoryDbSetKey,
+        executor: E,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM \"article_categories\" WHERE \"article_id\" = $1 AND \"category_id\" = $2",
+            self.id, category_key,
+        )
+            .execute(executor)
+            .await?;
+        Ok(result.rows_affected())
+    }
+    pub async fn labels<'e, E: sqlx::PgExecutor<'e>>(
+        &self,
+        executor: E,
+    ) -> Result<Vec<Tag>, sqlx::Error> {
+        LabelSet::fetch_by_keys_in(
+                "SELECT \"label_id\" FROM \"article_labels\" WHERE \"article_id\" = ",
+                self.id.clone(),
+                executor,
+            )
+            .await
+    }
+    pub async fn add_to_labels<'e, E: sqlx::PgExecutor<'e>>(
+        &self,
+        tag_key: LabelSetKey,
+        executor: E,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "INSERT INTO \"article_labels\" (\"article_id\", \"label_id\") VALUES ($1, $2) ON CONFLICT DO NOTHING",
+            self.id, tag_key,
+        )
+            .execute(executor)
+            .await?;
+        Ok(result.rows_affected())
+    }
+    pub async fn remove_from_labels<'e, E: sqlx::PgExecutor<'e>>(
+        &self,
+        tag_key: LabelSetKey,
+        executor: E,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM \"article_labels\" WHERE \"article_id\" = $1 AND \"label_id\" = $2",
+            self.id, tag_key,
+        )
+            .execute(executor)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
+impl ArticleDbSet {}
+    "#;
+
+    compare_computed_to_expected(input_str, output);
+    Ok(())
+}
+
+#[test]
+fn can_parse_belongs_to_relations_with_references_and_name() -> Result<(), String> {
+    let input_str = r#"
+#[derive(DbSet, Debug, Clone)]
+#[dbset(table_name = "products")]
+#[relation(belongs_to = "Category", foreign_key = "category_code", references = "code")]
+#[relation(belongs_to = "Address", foreign_key = "warehouse", name = "warehouse_address")]
+pub struct Product {
+    #[key]
+    id: i32,
+    category_code: String,
+    warehouse: Option<i32>,
+}
+    "#;
+
+    let output = r#"
+pub struct ProductDbSetManyWithCategoryQueryBuilder<Filtered = NotSet> {
+    filters: ProductDbSetManyQueryBuilder<Filtered>,
+}
+impl<Filtered> ProductDbSetManyQueryBuilder<Filtered> {
+    pub fn with_category(self) -> ProductDbSetManyWithCategoryQueryBuilder<Filtered> {
+        ProductDbSetManyWithCategoryQueryBuilder {
+            filters: self,
+        }
+    }
+}
+impl<Filtered> ProductDbSetManyWithCategoryQueryBuilder<Filtered> {
+    pub async fn fetch_all<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<Vec<(Product, Option<Category>)>, sqlx::Error> {
+        let mut query_builder = sqlx::QueryBuilder::<
+            sqlx::Postgres,
+        >::new(
+            format!(
+                "SELECT \"products\".\"id\", \"products\".\"category_code\", \"products\".\"warehouse\", {} FROM \"products\"",
+                CategoryDbSet::aliased_row_fields("category")
+            ),
+        );
+        let related_key = CategoryDbSet::CODE_COLUMN;
+        query_builder
+            .push(
+                CategoryDbSet::left_join(
+                    "category",
+                    &format!(
+                        "\"category\".\"{}\" = \"products\".\"category_code\"",
+                        related_key
+                    ),
+                ),
+            );
+        let filters = self.filters;
+        if filters.category_code.is_some() || filters.warehouse.is_some()
+            || !filters.relation_filters.is_empty()
+        {
+            query_builder.push(" WHERE ");
+        }
+        let mut where_fields = query_builder.separated(" AND ");
+        if let Some(category_code) = filters.category_code {
+            where_fields.push("\"products\".\"category_code\" = ");
+            where_fields.push_bind_unseparated(category_code);
+        }
+        if let Some(warehouse) = filters.warehouse {
+            where_fields.push("\"products\".\"warehouse\" = ");
+            where_fields.push_bind_unseparated(warehouse);
+        }
+        for relation_filter in filters.relation_filters {
+            relation_filter(&mut where_fields, "\"products\".");
+        }
+        let rows = query_builder.build().fetch_all(executor).await?;
+        rows.iter()
+            .map(|row| {
+                let value = <Product as sqlx::FromRow<
+                    sqlx::postgres::PgRow,
+                >>::from_row(row)?;
+                let related_key_column = format!("category__{}", related_key);
+                let related_value = if sqlx::ValueRef::is_null(
+                    &sqlx::Row::try_get_raw(row, related_key_column.as_str())?,
+                ) {
+                    None
+                } else {
+                    Some(Category::from_row_prefixed(row, "category__")?)
+                };
+                Ok((value, related_value))
+            })
+            .collect()
+    }
+}
+pub struct ProductDbSetManyWithWarehouseAddressQueryBuilder<Filtered = NotSet> {
+    filters: ProductDbSetManyQueryBuilder<Filtered>,
+}
+impl<Filtered> ProductDbSetManyQueryBuilder<Filtered> {
+    pub fn with_warehouse_address(
+        self,
+    ) -> ProductDbSetManyWithWarehouseAddressQueryBuilder<Filtered> {
+        ProductDbSetManyWithWarehouseAddressQueryBuilder {
+            filters: self,
+        }
+    }
+}
+impl<Filtered> ProductDbSetManyWithWarehouseAddressQueryBuilder<Filtered> {
+    pub async fn fetch_all<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<Vec<(Product, Option<Address>)>, sqlx::Error> {
+        let mut query_builder = sqlx::QueryBuilder::<
+            sqlx::Postgres,
+        >::new(
+            format!(
+                "SELECT \"products\".\"id\", \"products\".\"category_code\", \"products\".\"warehouse\", {} FROM \"products\"",
+                AddressDbSet::aliased_row_fields("warehouse_address")
+            ),
+        );
+        let related_key = AddressDbSet::ID_COLUMN;
+        query_builder
+            .push(
+                AddressDbSet::left_join(
+                    "warehouse_address",
+                    &format!(
+                        "\"warehouse_address\".\"{}\" = \"products\".\"warehouse\"",
+                        related_key
+                    ),
+                ),
+            );
+        let filters = self.filters;
+        if filters.category_code.is_some() || filters.warehouse.is_some()
+            || !filters.relation_filters.is_empty()
+        {
+            query_builder.push(" WHERE ");
+        }
+        let mut where_fields = query_builder.separated(" AND ");
+        if let Some(category_code) = filters.category_code {
+            where_fields.push("\"products\".\"category_code\" = ");
+            where_fields.push_bind_unseparated(category_code);
+        }
+        if let Some(warehouse) = filters.warehouse {
+            where_fields.push("\"products\".\"warehouse\" = ");
+            where_fields.push_bind_unseparated(warehouse);
+        }
+        for relation_filter in filters.relation_filters {
+            relation_filter(&mut where_fields, "\"products\".");
+        }
+        let rows = query_builder.build().fetch_all(executor).await?;
+        rows.iter()
+            .map(|row| {
+                let value = <Product as sqlx::FromRow<
+                    sqlx::postgres::PgRow,
+                >>::from_row(row)?;
+                let related_key_column = format!("warehouse_address__{}", related_key);
+                let related_value = if sqlx::ValueRef::is_null(
+                    &sqlx::Row::try_get_raw(row, related_key_column.as_str())?,
+                ) {
+                    None
+                } else {
+                    Some(Address::from_row_prefixed(row, "warehouse_address__")?)
+                };
+                Ok((value, related_value))
+            })
+            .collect()
+    }
+}
+impl Product {
+    pub async fn category<'e, E: sqlx::PgExecutor<'e>>(
+        &self,
+        executor: E,
+    ) -> Result<Category, sqlx::Error> {
+        CategoryDbSet::one()
+            .code_eq(self.category_code.clone())
+            .fetch_one(executor)
+            .await
+    }
+    pub async fn warehouse_address<'e, E: sqlx::PgExecutor<'e>>(
+        &self,
+        executor: E,
+    ) -> Result<Option<Address>, sqlx::Error> {
+        match &self.warehouse {
+            Some(key) => {
+                AddressDbSet::one().id_eq(key.clone()).fetch_optional(executor).await
+            }
+            None => Ok(None),
+        }
+    }
+}
+impl ProductDbSet {
+    pub async fn load_categories<'e, E: sqlx::PgExecutor<'e>>(
+        rows: &[Product],
+        executor: E,
+    ) -> Result<std::collections::HashMap<String, Category>, sqlx::Error> {
+        let keys = rows.iter().map(|row| row.category_code.clone()).collect::<Vec<_>>();
+        let related_rows = CategoryDbSet::fetch_by_column_any(
+                CategoryDbSet::CODE_COLUMN,
+                keys,
+                executor,
+            )
+            .await?;
+        Ok(
+            related_rows
+                .into_iter()
+                .map(|related_row| (related_row.code.clone(), related_row))
+                .collect(),
+        )
+    }
+    pub async fn load_warehouse_addresses<'e, E: sqlx::PgExecutor<'e>>(
+        rows: &[Product],
+        executor: E,
+    ) -> Result<std::collections::HashMap<i32, Address>, sqlx::Error> {
+        let keys = rows
+            .iter()
+            .filter_map(|row| row.warehouse.clone())
+            .collect::<Vec<_>>();
+        let related_rows = AddressDbSet::fetch_by_column_any(
+                AddressDbSet::ID_COLUMN,
+                keys,
+                executor,
+            )
+            .await?;
+        Ok(
+            related_rows
+                .into_iter()
+                .map(|related_row| (related_row.id.clone(), related_row))
+                .collect(),
+        )
+    }
+}
+    "#;
+
+    compare_computed_to_expected(input_str, output);
+    Ok(())
+}
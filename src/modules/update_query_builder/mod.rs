@@ -1,5 +1,11 @@
 #[cfg(test)]
 mod test_update_query_builder;
 mod update_query_builder;
+pub use update_query_builder::check_update_target;
+pub use update_query_builder::get_many_update_builder_struct_name;
+pub use update_query_builder::get_many_update_query_builder;
+pub use update_query_builder::get_tracked_struct_name;
 pub use update_query_builder::get_update_builder_struct_name;
+pub use update_query_builder::get_update_error_name;
 pub use update_query_builder::get_update_query_builder;
+pub use update_query_builder::has_update_target;
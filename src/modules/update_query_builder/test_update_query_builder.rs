@@ -14,6 +14,15 @@ pub fn compare_computed_to_expected(input_string: &str, output_string: &str) {
     assert_eq!(pretty_out.to_string(), pretty_expected);
 }
 
+pub fn compare_many_update_computed_to_expected(input_string: &str, output_string: &str) {
+    let input_tokens = derive_input_from_string(input_string).expect("Could not get tokens");
+    let out_tokens = update_query_builder::get_many_update_query_builder(&input_tokens);
+    let pretty_out = pretty_print_tokenstream(out_tokens);
+    let pretty_expected =
+        pretty_print_tokenstream(tokenstream_from_string(output_string).expect("coudnt"));
+    assert_eq!(pretty_out.to_string(), pretty_expected);
+}
+
 #[test]
 fn can_parse_user_struct_with_unique_and_auto_key_into_one_builder() -> Result<(), String> {
     let input_str = r#"
@@ -27,13 +36,25 @@ pub struct Account {
     "#;
 
     let output = r#"
-pub struct AccountDbSetUpdateBuilder {}
+pub struct AccountDbSetUpdateBuilder<Id = NotSet, UniqueFields = NotSet> {
+    id: Option<uuid::Uuid>,
+    where_email: Option<String>,
+    email: Option<String>,
+    _unique_fields: std::marker::PhantomData<UniqueFields>,
+    _id: std::marker::PhantomData<Id>,
+}
 pub struct AccountDbSetUpdateBuilderWithData {
     updatable: Account,
 }
 impl AccountDbSetUpdateBuilder {
-    pub fn new() -> AccountDbSetUpdateBuilder {
-        Self {}
+    pub fn new() -> AccountDbSetUpdateBuilder<NotSet, NotSet> {
+        Self {
+            id: None,
+            where_email: None,
+            email: None,
+            _unique_fields: std::marker::PhantomData::<NotSet>,
+            _id: std::marker::PhantomData::<NotSet>,
+        }
     }
 }
 impl AccountDbSetUpdateBuilder {
@@ -43,19 +64,95 @@ impl AccountDbSetUpdateBuilder {
         }
     }
 }
+impl AccountDbSetUpdateBuilder<NotSet, NotSet> {
+    pub fn id_eq(self, id: uuid::Uuid) -> AccountDbSetUpdateBuilder<Set, NotSet> {
+        AccountDbSetUpdateBuilder {
+            id: Some(id),
+            where_email: self.where_email,
+            email: self.email,
+            _unique_fields: self._unique_fields,
+            _id: std::marker::PhantomData::<Set>,
+        }
+    }
+}
+impl AccountDbSetUpdateBuilder<NotSet, NotSet> {
+    pub fn email_eq(self, email: String) -> AccountDbSetUpdateBuilder<NotSet, Set> {
+        AccountDbSetUpdateBuilder {
+            where_email: Some(email),
+            id: self.id,
+            email: self.email,
+            _unique_fields: std::marker::PhantomData::<Set>,
+            _id: self._id,
+        }
+    }
+}
+impl<Id, UniqueFields> AccountDbSetUpdateBuilder<Id, UniqueFields> {
+    pub fn set_email(mut self, email: String) -> Self {
+        self.email = Some(email);
+        self
+    }
+}
 impl AccountDbSetUpdateBuilderWithData {
     pub async fn update<'e, E: sqlx::PgExecutor<'e>>(
         self,
         executor: E,
     ) -> Result<Account, sqlx::Error> {
-
-        sqlx::query_as!(Account, "UPDATE users SET email = $2 WHERE id = $1 RETURNING id, email;",
+        sqlx::query_as!(
+            Account, "UPDATE \"users\" SET \"email\" = $2 WHERE \"id\" = $1 RETURNING \"id\", \"email\";",
             self.updatable.id, self.updatable.email,
-        ).fetch_one(executor).await
+        )
+            .fetch_one(executor)
+            .await
+    }
+}
+impl AccountDbSetUpdateBuilder<Set, NotSet> {
+    pub async fn execute<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<u64, sqlx::Error> {
+        if self.email.is_none() {
+            return Ok(0);
+        }
+        let mut query_builder = sqlx::QueryBuilder::<
+            sqlx::Postgres,
+        >::new("UPDATE \"users\" SET ");
+        let mut set_fields = query_builder.separated(", ");
+        if let Some(email) = self.email {
+            set_fields.push("\"email\" = ");
+            set_fields.push_bind_unseparated(email);
+        }
+        query_builder.push(" WHERE \"id\" = ");
+        query_builder.push_bind(self.id);
+        let result = query_builder.build().execute(executor).await?;
+        Ok(result.rows_affected())
+    }
+}
+impl AccountDbSetUpdateBuilder<NotSet, Set> {
+    pub async fn execute<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<u64, sqlx::Error> {
+        if self.email.is_none() {
+            return Ok(0);
+        }
+        let mut query_builder = sqlx::QueryBuilder::<
+            sqlx::Postgres,
+        >::new("UPDATE \"users\" SET ");
+        let mut set_fields = query_builder.separated(", ");
+        if let Some(email) = self.email {
+            set_fields.push("\"email\" = ");
+            set_fields.push_bind_unseparated(email);
+        }
+        query_builder.push(" WHERE ");
+        let mut where_fields = query_builder.separated(" AND ");
+        if let Some(where_email) = self.where_email {
+            where_fields.push("\"email\" = ");
+            where_fields.push_bind_unseparated(where_email);
+        }
+        let result = query_builder.build().execute(executor).await?;
+        Ok(result.rows_affected())
     }
 }
-
-
     "#;
 
     compare_computed_to_expected(input_str, output);
@@ -78,13 +175,27 @@ pub struct Account {
     "#;
 
     let output = r#"
-pub struct AccountDbSetUpdateBuilder {}
+pub struct AccountDbSetUpdateBuilder<Id = NotSet, UniqueFields = NotSet> {
+    id: Option<uuid::Uuid>,
+    where_email: Option<String>,
+    email: Option<String>,
+    status: Option<AccountStatus>,
+    _unique_fields: std::marker::PhantomData<UniqueFields>,
+    _id: std::marker::PhantomData<Id>,
+}
 pub struct AccountDbSetUpdateBuilderWithData {
     updatable: Account,
 }
 impl AccountDbSetUpdateBuilder {
-    pub fn new() -> AccountDbSetUpdateBuilder {
-        Self {}
+    pub fn new() -> AccountDbSetUpdateBuilder<NotSet, NotSet> {
+        Self {
+            id: None,
+            where_email: None,
+            email: None,
+            status: None,
+            _unique_fields: std::marker::PhantomData::<NotSet>,
+            _id: std::marker::PhantomData::<NotSet>,
+        }
     }
 }
 impl AccountDbSetUpdateBuilder {
@@ -94,19 +205,111 @@ impl AccountDbSetUpdateBuilder {
         }
     }
 }
+impl AccountDbSetUpdateBuilder<NotSet, NotSet> {
+    pub fn id_eq(self, id: uuid::Uuid) -> AccountDbSetUpdateBuilder<Set, NotSet> {
+        AccountDbSetUpdateBuilder {
+            id: Some(id),
+            where_email: self.where_email,
+            email: self.email,
+            status: self.status,
+            _unique_fields: self._unique_fields,
+            _id: std::marker::PhantomData::<Set>,
+        }
+    }
+}
+impl AccountDbSetUpdateBuilder<NotSet, NotSet> {
+    pub fn email_eq(self, email: String) -> AccountDbSetUpdateBuilder<NotSet, Set> {
+        AccountDbSetUpdateBuilder {
+            where_email: Some(email),
+            id: self.id,
+            email: self.email,
+            status: self.status,
+            _unique_fields: std::marker::PhantomData::<Set>,
+            _id: self._id,
+        }
+    }
+}
+impl<Id, UniqueFields> AccountDbSetUpdateBuilder<Id, UniqueFields> {
+    pub fn set_email(mut self, email: String) -> Self {
+        self.email = Some(email);
+        self
+    }
+    pub fn set_status(mut self, status: AccountStatus) -> Self {
+        self.status = Some(status);
+        self
+    }
+}
 impl AccountDbSetUpdateBuilderWithData {
     pub async fn update<'e, E: sqlx::PgExecutor<'e>>(
         self,
         executor: E,
     ) -> Result<Account, sqlx::Error> {
-
-        sqlx::query_as!(Account, "UPDATE users SET email = $2, status = $3 WHERE id = $1 RETURNING id, email, status AS \"status:AccountStatus\";",
-            self.updatable.id, self.updatable.email, self.updatable.status as AccountStatus,
-        ).fetch_one(executor).await
+        sqlx::query_as!(
+            Account,
+            "UPDATE \"users\" SET \"email\" = $2, \"status\" = $3 WHERE \"id\" = $1 RETURNING \"id\", \"email\", \"status\" AS \"status:AccountStatus\";",
+            self.updatable.id, self.updatable.email, self.updatable.status as
+            AccountStatus,
+        )
+            .fetch_one(executor)
+            .await
+    }
+}
+impl AccountDbSetUpdateBuilder<Set, NotSet> {
+    pub async fn execute<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<u64, sqlx::Error> {
+        if self.email.is_none() && self.status.is_none() {
+            return Ok(0);
+        }
+        let mut query_builder = sqlx::QueryBuilder::<
+            sqlx::Postgres,
+        >::new("UPDATE \"users\" SET ");
+        let mut set_fields = query_builder.separated(", ");
+        if let Some(email) = self.email {
+            set_fields.push("\"email\" = ");
+            set_fields.push_bind_unseparated(email);
+        }
+        if let Some(status) = self.status {
+            set_fields.push("\"status\" = ");
+            set_fields.push_bind_unseparated(status);
+        }
+        query_builder.push(" WHERE \"id\" = ");
+        query_builder.push_bind(self.id);
+        let result = query_builder.build().execute(executor).await?;
+        Ok(result.rows_affected())
+    }
+}
+impl AccountDbSetUpdateBuilder<NotSet, Set> {
+    pub async fn execute<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<u64, sqlx::Error> {
+        if self.email.is_none() && self.status.is_none() {
+            return Ok(0);
+        }
+        let mut query_builder = sqlx::QueryBuilder::<
+            sqlx::Postgres,
+        >::new("UPDATE \"users\" SET ");
+        let mut set_fields = query_builder.separated(", ");
+        if let Some(email) = self.email {
+            set_fields.push("\"email\" = ");
+            set_fields.push_bind_unseparated(email);
+        }
+        if let Some(status) = self.status {
+            set_fields.push("\"status\" = ");
+            set_fields.push_bind_unseparated(status);
+        }
+        query_builder.push(" WHERE ");
+        let mut where_fields = query_builder.separated(" AND ");
+        if let Some(where_email) = self.where_email {
+            where_fields.push("\"email\" = ");
+            where_fields.push_bind_unseparated(where_email);
+        }
+        let result = query_builder.build().execute(executor).await?;
+        Ok(result.rows_affected())
     }
 }
-
-
     "#;
 
     compare_computed_to_expected(input_str, output);
@@ -127,14 +330,29 @@ pub struct User {
     "#;
 
     let output = r#"
-pub struct UserDbSetUpdateBuilder {}
+pub struct UserDbSetUpdateBuilder<Id = NotSet, UniqueFields = NotSet> {
+    id: Option<String>,
+    where_email: Option<String>,
+    name: Option<String>,
+    details: Option<Option<String>>,
+    email: Option<String>,
+    _unique_fields: std::marker::PhantomData<UniqueFields>,
+    _id: std::marker::PhantomData<Id>,
+}
 pub struct UserDbSetUpdateBuilderWithData {
     updatable: User,
 }
-
 impl UserDbSetUpdateBuilder {
-    pub fn new() -> UserDbSetUpdateBuilder {
-        Self { }
+    pub fn new() -> UserDbSetUpdateBuilder<NotSet, NotSet> {
+        Self {
+            id: None,
+            where_email: None,
+            name: None,
+            details: None,
+            email: None,
+            _unique_fields: std::marker::PhantomData::<NotSet>,
+            _id: std::marker::PhantomData::<NotSet>,
+        }
     }
 }
 impl UserDbSetUpdateBuilder {
@@ -144,21 +362,1284 @@ impl UserDbSetUpdateBuilder {
         }
     }
 }
+impl UserDbSetUpdateBuilder<NotSet, NotSet> {
+    pub fn id_eq(self, id: String) -> UserDbSetUpdateBuilder<Set, NotSet> {
+        UserDbSetUpdateBuilder {
+            id: Some(id),
+            where_email: self.where_email,
+            name: self.name,
+            details: self.details,
+            email: self.email,
+            _unique_fields: self._unique_fields,
+            _id: std::marker::PhantomData::<Set>,
+        }
+    }
+}
+impl UserDbSetUpdateBuilder<NotSet, NotSet> {
+    pub fn email_eq(self, email: String) -> UserDbSetUpdateBuilder<NotSet, Set> {
+        UserDbSetUpdateBuilder {
+            where_email: Some(email),
+            id: self.id,
+            name: self.name,
+            details: self.details,
+            email: self.email,
+            _unique_fields: std::marker::PhantomData::<Set>,
+            _id: self._id,
+        }
+    }
+}
+impl<Id, UniqueFields> UserDbSetUpdateBuilder<Id, UniqueFields> {
+    pub fn set_name(mut self, name: String) -> Self {
+        self.name = Some(name);
+        self
+    }
+    pub fn set_details(mut self, details: Option<String>) -> Self {
+        self.details = Some(details);
+        self
+    }
+    pub fn set_email(mut self, email: String) -> Self {
+        self.email = Some(email);
+        self
+    }
+}
 impl UserDbSetUpdateBuilderWithData {
     pub async fn update<'e, E: sqlx::PgExecutor<'e>>(
         self,
         executor: E,
     ) -> Result<User, sqlx::Error> {
         sqlx::query_as!(
-            User, "UPDATE users SET name = $2, details = $3, email = $4 WHERE id = $1 RETURNING id, name, details, email;",
-            self.updatable.id, self.updatable.name, self.updatable.details, self.updatable.email,
+            User,
+            "UPDATE \"users\" SET \"name\" = $2, \"details\" = $3, \"email\" = $4 WHERE \"id\" = $1 RETURNING \"id\", \"name\", \"details\", \"email\";",
+            self.updatable.id, self.updatable.name, self.updatable.details, self
+            .updatable.email,
+        )
+            .fetch_one(executor)
+            .await
+    }
+}
+impl UserDbSetUpdateBuilder<Set, NotSet> {
+    pub async fn execute<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<u64, sqlx::Error> {
+        if self.name.is_none() && self.details.is_none() && self.email.is_none() {
+            return Ok(0);
+        }
+        let mut query_builder = sqlx::QueryBuilder::<
+            sqlx::Postgres,
+        >::new("UPDATE \"users\" SET ");
+        let mut set_fields = query_builder.separated(", ");
+        if let Some(name) = self.name {
+            set_fields.push("\"name\" = ");
+            set_fields.push_bind_unseparated(name);
+        }
+        if let Some(details) = self.details {
+            set_fields.push("\"details\" = ");
+            set_fields.push_bind_unseparated(details);
+        }
+        if let Some(email) = self.email {
+            set_fields.push("\"email\" = ");
+            set_fields.push_bind_unseparated(email);
+        }
+        query_builder.push(" WHERE \"id\" = ");
+        query_builder.push_bind(self.id);
+        let result = query_builder.build().execute(executor).await?;
+        Ok(result.rows_affected())
+    }
+}
+impl UserDbSetUpdateBuilder<NotSet, Set> {
+    pub async fn execute<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<u64, sqlx::Error> {
+        if self.name.is_none() && self.details.is_none() && self.email.is_none() {
+            return Ok(0);
+        }
+        let mut query_builder = sqlx::QueryBuilder::<
+            sqlx::Postgres,
+        >::new("UPDATE \"users\" SET ");
+        let mut set_fields = query_builder.separated(", ");
+        if let Some(name) = self.name {
+            set_fields.push("\"name\" = ");
+            set_fields.push_bind_unseparated(name);
+        }
+        if let Some(details) = self.details {
+            set_fields.push("\"details\" = ");
+            set_fields.push_bind_unseparated(details);
+        }
+        if let Some(email) = self.email {
+            set_fields.push("\"email\" = ");
+            set_fields.push_bind_unseparated(email);
+        }
+        query_builder.push(" WHERE ");
+        let mut where_fields = query_builder.separated(" AND ");
+        if let Some(where_email) = self.where_email {
+            where_fields.push("\"email\" = ");
+            where_fields.push_bind_unseparated(where_email);
+        }
+        let result = query_builder.build().execute(executor).await?;
+        Ok(result.rows_affected())
+    }
+}
+    "#;
+
+    compare_computed_to_expected(input_str, output);
+    Ok(())
+}
+
+#[test]
+fn can_parse_order_line_with_two_keys_into_partial_update_builder() -> Result<(), String> {
+    let input_str = r#"
+#[dbset(table_name = "order_lines")]
+pub struct OrderLine {
+    #[key]
+    order_id: String,
+    #[key]
+    line_no: i32,
+    product: String,
+    note: Option<String>,
+}
+    "#;
+
+    let output = r#"
+pub struct OrderLineDbSetUpdateBuilder<
+    OrderId = NotSet,
+    LineNo = NotSet,
+    UniqueFields = NotSet,
+> {
+    order_id: Option<String>,
+    line_no: Option<i32>,
+    product: Option<String>,
+    note: Option<Option<String>>,
+    _unique_fields: std::marker::PhantomData<UniqueFields>,
+    _order_id: std::marker::PhantomData<OrderId>,
+    _line_no: std::marker::PhantomData<LineNo>,
+}
+pub struct OrderLineDbSetUpdateBuilderWithData {
+    updatable: OrderLine,
+}
+impl OrderLineDbSetUpdateBuilder {
+    pub fn new() -> OrderLineDbSetUpdateBuilder<NotSet, NotSet, NotSet> {
+        Self {
+            order_id: None,
+            line_no: None,
+            product: None,
+            note: None,
+            _unique_fields: std::marker::PhantomData::<NotSet>,
+            _order_id: std::marker::PhantomData::<NotSet>,
+            _line_no: std::marker::PhantomData::<NotSet>,
+        }
+    }
+}
+impl OrderLineDbSetUpdateBuilder {
+    pub fn data(self, value: OrderLine) -> OrderLineDbSetUpdateBuilderWithData {
+        OrderLineDbSetUpdateBuilderWithData {
+            updatable: value,
+        }
+    }
+}
+impl<LineNo> OrderLineDbSetUpdateBuilder<NotSet, LineNo, NotSet> {
+    pub fn order_id_eq(
+        self,
+        order_id: String,
+    ) -> OrderLineDbSetUpdateBuilder<Set, LineNo, NotSet> {
+        OrderLineDbSetUpdateBuilder {
+            order_id: Some(order_id),
+            line_no: self.line_no,
+            product: self.product,
+            note: self.note,
+            _unique_fields: self._unique_fields,
+            _order_id: std::marker::PhantomData::<Set>,
+            _line_no: self._line_no,
+        }
+    }
+}
+impl<OrderId> OrderLineDbSetUpdateBuilder<OrderId, NotSet, NotSet> {
+    pub fn line_no_eq(
+        self,
+        line_no: i32,
+    ) -> OrderLineDbSetUpdateBuilder<OrderId, Set, NotSet> {
+        OrderLineDbSetUpdateBuilder {
+            line_no: Some(line_no),
+            order_id: self.order_id,
+            product: self.product,
+            note: self.note,
+            _unique_fields: self._unique_fields,
+            _line_no: std::marker::PhantomData::<Set>,
+            _order_id: self._order_id,
+        }
+    }
+}
+impl<
+    OrderId,
+    LineNo,
+    UniqueFields,
+> OrderLineDbSetUpdateBuilder<OrderId, LineNo, UniqueFields> {
+    pub fn set_product(mut self, product: String) -> Self {
+        self.product = Some(product);
+        self
+    }
+    pub fn set_note(mut self, note: Option<String>) -> Self {
+        self.note = Some(note);
+        self
+    }
+}
+impl OrderLineDbSetUpdateBuilderWithData {
+    pub async fn update<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<OrderLine, sqlx::Error> {
+        sqlx::query_as!(
+            OrderLine,
+            "UPDATE \"order_lines\" SET \"product\" = $3, \"note\" = $4 WHERE \"order_id\" = $1 AND \"line_no\" = $2 RETURNING \"order_id\", \"line_no\", \"product\", \"note\";",
+            self.updatable.order_id, self.updatable.line_no, self.updatable.product, self
+            .updatable.note,
+        )
+            .fetch_one(executor)
+            .await
+    }
+}
+impl OrderLineDbSetUpdateBuilder<Set, Set, NotSet> {
+    pub async fn execute<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<u64, sqlx::Error> {
+        if self.product.is_none() && self.note.is_none() {
+            return Ok(0);
+        }
+        let mut query_builder = sqlx::QueryBuilder::<
+            sqlx::Postgres,
+        >::new("UPDATE \"order_lines\" SET ");
+        let mut set_fields = query_builder.separated(", ");
+        if let Some(product) = self.product {
+            set_fields.push("\"product\" = ");
+            set_fields.push_bind_unseparated(product);
+        }
+        if let Some(note) = self.note {
+            set_fields.push("\"note\" = ");
+            set_fields.push_bind_unseparated(note);
+        }
+        query_builder.push(" WHERE \"order_id\" = ");
+        query_builder.push_bind(self.order_id);
+        query_builder.push(" AND \"line_no\" = ");
+        query_builder.push_bind(self.line_no);
+        let result = query_builder.build().execute(executor).await?;
+        Ok(result.rows_affected())
+    }
+}
+    "#;
+
+    compare_computed_to_expected(input_str, output);
+    Ok(())
+}
+
+#[test]
+fn can_parse_user_struct_into_many_update_builder() -> Result<(), String> {
+    let input_str = r#"
+#[dbset(table_name = "users")]
+pub struct User {
+    #[key]
+    id: String,
+    name: String,
+    details: Option<String>,
+    #[unique]
+    email: String,
+    #[custom_enum]
+    status: UserStatus,
+}
+    "#;
+
+    let output = r#"
+pub struct UserDbSetManyUpdateBuilder {
+    filters: UserDbSetManyQueryBuilder<Set>,
+    name: Option<String>,
+    details: Option<Option<String>>,
+    email: Option<String>,
+    status: Option<UserStatus>,
+}
+impl UserDbSetManyUpdateBuilder {
+    pub fn new(filters: UserDbSetManyQueryBuilder<Set>) -> Self {
+        Self {
+            filters,
+            name: None,
+            details: None,
+            email: None,
+            status: None,
+        }
+    }
+    pub fn set_name(mut self, name: String) -> Self {
+        self.name = Some(name);
+        self
+    }
+    pub fn set_details(mut self, details: Option<String>) -> Self {
+        self.details = Some(details);
+        self
+    }
+    pub fn set_email(mut self, email: String) -> Self {
+        self.email = Some(email);
+        self
+    }
+    pub fn set_status(mut self, status: UserStatus) -> Self {
+        self.status = Some(status);
+        self
+    }
+    pub async fn execute<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<u64, sqlx::Error> {
+        if self.name.is_none() && self.details.is_none() && self.email.is_none()
+            && self.status.is_none()
+        {
+            return Ok(0);
+        }
+        let mut query_builder = sqlx::QueryBuilder::<
+            sqlx::Postgres,
+        >::new("UPDATE \"users\" SET ");
+        let mut set_fields = query_builder.separated(", ");
+        if let Some(name) = self.name {
+            set_fields.push("\"name\" = ");
+            set_fields.push_bind_unseparated(name);
+        }
+        if let Some(details) = self.details {
+            set_fields.push("\"details\" = ");
+            set_fields.push_bind_unseparated(details);
+        }
+        if let Some(email) = self.email {
+            set_fields.push("\"email\" = ");
+            set_fields.push_bind_unseparated(email);
+        }
+        if let Some(status) = self.status {
+            set_fields.push("\"status\" = ");
+            set_fields.push_bind_unseparated(status);
+        }
+        let filters = self.filters;
+        if filters.name.is_some() || filters.details.is_some()
+            || filters.status.is_some()
+        {
+            query_builder.push(" WHERE ");
+        }
+        let mut where_fields = query_builder.separated(" AND ");
+        if let Some(name) = filters.name {
+            where_fields.push("\"name\" = ");
+            where_fields.push_bind_unseparated(name);
+        }
+        if let Some(details) = filters.details {
+            where_fields.push("\"details\" = ");
+            where_fields.push_bind_unseparated(details);
+        }
+        if let Some(status) = filters.status {
+            where_fields.push("\"status\" = ");
+            where_fields.push_bind_unseparated(status);
+        }
+        let result = query_builder.build().execute(executor).await?;
+        Ok(result.rows_affected())
+    }
+}
+    "#;
+
+    compare_many_update_computed_to_expected(input_str, output);
+    Ok(())
+}
+
+#[test]
+fn can_parse_document_with_version_into_update_builder() -> Result<(), String> {
+    let input_str = r#"
+        #[derive(DbSet, Debug)]
+        #[dbset(table_name = "documents")]
+        pub struct Document {
+            #[key]
+            id: i32,
+            title: String,
+            body: Option<String>,
+            #[version]
+            version: i32,
+        }
+    "#;
+
+    let output = r#"
+pub struct DocumentDbSetUpdateBuilder<
+    Id = NotSet,
+    UniqueFields = NotSet,
+    Version = NotSet,
+> {
+    id: Option<i32>,
+    title: Option<String>,
+    body: Option<Option<String>>,
+    version: Option<i32>,
+    _unique_fields: std::marker::PhantomData<UniqueFields>,
+    _id: std::marker::PhantomData<Id>,
+    _version: std::marker::PhantomData<Version>,
+}
+pub struct DocumentDbSetUpdateBuilderWithData {
+    updatable: Document,
+}
+impl DocumentDbSetUpdateBuilder {
+    pub fn new() -> DocumentDbSetUpdateBuilder<NotSet, NotSet, NotSet> {
+        Self {
+            id: None,
+            title: None,
+            body: None,
+            version: None,
+            _unique_fields: std::marker::PhantomData::<NotSet>,
+            _id: std::marker::PhantomData::<NotSet>,
+            _version: std::marker::PhantomData::<NotSet>,
+        }
+    }
+}
+impl DocumentDbSetUpdateBuilder {
+    pub fn data(self, value: Document) -> DocumentDbSetUpdateBuilderWithData {
+        DocumentDbSetUpdateBuilderWithData {
+            updatable: value,
+        }
+    }
+}
+impl<Version> DocumentDbSetUpdateBuilder<NotSet, NotSet, Version> {
+    pub fn id_eq(self, id: i32) -> DocumentDbSetUpdateBuilder<Set, NotSet, Version> {
+        DocumentDbSetUpdateBuilder {
+            id: Some(id),
+            title: self.title,
+            body: self.body,
+            version: self.version,
+            _unique_fields: self._unique_fields,
+            _id: std::marker::PhantomData::<Set>,
+            _version: self._version,
+        }
+    }
+}
+impl<Id, UniqueFields, Version> DocumentDbSetUpdateBuilder<Id, UniqueFields, Version> {
+    pub fn set_title(mut self, title: String) -> Self {
+        self.title = Some(title);
+        self
+    }
+    pub fn set_body(mut self, body: Option<String>) -> Self {
+        self.body = Some(body);
+        self
+    }
+}
+impl<Id, UniqueFields> DocumentDbSetUpdateBuilder<Id, UniqueFields, NotSet> {
+    pub fn version_eq(
+        self,
+        version: i32,
+    ) -> DocumentDbSetUpdateBuilder<Id, UniqueFields, Set> {
+        DocumentDbSetUpdateBuilder {
+            version: Some(version),
+            id: self.id,
+            title: self.title,
+            body: self.body,
+            _unique_fields: self._unique_fields,
+            _id: self._id,
+            _version: std::marker::PhantomData::<Set>,
+        }
+    }
+}
+#[derive(Debug)]
+pub enum DocumentDbSetUpdateError {
+    ConcurrencyConflict,
+    Database(sqlx::Error),
+}
+impl std::fmt::Display for DocumentDbSetUpdateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ConcurrencyConflict => {
+                write!(f, "row was modified or removed since it was fetched")
+            }
+            Self::Database(err) => write!(f, "{}", err),
+        }
+    }
+}
+impl std::error::Error for DocumentDbSetUpdateError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::ConcurrencyConflict => None,
+            Self::Database(err) => Some(err),
+        }
+    }
+}
+impl From<sqlx::Error> for DocumentDbSetUpdateError {
+    fn from(err: sqlx::Error) -> Self {
+        Self::Database(err)
+    }
+}
+impl DocumentDbSetUpdateBuilderWithData {
+    pub async fn update<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<Document, DocumentDbSetUpdateError> {
+        sqlx::query_as!(
+            Document,
+            "UPDATE \"documents\" SET \"title\" = $2, \"body\" = $3, \"version\" = \"version\" + 1 WHERE \"id\" = $1 AND \"version\" = $4 RETURNING \"id\", \"title\", \"body\", \"version\";",
+            self.updatable.id, self.updatable.title, self.updatable.body, self.updatable
+            .version,
         )
             .fetch_one(executor)
             .await
+            .map_err(|err| match err {
+                sqlx::Error::RowNotFound => DocumentDbSetUpdateError::ConcurrencyConflict,
+                err => DocumentDbSetUpdateError::Database(err),
+            })
+    }
+}
+impl DocumentDbSetUpdateBuilder<Set, NotSet, Set> {
+    pub async fn execute<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<u64, DocumentDbSetUpdateError> {
+        if self.title.is_none() && self.body.is_none() {
+            return Ok(0);
+        }
+        let mut query_builder = sqlx::QueryBuilder::<
+            sqlx::Postgres,
+        >::new("UPDATE \"documents\" SET ");
+        let mut set_fields = query_builder.separated(", ");
+        if let Some(title) = self.title {
+            set_fields.push("\"title\" = ");
+            set_fields.push_bind_unseparated(title);
+        }
+        if let Some(body) = self.body {
+            set_fields.push("\"body\" = ");
+            set_fields.push_bind_unseparated(body);
+        }
+        set_fields.push("\"version\" = \"version\" + 1");
+        query_builder.push(" WHERE \"id\" = ");
+        query_builder.push_bind(self.id);
+        query_builder.push(" AND \"version\" = ");
+        query_builder.push_bind(self.version);
+        let result = query_builder.build().execute(executor).await?;
+        if result.rows_affected() == 0 {
+            return Err(DocumentDbSetUpdateError::ConcurrencyConflict);
+        }
+        Ok(result.rows_affected())
     }
 }
+    "#;
+
+    compare_computed_to_expected(input_str, output);
+    Ok(())
+}
 
+#[test]
+fn can_parse_note_with_timestamp_fields_into_update_builder() -> Result<(), String> {
+    let input_str = r#"
+        #[derive(DbSet, Debug)]
+        #[dbset(table_name = "notes")]
+        pub struct Note {
+            #[key]
+            id: i32,
+            text: String,
+            #[created_at]
+            created_at: DateTime<Utc>,
+            #[updated_at]
+            updated_at: DateTime<Utc>,
+        }
+    "#;
 
+    let output = r#"
+pub struct NoteDbSetUpdateBuilder<Id = NotSet, UniqueFields = NotSet> {
+    id: Option<i32>,
+    text: Option<String>,
+    _unique_fields: std::marker::PhantomData<UniqueFields>,
+    _id: std::marker::PhantomData<Id>,
+}
+pub struct NoteDbSetUpdateBuilderWithData {
+    updatable: Note,
+}
+impl NoteDbSetUpdateBuilder {
+    pub fn new() -> NoteDbSetUpdateBuilder<NotSet, NotSet> {
+        Self {
+            id: None,
+            text: None,
+            _unique_fields: std::marker::PhantomData::<NotSet>,
+            _id: std::marker::PhantomData::<NotSet>,
+        }
+    }
+}
+impl NoteDbSetUpdateBuilder {
+    pub fn data(self, value: Note) -> NoteDbSetUpdateBuilderWithData {
+        NoteDbSetUpdateBuilderWithData {
+            updatable: value,
+        }
+    }
+}
+impl NoteDbSetUpdateBuilder<NotSet, NotSet> {
+    pub fn id_eq(self, id: i32) -> NoteDbSetUpdateBuilder<Set, NotSet> {
+        NoteDbSetUpdateBuilder {
+            id: Some(id),
+            text: self.text,
+            _unique_fields: self._unique_fields,
+            _id: std::marker::PhantomData::<Set>,
+        }
+    }
+}
+impl<Id, UniqueFields> NoteDbSetUpdateBuilder<Id, UniqueFields> {
+    pub fn set_text(mut self, text: String) -> Self {
+        self.text = Some(text);
+        self
+    }
+}
+impl NoteDbSetUpdateBuilderWithData {
+    pub async fn update<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<Note, sqlx::Error> {
+        sqlx::query_as!(
+            Note,
+            "UPDATE \"notes\" SET \"text\" = $2, \"updated_at\" = now() WHERE \"id\" = $1 RETURNING \"id\", \"text\", \"created_at\", \"updated_at\";",
+            self.updatable.id, self.updatable.text,
+        )
+            .fetch_one(executor)
+            .await
+    }
+}
+impl NoteDbSetUpdateBuilder<Set, NotSet> {
+    pub async fn execute<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<u64, sqlx::Error> {
+        if self.text.is_none() {
+            return Ok(0);
+        }
+        let mut query_builder = sqlx::QueryBuilder::<
+            sqlx::Postgres,
+        >::new("UPDATE \"notes\" SET ");
+        let mut set_fields = query_builder.separated(", ");
+        if let Some(text) = self.text {
+            set_fields.push("\"text\" = ");
+            set_fields.push_bind_unseparated(text);
+        }
+        set_fields.push("\"updated_at\" = now()");
+        query_builder.push(" WHERE \"id\" = ");
+        query_builder.push_bind(self.id);
+        let result = query_builder.build().execute(executor).await?;
+        Ok(result.rows_affected())
+    }
+}
+    "#;
+
+    compare_computed_to_expected(input_str, output);
+    Ok(())
+}
+
+#[test]
+fn can_parse_tracked_note_into_update_builder() -> Result<(), String> {
+    let input_str = r#"
+        #[derive(DbSet, Debug, Clone)]
+        #[dbset(table_name = "notes")]
+        #[dbset(tracked)]
+        pub struct Note {
+            #[key]
+            id: i32,
+            text: String,
+            pinned: bool,
+        }
+    "#;
+
+    let output = r#"
+pub struct NoteDbSetUpdateBuilder<Id = NotSet, UniqueFields = NotSet> {
+    id: Option<i32>,
+    text: Option<String>,
+    pinned: Option<bool>,
+    _unique_fields: std::marker::PhantomData<UniqueFields>,
+    _id: std::marker::PhantomData<Id>,
+}
+pub struct NoteDbSetUpdateBuilderWithData {
+    updatable: Note,
+}
+impl NoteDbSetUpdateBuilder {
+    pub fn new() -> NoteDbSetUpdateBuilder<NotSet, NotSet> {
+        Self {
+            id: None,
+            text: None,
+            pinned: None,
+            _unique_fields: std::marker::PhantomData::<NotSet>,
+            _id: std::marker::PhantomData::<NotSet>,
+        }
+    }
+}
+impl NoteDbSetUpdateBuilder {
+    pub fn data(self, value: Note) -> NoteDbSetUpdateBuilderWithData {
+        NoteDbSetUpdateBuilderWithData {
+            updatable: value,
+        }
+    }
+}
+impl NoteDbSetUpdateBuilder<NotSet, NotSet> {
+    pub fn id_eq(self, id: i32) -> NoteDbSetUpdateBuilder<Set, NotSet> {
+        NoteDbSetUpdateBuilder {
+            id: Some(id),
+            text: self.text,
+            pinned: self.pinned,
+            _unique_fields: self._unique_fields,
+            _id: std::marker::PhantomData::<Set>,
+        }
+    }
+}
+impl<Id, UniqueFields> NoteDbSetUpdateBuilder<Id, UniqueFields> {
+    pub fn set_text(mut self, text: String) -> Self {
+        self.text = Some(text);
+        self
+    }
+    pub fn set_pinned(mut self, pinned: bool) -> Self {
+        self.pinned = Some(pinned);
+        self
+    }
+}
+impl NoteDbSetUpdateBuilderWithData {
+    pub async fn update<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<Note, sqlx::Error> {
+        sqlx::query_as!(
+            Note,
+            "UPDATE \"notes\" SET \"text\" = $2, \"pinned\" = $3 WHERE \"id\" = $1 RETURNING \"id\", \"text\", \"pinned\";",
+            self.updatable.id, self.updatable.text, self.updatable.pinned,
+        )
+            .fetch_one(executor)
+            .await
+    }
+}
+impl NoteDbSetUpdateBuilder<Set, NotSet> {
+    pub async fn execute<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<u64, sqlx::Error> {
+        if self.text.is_none() && self.pinned.is_none() {
+            return Ok(0);
+        }
+        let mut query_builder = sqlx::QueryBuilder::<
+            sqlx::Postgres,
+        >::new("UPDATE \"notes\" SET ");
+        let mut set_fields = query_builder.separated(", ");
+        if let Some(text) = self.text {
+            set_fields.push("\"text\" = ");
+            set_fields.push_bind_unseparated(text);
+        }
+        if let Some(pinned) = self.pinned {
+            set_fields.push("\"pinned\" = ");
+            set_fields.push_bind_unseparated(pinned);
+        }
+        query_builder.push(" WHERE \"id\" = ");
+        query_builder.push_bind(self.id);
+        let result = query_builder.build().execute(executor).await?;
+        Ok(result.rows_affected())
+    }
+}
+pub struct NoteDbSetTracked {
+    original: Note,
+    current: Note,
+}
+impl NoteDbSetTracked {
+    pub fn new(value: Note) -> Self {
+        Self {
+            original: value.clone(),
+            current: value,
+        }
+    }
+    pub fn is_dirty(&self) -> bool {
+        self.original.text != self.current.text
+            || self.original.pinned != self.current.pinned
+    }
+    pub fn into_inner(self) -> Note {
+        self.current
+    }
+}
+impl std::ops::Deref for NoteDbSetTracked {
+    type Target = Note;
+    fn deref(&self) -> &Self::Target {
+        &self.current
+    }
+}
+impl std::ops::DerefMut for NoteDbSetTracked {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.current
+    }
+}
+pub struct NoteDbSetUpdateBuilderWithTracked {
+    tracked: NoteDbSetTracked,
+}
+impl NoteDbSetUpdateBuilder {
+    pub fn tracked(self, value: NoteDbSetTracked) -> NoteDbSetUpdateBuilderWithTracked {
+        NoteDbSetUpdateBuilderWithTracked {
+            tracked: value,
+        }
+    }
+}
+impl NoteDbSetUpdateBuilderWithTracked {
+    pub async fn update<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<Note, sqlx::Error> {
+        if !self.tracked.is_dirty() {
+            return Ok(self.tracked.current);
+        }
+        let NoteDbSetTracked { original, current } = self.tracked;
+        let mut query_builder = sqlx::QueryBuilder::<
+            sqlx::Postgres,
+        >::new("UPDATE \"notes\" SET ");
+        let mut set_fields = query_builder.separated(", ");
+        if original.text != current.text {
+            set_fields.push("\"text\" = ");
+            set_fields.push_bind_unseparated(current.text);
+        }
+        if original.pinned != current.pinned {
+            set_fields.push("\"pinned\" = ");
+            set_fields.push_bind_unseparated(current.pinned);
+        }
+        query_builder.push(" WHERE \"id\" = ");
+        query_builder.push_bind(original.id);
+        query_builder.push(" RETURNING \"id\", \"text\", \"pinned\"");
+        query_builder.build_query_as::<Note>().fetch_one(executor).await
+    }
+}
+    "#;
+
+    compare_computed_to_expected(input_str, output);
+    Ok(())
+}
+
+#[test]
+fn can_parse_tag_with_only_unique_field_into_update_builder() -> Result<(), String> {
+    let input_str = r#"
+        #[derive(DbSet, Debug)]
+        #[dbset(table_name = "tags")]
+        pub struct Tag {
+            #[unique]
+            name: String,
+            colour: Option<String>,
+        }
+    "#;
+
+    let output = r#"
+pub struct TagDbSetUpdateBuilder<UniqueFields = NotSet> {
+    where_name: Option<String>,
+    name: Option<String>,
+    colour: Option<Option<String>>,
+    _unique_fields: std::marker::PhantomData<UniqueFields>,
+}
+pub struct TagDbSetUpdateBuilderWithData {
+    updatable: Tag,
+}
+impl TagDbSetUpdateBuilder {
+    pub fn new() -> TagDbSetUpdateBuilder<NotSet> {
+        Self {
+            where_name: None,
+            name: None,
+            colour: None,
+            _unique_fields: std::marker::PhantomData::<NotSet>,
+        }
+    }
+}
+impl TagDbSetUpdateBuilder<NotSet> {
+    pub fn name_eq(self, name: String) -> TagDbSetUpdateBuilder<Set> {
+        TagDbSetUpdateBuilder {
+            where_name: Some(name),
+            name: self.name,
+            colour: self.colour,
+            _unique_fields: std::marker::PhantomData::<Set>,
+        }
+    }
+}
+impl<UniqueFields> TagDbSetUpdateBuilder<UniqueFields> {
+    pub fn set_name(mut self, name: String) -> Self {
+        self.name = Some(name);
+        self
+    }
+    pub fn set_colour(mut self, colour: Option<String>) -> Self {
+        self.colour = Some(colour);
+        self
+    }
+}
+impl TagDbSetUpdateBuilder<Set> {
+    pub async fn execute<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<u64, sqlx::Error> {
+        if self.name.is_none() && self.colour.is_none() {
+            return Ok(0);
+        }
+        let mut query_builder = sqlx::QueryBuilder::<
+            sqlx::Postgres,
+        >::new("UPDATE \"tags\" SET ");
+        let mut set_fields = query_builder.separated(", ");
+        if let Some(name) = self.name {
+            set_fields.push("\"name\" = ");
+            set_fields.push_bind_unseparated(name);
+        }
+        if let Some(colour) = self.colour {
+            set_fields.push("\"colour\" = ");
+            set_fields.push_bind_unseparated(colour);
+        }
+        query_builder.push(" WHERE ");
+        let mut where_fields = query_builder.separated(" AND ");
+        if let Some(where_name) = self.where_name {
+            where_fields.push("\"name\" = ");
+            where_fields.push_bind_unseparated(where_name);
+        }
+        let result = query_builder.build().execute(executor).await?;
+        Ok(result.rows_affected())
+    }
+}
+    "#;
+
+    compare_computed_to_expected(input_str, output);
+    Ok(())
+}
+
+#[test]
+fn set_without_key_or_unique_field_is_rejected() {
+    let input_str = r#"
+        #[derive(DbSet, Debug)]
+        #[dbset(table_name = "logs")]
+        pub struct Log {
+            message: String,
+        }
+    "#;
+
+    let input_tokens = derive_input_from_string(input_str).expect("Could not get tokens");
+    let err = update_query_builder::check_update_target(&input_tokens)
+        .expect_err("a set without a key or unique field");
+    assert_eq!(
+        err.to_string(),
+        "Log needs a #[key] or #[unique] field to be updated, add one or mark its columns #[readonly]"
+    );
+}
+
+#[test]
+fn update_builder_for_readonly_view_is_skipped() {
+    let input_str = r#"
+        #[derive(DbSet, Debug)]
+        #[dbset(table_name = "order_totals")]
+        pub struct OrderTotal {
+            #[readonly]
+            customer_id: i32,
+            #[readonly]
+            total: i64,
+        }
+    "#;
+
+    let input_tokens = derive_input_from_string(input_str).expect("Could not get tokens");
+    assert!(update_query_builder::check_update_target(&input_tokens).is_ok());
+    compare_computed_to_expected(input_str, "");
+}
+
+#[test]
+fn can_parse_product_with_auto_and_readonly_fields_into_update_builder() -> Result<(), String> {
+    let input_str = r#"
+        #[derive(DbSet, Debug)]
+        #[dbset(table_name = "products")]
+        pub struct Product {
+            name: String,
+            #[key]
+            id: i32,
+            #[auto]
+            search_rank: i32,
+            #[readonly]
+            created_by: String,
+            price: i32,
+        }
+    "#;
+
+    let output = r#"
+pub struct ProductDbSetUpdateBuilder<Id = NotSet, UniqueFields = NotSet> {
+    id: Option<i32>,
+    name: Option<String>,
+    price: Option<i32>,
+    _unique_fields: std::marker::PhantomData<UniqueFields>,
+    _id: std::marker::PhantomData<Id>,
+}
+pub struct ProductDbSetUpdateBuilderWithData {
+    updatable: Product,
+}
+impl ProductDbSetUpdateBuilder {
+    pub fn new() -> ProductDbSetUpdateBuilder<NotSet, NotSet> {
+        Self {
+            id: None,
+            name: None,
+            price: None,
+            _unique_fields: std::marker::PhantomData::<NotSet>,
+            _id: std::marker::PhantomData::<NotSet>,
+        }
+    }
+}
+impl ProductDbSetUpdateBuilder {
+    pub fn data(self, value: Product) -> ProductDbSetUpdateBuilderWithData {
+        ProductDbSetUpdateBuilderWithData {
+            updatable: value,
+        }
+    }
+}
+impl ProductDbSetUpdateBuilder<NotSet, NotSet> {
+    pub fn id_eq(self, id: i32) -> ProductDbSetUpdateBuilder<Set, NotSet> {
+        ProductDbSetUpdateBuilder {
+            id: Some(id),
+            name: self.name,
+            price: self.price,
+            _unique_fields: self._unique_fields,
+            _id: std::marker::PhantomData::<Set>,
+        }
+    }
+}
+impl<Id, UniqueFields> ProductDbSetUpdateBuilder<Id, UniqueFields> {
+    pub fn set_name(mut self, name: String) -> Self {
+        self.name = Some(name);
+        self
+    }
+    pub fn set_price(mut self, price: i32) -> Self {
+        self.price = Some(price);
+        self
+    }
+}
+impl ProductDbSetUpdateBuilderWithData {
+    pub async fn update<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<Product, sqlx::Error> {
+        sqlx::query_as!(
+            Product,
+            "UPDATE \"products\" SET \"name\" = $2, \"price\" = $3 WHERE \"id\" = $1 RETURNING \"name\", \"id\", \"search_rank\", \"created_by\", \"price\";",
+            self.updatable.id, self.updatable.name, self.updatable.price,
+        )
+            .fetch_one(executor)
+            .await
+    }
+}
+impl ProductDbSetUpdateBuilder<Set, NotSet> {
+    pub async fn execute<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<u64, sqlx::Error> {
+        if self.name.is_none() && self.price.is_none() {
+            return Ok(0);
+        }
+        let mut query_builder = sqlx::QueryBuilder::<
+            sqlx::Postgres,
+        >::new("UPDATE \"products\" SET ");
+        let mut set_fields = query_builder.separated(", ");
+        if let Some(name) = self.name {
+            set_fields.push("\"name\" = ");
+            set_fields.push_bind_unseparated(name);
+        }
+        if let Some(price) = self.price {
+            set_fields.push("\"price\" = ");
+            set_fields.push_bind_unseparated(price);
+        }
+        query_builder.push(" WHERE \"id\" = ");
+        query_builder.push_bind(self.id);
+        let result = query_builder.build().execute(executor).await?;
+        Ok(result.rows_affected())
+    }
+}
+    "#;
+
+    compare_computed_to_expected(input_str, output);
+    Ok(())
+}
+
+#[test]
+fn can_parse_key_named_after_its_type_into_update_builder() -> Result<(), String> {
+    let input_str = r#"
+#[dbset(table_name = "memberships")]
+pub struct Membership {
+    #[key]
+    user_id: String,
+    #[key]
+    #[custom_enum]
+    group: Group,
+    role: String,
+}
+    "#;
+
+    let output = r#"
+pub struct MembershipDbSetUpdateBuilder<
+    UserId = NotSet,
+    GroupField = NotSet,
+    UniqueFields = NotSet,
+> {
+    user_id: Option<String>,
+    group: Option<Group>,
+    role: Option<String>,
+    _unique_fields: std::marker::PhantomData<UniqueFields>,
+    _user_id: std::marker::PhantomData<UserId>,
+    _group: std::marker::PhantomData<GroupField>,
+}
+pub struct MembershipDbSetUpdateBuilderWithData {
+    updatable: Membership,
+}
+impl MembershipDbSetUpdateBuilder {
+    pub fn new() -> MembershipDbSetUpdateBuilder<NotSet, NotSet, NotSet> {
+        Self {
+            user_id: None,
+            group: None,
+            role: None,
+            _unique_fields: std::marker::PhantomData::<NotSet>,
+            _user_id: std::marker::PhantomData::<NotSet>,
+            _group: std::marker::PhantomData::<NotSet>,
+        }
+    }
+}
+impl MembershipDbSetUpdateBuilder {
+    pub fn data(self, value: Membership) -> MembershipDbSetUpdateBuilderWithData {
+        MembershipDbSetUpdateBuilderWithData {
+            updatable: value,
+        }
+    }
+}
+impl<GroupField> MembershipDbSetUpdateBuilder<NotSet, GroupField, NotSet> {
+    pub fn user_id_eq(
+        self,
+        user_id: String,
+    ) -> MembershipDbSetUpdateBuilder<Set, GroupField, NotSet> {
+        MembershipDbSetUpdateBuilder {
+            user_id: Some(user_id),
+            group: self.group,
+            role: self.role,
+            _unique_fields: self._unique_fields,
+            _user_id: std::marker::PhantomData::<Set>,
+            _group: self._group,
+        }
+    }
+}
+impl<UserId> MembershipDbSetUpdateBuilder<UserId, NotSet, NotSet> {
+    pub fn group_eq(
+        self,
+        group: Group,
+    ) -> MembershipDbSetUpdateBuilder<UserId, Set, NotSet> {
+        MembershipDbSetUpdateBuilder {
+            group: Some(group),
+            user_id: self.user_id,
+            role: self.role,
+            _unique_fields: self._unique_fields,
+            _group: std::marker::PhantomData::<Set>,
+            _user_id: self._user_id,
+        }
+    }
+}
+impl<
+    UserId,
+    GroupField,
+    UniqueFields,
+> MembershipDbSetUpdateBuilder<UserId, GroupField, UniqueFields> {
+    pub fn set_role(mut self, role: String) -> Self {
+        self.role = Some(role);
+        self
+    }
+}
+impl MembershipDbSetUpdateBuilderWithData {
+    pub async fn update<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<Membership, sqlx::Error> {
+        sqlx::query_as!(
+            Membership,
+            "UPDATE \"memberships\" SET \"role\" = $3 WHERE \"user_id\" = $1 AND \"group\" = $2 RETURNING \"user_id\", \"group\" AS \"group:Group\", \"role\";",
+            self.updatable.user_id, self.updatable.group as Group, self.updatable.role,
+        )
+            .fetch_one(executor)
+            .await
+    }
+}
+impl MembershipDbSetUpdateBuilder<Set, Set, NotSet> {
+    pub async fn execute<'e, E: sqlx::PgExecutor<'e>>(
+        self,
+        executor: E,
+    ) -> Result<u64, sqlx::Error> {
+        if self.role.is_none() {
+            return Ok(0);
+        }
+        let mut query_builder = sqlx::QueryBuilder::<
+            sqlx::Postgres,
+        >::new("UPDATE \"memberships\" SET ");
+        let mut set_fields = query_builder.separated(", ");
+        if let Some(role) = self.role {
+            set_fields.push("\"role\" = ");
+            set_fields.push_bind_unseparated(role);
+        }
+        query_builder.push(" WHERE \"user_id\" = ");
+        query_builder.push_bind(self.user_id);
+        query_builder.push(" AND \"group\" = ");
+        query_builder.push_bind(self.group);
+        let result = query_builder.build().execute(executor).await?;
+        Ok(result.rows_affected())
+    }
+}
+    "#;
+
+    compare_computed_to_expected(input_str, output);
+    Ok(())
+}
+
+#[test]
+fn can_parse_key_and_auto_fields_into_update_builder_without_data_update() -> Result<(), String> {
+    let input_str = r#"
+#[dbset(table_name = "post_tags")]
+pub struct PostTag {
+    #[key]
+    post_id: i32,
+    #[key]
+    tag_id: i32,
+    #[auto]
+    position: i32,
+}
+    "#;
+
+    let output = r#"
+pub struct PostTagDbSetUpdateBuilder<
+    PostId = NotSet,
+    TagId = NotSet,
+    UniqueFields = NotSet,
+> {
+    post_id: Option<i32>,
+    tag_id: Option<i32>,
+    _unique_fields: std::marker::PhantomData<UniqueFields>,
+    _post_id: std::marker::PhantomData<PostId>,
+    _tag_id: std::marker::PhantomData<TagId>,
+}
+pub struct PostTagDbSetUpdateBuilderWithData {
+    updatable: PostTag,
+}
+impl PostTagDbSetUpdateBuilder {
+    pub fn new() -> PostTagDbSetUpdateBuilder<NotSet, NotSet, NotSet> {
+        Self {
+            post_id: None,
+            tag_id: None,
+            _unique_fields: std::marker::PhantomData::<NotSet>,
+            _post_id: std::marker::PhantomData::<NotSet>,
+            _tag_id: std::marker::PhantomData::<NotSet>,
+        }
+    }
+}
+impl<TagId> PostTagDbSetUpdateBuilder<NotSet, TagId, NotSet> {
+    pub fn post_id_eq(
+        self,
+        post_id: i32,
+    ) -> PostTagDbSetUpdateBuilder<Set, TagId, NotSet> {
+        PostTagDbSetUpdateBuilder {
+            post_id: Some(post_id),
+            tag_id: self.tag_id,
+            _unique_fields: self._unique_fields,
+            _post_id: std::marker::PhantomData::<Set>,
+            _tag_id: self._tag_id,
+        }
+    }
+}
+impl<PostId> PostTagDbSetUpdateBuilder<PostId, NotSet, NotSet> {
+    pub fn tag_id_eq(
+        self,
+        tag_id: i32,
+    ) -> PostTagDbSetUpdateBuilder<PostId, Set, NotSet> {
+        PostTagDbSetUpdateBuilder {
+            tag_id: Some(tag_id),
+            post_id: self.post_id,
+            _unique_fields: self._unique_fields,
+            _tag_id: std::marker::PhantomData::<Set>,
+            _post_id: self._post_id,
+        }
+    }
+}
+impl<
+    PostId,
+    TagId,
+    UniqueFields,
+> PostTagDbSetUpdateBuilder<PostId, TagId, UniqueFields> {}
     "#;
 
     compare_computed_to_expected(input_str, output);
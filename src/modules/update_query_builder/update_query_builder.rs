@@ -2,106 +2,954 @@ use proc_macro2::Ident;
 use quote::quote;
 use syn::{Attribute, DeriveInput, Type};
 
+use crate::modules::many_query_builder::utils::{
+    get_many_query_builder_push_filters, get_many_query_builder_struct_name,
+};
+
 use crate::common::utils::{
-    get_all_fields, get_auto_fields, get_dbset_name, get_key_fields, get_query_fields_string,
-    get_struct_name, get_table_name, is_custom_enum_attr,
+    get_all_fields, get_auto_fields, get_column_sql, get_dbset_name, get_field_columns,
+    get_inner_option_type, get_json_arg, get_json_bind, get_key_fields, get_query_fields_string,
+    get_row_fields_string, get_struct_name, get_table_name, get_timestamp_fields,
+    get_type_state_name, get_unique_fields, get_version_field, has_dbset_flag, is_custom_enum_attr,
+    is_readonly_attr, is_updated_at_attr, quote_identifier,
 };
+use crate::modules::from_row::get_query_as_parts;
 pub fn get_update_builder_struct_name(input: &DeriveInput) -> Ident {
     let dbset_name = get_dbset_name(input);
     quote::format_ident!("{}UpdateBuilder", dbset_name)
 }
 
+pub fn get_update_error_name(input: &DeriveInput) -> Ident {
+    let dbset_name = get_dbset_name(input);
+    quote::format_ident!("{}UpdateError", dbset_name)
+}
+
+pub fn get_tracked_struct_name(input: &DeriveInput) -> Ident {
+    let dbset_name = get_dbset_name(input);
+    quote::format_ident!("{}Tracked", dbset_name)
+}
+
+pub fn get_many_update_builder_struct_name(input: &DeriveInput) -> Ident {
+    let dbset_name = get_dbset_name(input);
+    quote::format_ident!("{}ManyUpdateBuilder", dbset_name)
+}
+
+// update() finds its row by a key or a unique field, so sets with neither, such as views
+// whose columns are all #[readonly], get no update builder
+pub fn has_update_target(input: &DeriveInput) -> bool {
+    !get_key_fields(input).is_empty() || !get_unique_fields(input).is_empty()
+}
+
+// A set with columns to write but no key or unique field to find the row by is rejected,
+// views and other sets whose columns are all #[readonly] are fine without one
+pub fn check_update_target(input: &DeriveInput) -> Result<(), syn::Error> {
+    if has_update_target(input) || get_settable_fields(input).is_empty() {
+        return Ok(());
+    }
+    Err(syn::Error::new_spanned(
+        &input.ident,
+        format!(
+            "{} needs a #[key] or #[unique] field to be updated, add one or mark its columns #[readonly]",
+            input.ident
+        ),
+    ))
+}
+
+type FieldRef<'a> = (&'a Ident, &'a Type, &'a Vec<Attribute>);
+
+// Columns that can be written by an UPDATE, keys, #[auto], #[readonly], #[version] and
+// timestamp fields are never set
+fn get_settable_fields(input: &DeriveInput) -> Vec<FieldRef<'_>> {
+    let key_fields = get_key_fields(input);
+    let auto_fields = get_auto_fields(input);
+    let version_field = get_version_field(input);
+    let timestamp_fields = get_timestamp_fields(input);
+    get_all_fields(input)
+        .into_iter()
+        .filter(|(field, _, _)| !key_fields.iter().any(|(key_field, _)| key_field == field))
+        .filter(|(_, _, attrs)| !attrs.iter().any(is_readonly_attr))
+        .filter(|(field, _, _)| version_field.is_none_or(|(version, _)| version != *field))
+        .filter(|(field, _, _)| {
+            !timestamp_fields
+                .iter()
+                .any(|(timestamp_field, _, _)| timestamp_field == field)
+        })
+        .filter(|(field, _, _)| {
+            !auto_fields
+                .iter()
+                .any(|(auto_field, _, _)| auto_field == field)
+        })
+        .collect()
+}
+
+fn get_set_methods(settable_fields: &[FieldRef]) -> Vec<proc_macro2::TokenStream> {
+    settable_fields
+        .iter()
+        .map(|(field_name, field_type, _)| {
+            let method_name = quote::format_ident!("set_{}", field_name);
+            quote! {
+                    pub fn #method_name(mut self, #field_name: #field_type) -> Self {
+                        self.#field_name = Some(#field_name);
+                        self
+                    }
+            }
+        })
+        .collect()
+}
+
+fn get_none_set_checks(settable_fields: &[FieldRef]) -> Vec<proc_macro2::TokenStream> {
+    settable_fields
+        .iter()
+        .map(|(name, _, _)| {
+            quote! { self.#name.is_none() }
+        })
+        .collect()
+}
+
+// Pushes `column = $n` onto `set_fields` for the field holding `value`, or for each
+// column of the embedded struct when the field is flattened
+fn get_push_set_columns(
+    input: &DeriveInput,
+    (name, ty, attrs): &FieldRef,
+    value: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let push_set_columns = get_field_columns(input, name)
+        .into_iter()
+        .map(|(sub_field, column)| {
+            let set_field_str = format!("{} = ", quote_identifier(&column));
+            let value = match sub_field {
+                Some(sub_field) => quote! { #value.#sub_field },
+                None => get_json_bind(attrs, value.clone(), get_inner_option_type(ty).is_some()),
+            };
+            quote! {
+                set_fields.push(#set_field_str);
+                set_fields.push_bind_unseparated(#value);
+            }
+        });
+    quote! { #(#push_set_columns)* }
+}
+
+// Pushes `column = $n` onto a `set_fields` separated builder for each field that was set
+fn get_push_set_fields(
+    input: &DeriveInput,
+    settable_fields: &[FieldRef],
+) -> Vec<proc_macro2::TokenStream> {
+    settable_fields
+        .iter()
+        .map(|field| {
+            let name = field.0;
+            let push_set_columns = get_push_set_columns(input, field, quote! { #name });
+            quote! {
+                if let Some(#name) = self.#name {
+                    #push_set_columns
+                }
+            }
+        })
+        .collect()
+}
+
+// Columns the database fills in on every update: `updated_at = now()` and the version bump,
+// so stale `data().update()` calls on a versioned row are caught
+fn get_generated_set_fields(input: &DeriveInput) -> Vec<String> {
+    let updated_at_fields = get_timestamp_fields(input)
+        .into_iter()
+        .filter(|(_, _, attrs)| attrs.iter().any(is_updated_at_attr))
+        .map(|(name, _, _)| format!("{} = now()", get_column_sql(input, name)));
+    let version_increment = get_version_field(input).map(|(version_name, _)| {
+        let version_column = get_column_sql(input, version_name);
+        format!("{version_column} = {version_column} + 1")
+    });
+    updated_at_fields.chain(version_increment).collect()
+}
+
+fn get_update_error(input: &DeriveInput) -> proc_macro2::TokenStream {
+    let update_error_name = get_update_error_name(input);
+    quote! {
+        #[derive(Debug)]
+        pub enum #update_error_name {
+            ConcurrencyConflict,
+            Database(sqlx::Error),
+        }
+
+        impl std::fmt::Display for #update_error_name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                match self {
+                    Self::ConcurrencyConflict => write!(f, "row was modified or removed since it was fetched"),
+                    Self::Database(err) => write!(f, "{}", err),
+                }
+            }
+        }
+
+        impl std::error::Error for #update_error_name {
+            fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+                match self {
+                    Self::ConcurrencyConflict => None,
+                    Self::Database(err) => Some(err),
+                }
+            }
+        }
+
+        impl From<sqlx::Error> for #update_error_name {
+            fn from(err: sqlx::Error) -> Self {
+                Self::Database(err)
+            }
+        }
+    }
+}
+
+// A loaded row paired with a snapshot of how it was fetched, so an update only writes the
+// columns that changed. Comparing needs `Clone` on the struct and `PartialEq` on its fields,
+// which is why this is opt-in with #[dbset(tracked)]
+fn get_tracked_update(input: &DeriveInput) -> proc_macro2::TokenStream {
+    let table_name = get_table_name(input);
+    let struct_name = get_struct_name(input);
+    let tracked_struct_name = get_tracked_struct_name(input);
+    let builder_struct_name = get_update_builder_struct_name(input);
+    let builder_struct_name_with_tracked =
+        quote::format_ident!("{}WithTracked", builder_struct_name);
+    let key_fields = get_key_fields(input);
+    let settable_fields = get_settable_fields(input);
+    let settable_names = settable_fields
+        .iter()
+        .map(|(name, _, _)| *name)
+        .collect::<Vec<_>>();
+
+    let dirty_check = if settable_names.is_empty() {
+        quote! { false }
+    } else {
+        quote! { #(self.original.#settable_names != self.current.#settable_names)||* }
+    };
+
+    let tracked_struct = quote! {
+        pub struct #tracked_struct_name {
+            original: #struct_name,
+            current: #struct_name,
+        }
+
+        impl #tracked_struct_name {
+            pub fn new(value: #struct_name) -> Self {
+                Self {
+                    original: value.clone(),
+                    current: value,
+                }
+            }
+
+            pub fn is_dirty(&self) -> bool {
+                #dirty_check
+            }
+
+            pub fn into_inner(self) -> #struct_name {
+                self.current
+            }
+        }
+
+        impl std::ops::Deref for #tracked_struct_name {
+            type Target = #struct_name;
+
+            fn deref(&self) -> &Self::Target {
+                &self.current
+            }
+        }
+
+        impl std::ops::DerefMut for #tracked_struct_name {
+            fn deref_mut(&mut self) -> &mut Self::Target {
+                &mut self.current
+            }
+        }
+    };
+
+    if key_fields.is_empty() || settable_fields.is_empty() {
+        return tracked_struct;
+    }
+
+    let update_query_start = format!("UPDATE {table_name} SET ");
+    let returning = format!(" RETURNING {}", get_row_fields_string(input));
+    let generated_set_fields = get_generated_set_fields(input);
+
+    let push_changed_fields = settable_fields.iter().map(|field| {
+        let name = field.0;
+        let push_set_columns = get_push_set_columns(input, field, quote! { current.#name });
+        quote! {
+            if original.#name != current.#name {
+                #push_set_columns
+            }
+        }
+    });
+
+    // Keys and the expected version come from the snapshot, not from the possibly edited row
+    let push_where_fields = key_fields.iter().enumerate().map(|(index, (name, _))| {
+        let column = get_column_sql(input, name);
+        let where_field_str = if index == 0 {
+            format!(" WHERE {column} = ")
+        } else {
+            format!(" AND {column} = ")
+        };
+        quote! {
+            query_builder.push(#where_field_str);
+            query_builder.push_bind(original.#name);
+        }
+    });
+
+    let (push_version_where_field, result_type, map_err) = match get_version_field(input) {
+        Some((version_name, _)) => {
+            let version_where_str = format!(" AND {} = ", get_column_sql(input, version_name));
+            let update_error_name = get_update_error_name(input);
+            (
+                quote! {
+                    query_builder.push(#version_where_str);
+                    query_builder.push_bind(original.#version_name);
+                },
+                quote! { #update_error_name },
+                quote! {
+                    .map_err(|err| match err {
+                        sqlx::Error::RowNotFound => #update_error_name::ConcurrencyConflict,
+                        err => #update_error_name::Database(err),
+                    })
+                },
+            )
+        }
+        None => (quote! {}, quote! { sqlx::Error }, quote! {}),
+    };
+
+    quote! {
+        #tracked_struct
+
+        pub struct #builder_struct_name_with_tracked {
+            tracked: #tracked_struct_name,
+        }
+
+        impl #builder_struct_name {
+                pub fn tracked(self, value: #tracked_struct_name) -> #builder_struct_name_with_tracked  {
+                    #builder_struct_name_with_tracked  {
+                        tracked: value,
+                    }
+                }
+        }
+
+        impl #builder_struct_name_with_tracked {
+                pub async fn update<'e, E: sqlx::PgExecutor<'e>>(
+                    self,
+                    executor: E,
+                ) -> Result<#struct_name, #result_type> {
+                    if !self.tracked.is_dirty() {
+                        return Ok(self.tracked.current);
+                    }
+                    let #tracked_struct_name { original, current } = self.tracked;
+                    let mut query_builder = sqlx::QueryBuilder::<sqlx::Postgres>::new(#update_query_start);
+                    let mut set_fields = query_builder.separated(", ");
+                    #(#push_changed_fields)*
+                    #(set_fields.push(#generated_set_fields);)*
+                    #(#push_where_fields)*
+                    #push_version_where_field
+                    query_builder.push(#returning);
+                    query_builder
+                        .build_query_as::<#struct_name>()
+                        .fetch_one(executor)
+                        .await
+                        #map_err
+            }
+        }
+    }
+}
+
 pub fn get_update_query_builder(input: &DeriveInput) -> proc_macro2::TokenStream {
+    let (query_as, query_as_struct, query_as_map) = get_query_as_parts(input);
     let table_name = get_table_name(input);
     let struct_name = get_struct_name(input);
     let builder_struct_name = get_update_builder_struct_name(input);
     let all_fields = get_all_fields(input);
-    let auto_fields = get_auto_fields(input);
     let all_fields_str = get_query_fields_string(input);
 
-    let is_not_auto_field = |(field, _, _): &(&proc_macro2::Ident, &Type, &Vec<Attribute>)| {
-        !auto_fields
-            .iter()
-            .any(|(auto_field, _, _)| auto_field == field)
-    };
-
-    let all_update_fields = all_fields.iter().filter(|&x| is_not_auto_field(x));
     let builder_struct_name_with_data = quote::format_ident!("{}WithData", builder_struct_name);
 
+    let key_fields = get_key_fields(input);
+    let unique_fields = get_unique_fields(input);
+
+    if !has_update_target(input) {
+        return quote! {};
+    }
+
+    let required_key_fields = key_fields
+        .iter()
+        .filter(|(_, ty)| get_inner_option_type(ty).is_none());
+
+    let settable_fields = get_settable_fields(input);
+    let version_field = get_version_field(input);
+
+    // A whole row update needs a column to write, so a struct of only keys, #[auto] and
+    // #[readonly] fields, such as a join table, gets no data().update()
+    let has_data_update = !key_fields.is_empty()
+        && (!settable_fields.is_empty() || !get_generated_set_fields(input).is_empty());
+
+    // A unique field can also be set, so the value being matched on is kept as `where_{field}`
+    let unique_filter_fields = unique_fields
+        .iter()
+        .map(|(name, ty)| (quote::format_ident!("where_{}", name), *name, *ty))
+        .collect::<Vec<_>>();
+
+    // On a #[version] struct the expected version is part of the type-state, so a partial
+    // update can't be executed without checking it
+    let version_generic =
+        version_field.map(|(version_name, _)| get_type_state_name(input, version_name));
+    let version_generic_in = version_generic
+        .iter()
+        .map(|gen_name_pascal| quote! { #gen_name_pascal, })
+        .collect::<Vec<_>>();
+
+    // Get builder struct generics
+    let builder_struct_generics = required_key_fields
+        .clone()
+        .map(|(field_name, _)| {
+            let gen_name_pascal = get_type_state_name(input, field_name);
+            quote! {
+                #gen_name_pascal = NotSet,
+            }
+        })
+        .chain(vec![quote! {
+            UniqueFields = NotSet,
+        }])
+        .chain(version_generic.iter().map(|gen_name_pascal| {
+            quote! {
+                #gen_name_pascal = NotSet,
+            }
+        }));
+
+    let key_struct_fields = key_fields.iter().map(|(name, ty)| {
+        let type_arg = get_inner_option_type(ty).unwrap_or(ty);
+        quote! { #name: Option< #type_arg >, }
+    });
+
+    let unique_struct_fields = unique_filter_fields.iter().map(|(where_name, _, ty)| {
+        let type_arg = get_inner_option_type(ty).unwrap_or(ty);
+        quote! { #where_name: Option< #type_arg >, }
+    });
+
+    let set_struct_fields = settable_fields.iter().map(|(name, ty, _)| {
+        quote! { #name: Option< #ty >, }
+    });
+
+    let version_struct_field = version_field.map(|(name, ty)| {
+        quote! { #name: Option< #ty >, }
+    });
+
+    let phantom_struct_fields = required_key_fields
+        .clone()
+        .map(|(name, _)| *name)
+        .chain(version_field.map(|(name, _)| name))
+        .map(|name| {
+            let gen_name_pascal = get_type_state_name(input, name);
+            let ph_name = quote::format_ident!("_{}", name);
+            quote! { #ph_name: std::marker::PhantomData::<#gen_name_pascal>, }
+        });
+
     // Create Builder Struct
     let builder_struct = quote! {
-        pub struct #builder_struct_name  {}
+        pub struct #builder_struct_name <#(#builder_struct_generics)*> {
+            #(#key_struct_fields)*
+            #(#unique_struct_fields)*
+            #(#set_struct_fields)*
+            #version_struct_field
+            _unique_fields: std::marker::PhantomData::<UniqueFields>,
+            #(#phantom_struct_fields)*
+        }
         pub struct #builder_struct_name_with_data  {
              updatable: #struct_name,
         }
     };
 
+    // Create new impl
+    let initial_generics = required_key_fields
+        .clone()
+        .map(|_| {
+            quote! {
+                NotSet,
+            }
+        })
+        .chain(vec![quote! { NotSet, }])
+        .chain(version_field.map(|_| quote! { NotSet, }));
+
+    let initial_struct_fields = key_fields
+        .iter()
+        .map(|(name, _)| *name)
+        .chain(
+            unique_filter_fields
+                .iter()
+                .map(|(where_name, _, _)| where_name),
+        )
+        .chain(settable_fields.iter().map(|(name, _, _)| *name))
+        .chain(version_field.map(|(name, _)| name))
+        .map(|name| {
+            quote! { #name: None, }
+        })
+        .chain(vec![quote! {
+            _unique_fields: std::marker::PhantomData::<NotSet>,
+        }]);
+
+    let initial_phantom_struct_fields = required_key_fields
+        .clone()
+        .map(|(name, _)| *name)
+        .chain(version_field.map(|(name, _)| name))
+        .map(|name| {
+            let ph_name = quote::format_ident!("_{}", name);
+            quote! { #ph_name: std::marker::PhantomData::<NotSet>, }
+        });
+
     let new_impl = quote! {
-            pub fn new() -> #builder_struct_name  {
-                Self {}
+            pub fn new() -> #builder_struct_name <#(#initial_generics)*>  {
+                Self {
+                    #(#initial_struct_fields)*
+                    #(#initial_phantom_struct_fields)*
+                }
             }
     };
 
-    let builder_method = quote! {
-        impl #builder_struct_name {
-                pub fn data(self, value: #struct_name) -> #builder_struct_name_with_data  {
-                    #builder_struct_name_with_data  {
-                        updatable: value,
+    let builder_method = if !has_data_update {
+        quote! {}
+    } else {
+        quote! {
+            impl #builder_struct_name {
+                    pub fn data(self, value: #struct_name) -> #builder_struct_name_with_data  {
+                        #builder_struct_name_with_data  {
+                            updatable: value,
 
+                        }
                     }
+
+        }
+            }
+    };
+
+    // Create key filter functions
+    let fill_other_fields = key_fields
+        .iter()
+        .map(|(name, _)| *name)
+        .chain(
+            unique_filter_fields
+                .iter()
+                .map(|(where_name, _, _)| where_name),
+        )
+        .chain(settable_fields.iter().map(|(name, _, _)| *name))
+        .chain(version_field.map(|(name, _)| name))
+        .map(|name| (name, quote! { #name: self.#name, }));
+
+    let fill_other_phantom_fields = required_key_fields
+        .clone()
+        .map(|(name, _)| *name)
+        .chain(version_field.map(|(name, _)| name))
+        .map(|name| {
+            let ph_name = quote::format_ident!("_{}", name);
+            (name, quote! { #ph_name: self.#ph_name, })
+        });
+
+    let key_methods = key_fields.iter().map(|(field_name, field_type)| {
+        let method_name = quote::format_ident!("{}_eq", field_name);
+        let ph_name = quote::format_ident!("_{}", field_name);
+        let inner_field_type = get_inner_option_type(field_type);
+        let type_arg = inner_field_type.unwrap_or(field_type);
+
+        let ph_field = if inner_field_type.is_none() {
+            quote! { #ph_name: std::marker::PhantomData::<Set>, }
+        } else {
+            quote! {}
+        };
+
+        let remaining_fill = fill_other_fields
+            .clone()
+            .filter(|(other_field_name, _)| other_field_name != field_name)
+            .map(|(_, value)| value);
+
+        let remaining_phantom_fill = fill_other_phantom_fields
+            .clone()
+            .filter(|(other_field_name, _)| other_field_name != field_name)
+            .map(|(_, value)| value);
+
+        let pre_impl_generics_in = required_key_fields
+            .clone()
+            .map(|(gen_name, _)| {
+                if gen_name != field_name {
+                    let gen_name_pascal = get_type_state_name(input, gen_name);
+                    return quote! { #gen_name_pascal, };
                 }
+                quote! {}
+            })
+            .chain(version_generic_in.clone());
 
-    }
+        let generics_in = required_key_fields
+            .clone()
+            .map(|(gen_name, _)| {
+                if gen_name != field_name {
+                    let gen_name_pascal = get_type_state_name(input, gen_name);
+                    return quote! { #gen_name_pascal, };
+                }
+                quote! { NotSet, }
+            })
+            .chain(vec![quote! { NotSet, }])
+            .chain(version_generic_in.clone());
+
+        let generics_out = required_key_fields
+            .clone()
+            .map(|(gen_name, _)| {
+                if gen_name != field_name {
+                    let gen_name_pascal = get_type_state_name(input, gen_name);
+                    return quote! { #gen_name_pascal, };
+                }
+                quote! { Set, }
+            })
+            .chain(vec![quote! { NotSet, }])
+            .chain(version_generic_in.clone());
+
+        quote! {
+            impl <#(#pre_impl_generics_in)*> #builder_struct_name <#(#generics_in)*> {
+                    pub fn #method_name(self, #field_name: #type_arg) -> #builder_struct_name <#(#generics_out)*>  {
+                        #builder_struct_name  {
+                            #field_name: Some(#field_name),
+                            #(#remaining_fill)*
+                            _unique_fields: self._unique_fields,
+                            #ph_field
+                            #(#remaining_phantom_fill)*
+                        }
+                    }
+            }
+        }
+    });
+
+    // Create unique filter functions, like one() only a single unique field can be matched on
+    let unique_methods = unique_filter_fields
+        .iter()
+        .map(|(where_name, field_name, field_type)| {
+            let method_name = quote::format_ident!("{}_eq", field_name);
+            let type_arg = get_inner_option_type(field_type).unwrap_or(field_type);
+
+            let remaining_fill = fill_other_fields
+                .clone()
+                .filter(|(other_field_name, _)| *other_field_name != where_name)
+                .map(|(_, value)| value);
+
+            let remaining_phantom_fill = fill_other_phantom_fields.clone().map(|(_, value)| value);
+
+            let generics_in = required_key_fields
+                .clone()
+                .map(|_| quote! { NotSet, })
+                .chain(vec![quote! { NotSet, }])
+                .chain(version_generic_in.clone());
+
+            let generics_out = required_key_fields
+                .clone()
+                .map(|_| quote! { NotSet, })
+                .chain(vec![quote! { Set, }])
+                .chain(version_generic_in.clone());
+
+            quote! {
+                impl <#(#version_generic_in)*> #builder_struct_name <#(#generics_in)*> {
+                        pub fn #method_name(self, #field_name: #type_arg) -> #builder_struct_name <#(#generics_out)*>  {
+                            #builder_struct_name  {
+                                #where_name: Some(#field_name),
+                                #(#remaining_fill)*
+                                _unique_fields: std::marker::PhantomData::<Set>,
+                                #(#remaining_phantom_fill)*
+                            }
+                        }
+                }
+            }
+        });
+
+    // Create set value functions, these don't change the type-state so work with any keys set
+    let any_generics = required_key_fields
+        .clone()
+        .map(|(gen_name, _)| {
+            let gen_name_pascal = get_type_state_name(input, gen_name);
+            quote! { #gen_name_pascal, }
+        })
+        .chain(vec![quote! { UniqueFields, }])
+        .chain(version_generic_in.clone())
+        .collect::<Vec<_>>();
+
+    let set_methods = get_set_methods(&settable_fields);
+
+    let set_methods_impl = quote! {
+        impl <#(#any_generics)*> #builder_struct_name <#(#any_generics)*> {
+            #(#set_methods)*
+        }
+    };
+
+    // Sets the version the row is expected to still be at
+    let version_method = version_field.map(|(version_name, version_type)| {
+            let method_name = quote::format_ident!("{}_eq", version_name);
+            let ph_name = quote::format_ident!("_{}", version_name);
+            let pre_impl_generics_in = any_generics
+                .iter()
+                .take(any_generics.len() - 1)
+                .collect::<Vec<_>>();
+            let remaining_fill = fill_other_fields
+                .clone()
+                .filter(|(other_field_name, _)| *other_field_name != version_name)
+                .map(|(_, value)| value);
+            let remaining_phantom_fill = fill_other_phantom_fields
+                .clone()
+                .filter(|(other_field_name, _)| *other_field_name != version_name)
+                .map(|(_, value)| value);
+            quote! {
+                impl <#(#pre_impl_generics_in)*> #builder_struct_name <#(#pre_impl_generics_in)* NotSet> {
+                        pub fn #method_name(self, #version_name: #version_type) -> #builder_struct_name <#(#pre_impl_generics_in)* Set> {
+                            #builder_struct_name  {
+                                #version_name: Some(#version_name),
+                                #(#remaining_fill)*
+                                _unique_fields: self._unique_fields,
+                                #(#remaining_phantom_fill)*
+                                #ph_name: std::marker::PhantomData::<Set>,
+                            }
+                        }
+                }
+            }
+    });
+
+    // Only the set fields make it into the SET clause, so the query is built at runtime
+    let none_set_checks = get_none_set_checks(&settable_fields);
+    let update_query_start = format!("UPDATE {table_name} SET ");
+    let push_set_fields = get_push_set_fields(input, &settable_fields);
+    let generated_set_fields = get_generated_set_fields(input);
+
+    // With a #[version] the row must still be at the expected version, and a stale version
+    // gives a ConcurrencyConflict rather than 0 rows affected
+    let (push_version_where_field, execute_result_type, check_rows_affected) = match version_field {
+        Some((version_name, _)) => {
+            let update_error_name = get_update_error_name(input);
+            let version_where_str = format!(" AND {} = ", get_column_sql(input, version_name));
+            (
+                quote! {
+                    query_builder.push(#version_where_str);
+                    query_builder.push_bind(self.#version_name);
+                },
+                quote! { #update_error_name },
+                quote! {
+                    if result.rows_affected() == 0 {
+                        return Err(#update_error_name::ConcurrencyConflict);
+                    }
+                },
+            )
+        }
+        None => (quote! {}, quote! { sqlx::Error }, quote! {}),
+    };
+
+    let get_execute_method =
+        |execute_method_generics: Vec<proc_macro2::TokenStream>,
+         push_where_fields: proc_macro2::TokenStream| {
+            quote! {
+                impl #builder_struct_name <#(#execute_method_generics)*> {
+                        pub async fn execute<'e, E: sqlx::PgExecutor<'e>>(
+                            self,
+                            executor: E,
+                        ) -> Result<u64, #execute_result_type> {
+                            if #(#none_set_checks)&&* {
+                                return Ok(0);
+                            }
+                            let mut query_builder = sqlx::QueryBuilder::<sqlx::Postgres>::new(#update_query_start);
+                            let mut set_fields = query_builder.separated(", ");
+                            #(#push_set_fields)*
+                            #(set_fields.push(#generated_set_fields);)*
+                            #push_where_fields
+                            #push_version_where_field
+                            let result = query_builder.build().execute(executor).await?;
+                            #check_rows_affected
+                            Ok(result.rows_affected())
+                    }
+                }
+            }
+        };
+
+    let key_execute_method = if key_fields.is_empty() || settable_fields.is_empty() {
+        quote! {}
+    } else {
+        let execute_method_generics = required_key_fields
+            .clone()
+            .map(|_| quote! { Set, })
+            .chain(vec![quote! { NotSet, }])
+            .chain(version_field.map(|_| quote! { Set, }))
+            .collect();
+
+        let push_where_fields = key_fields.iter().enumerate().map(|(index, (name, _))| {
+            let column = get_column_sql(input, name);
+            let where_field_str = if index == 0 {
+                format!(" WHERE {column} = ")
+            } else {
+                format!(" AND {column} = ")
+            };
+            quote! {
+                query_builder.push(#where_field_str);
+                query_builder.push_bind(self.#name);
+            }
+        });
+
+        get_execute_method(execute_method_generics, quote! { #(#push_where_fields)* })
+    };
+
+    let unique_execute_method = if unique_fields.is_empty() || settable_fields.is_empty() {
+        quote! {}
+    } else {
+        let execute_method_generics = required_key_fields
+            .clone()
+            .map(|_| quote! { NotSet, })
+            .chain(vec![quote! { Set, }])
+            .chain(version_field.map(|_| quote! { Set, }))
+            .collect();
+
+        let push_where_fields = unique_filter_fields.iter().map(|(where_name, name, _)| {
+            let where_field_str = format!("{} = ", get_column_sql(input, name));
+            quote! {
+                if let Some(#where_name) = self.#where_name {
+                    where_fields.push(#where_field_str);
+                    where_fields.push_bind_unseparated(#where_name);
+                }
+            }
+        });
+
+        get_execute_method(
+            execute_method_generics,
+            quote! {
+                query_builder.push(" WHERE ");
+                let mut where_fields = query_builder.separated(" AND ");
+                #(#push_where_fields)*
+            },
+        )
     };
 
-    let key_fields = get_key_fields(input);
     // Create complete impl
     let where_size = key_fields.len();
     let query_builder_where_fields = key_fields
         .iter()
         .enumerate()
-        .map(|(index, (field_name, _))| format!("{} = ${}", field_name, index + 1))
+        .map(|(index, (field_name, _))| {
+            format!("{} = ${}", get_column_sql(input, field_name), index + 1)
+        })
         .collect::<Vec<_>>()
         .join(" AND ");
 
-    let set_fields = all_fields
+    // A flattened field is written to each of its columns
+    let set_columns = settable_fields
+        .iter()
+        .flat_map(|field| {
+            get_field_columns(input, field.0)
+                .into_iter()
+                .map(move |(sub_field, column)| (field, sub_field, column))
+        })
+        .collect::<Vec<_>>();
+
+    let mut set_fields = set_columns
         .iter()
-        .filter(|(ident, _, _)| !key_fields.iter().any(|(kf_ident, _)| kf_ident == ident))
         .enumerate()
-        .map(|(index, (field_name, _, _))| format!("{} = ${}", field_name, index + where_size + 1))
-        .collect::<Vec<_>>()
-        .join(", ");
+        .map(|(index, (_, _, column))| {
+            format!("{} = ${}", quote_identifier(column), index + where_size + 1)
+        })
+        .collect::<Vec<_>>();
+    set_fields.extend(get_generated_set_fields(input));
+
+    // Binds go keys, then the SET columns, then the expected version, the same order as the params
+    let key_args_fields = all_fields
+        .iter()
+        .filter(|(ident, _, _)| key_fields.iter().any(|(kf_ident, _)| kf_ident == ident));
+    let version_args_field = all_fields.iter().filter(|(ident, _, _)| {
+        version_field.is_some_and(|(version_name, _)| version_name == *ident)
+    });
+    let query_arg = |(name, ty, attrs): &FieldRef, sub_field: &Option<Ident>| {
+        let is_custom_enum = attrs.iter().any(is_custom_enum_attr);
+        match sub_field {
+            Some(sub_field) => quote! { self.updatable.#name.#sub_field, },
+            None if is_custom_enum => quote! { self.updatable.#name as #ty, },
+            None => {
+                let value = get_json_arg(
+                    attrs,
+                    quote! { self.updatable.#name },
+                    get_inner_option_type(ty).is_some(),
+                );
+                quote! { #value, }
+            }
+        }
+    };
+    let query_args = key_args_fields
+        .map(|field| query_arg(field, &None))
+        .chain(
+            set_columns
+                .iter()
+                .map(|(field, sub_field, _)| query_arg(field, sub_field)),
+        )
+        .chain(version_args_field.map(|field| query_arg(field, &None)))
+        .collect::<Vec<_>>();
+
+    let version_where_field = match version_field {
+        Some((version_name, _)) => {
+            format!(
+                " AND {} = ${}",
+                get_column_sql(input, version_name),
+                query_args.len()
+            )
+        }
+        None => String::new(),
+    };
+
+    let set_fields = set_fields.join(", ");
 
     let query = format!(
-        "UPDATE {table_name} SET {set_fields} WHERE {query_builder_where_fields} RETURNING {all_fields_str};"
+        "UPDATE {table_name} SET {set_fields} WHERE {query_builder_where_fields}{version_where_field} RETURNING {all_fields_str};"
     );
 
-    let query_args = all_fields.clone().into_iter().map(|(name, ty, attrs)| {
-        let is_custom_enum = attrs.iter().any(is_custom_enum_attr);
-        if is_custom_enum {
-            quote! { self.updatable.#name as #ty, }
-        } else {
-            quote! { self.updatable.#name, }
+    let update_method = if !has_data_update {
+        // Without a key there is nothing on the struct to find its row by
+        quote! {}
+    } else if version_field.is_some() {
+        let update_error_name = get_update_error_name(input);
+        quote! {
+            impl  #builder_struct_name_with_data  {
+                    pub async fn update<'e, E: sqlx::PgExecutor<'e>>(
+                        self,
+                        executor: E,
+                    ) -> Result<#struct_name, #update_error_name> {
+                        #query_as(
+                            #query_as_struct
+                            #query,
+                            #(#query_args)*
+                        )
+                            #query_as_map
+                            .fetch_one(executor)
+                            .await
+                            .map_err(|err| match err {
+                                sqlx::Error::RowNotFound => #update_error_name::ConcurrencyConflict,
+                                err => #update_error_name::Database(err),
+                            })
+                }
+            }
         }
-    });
-
-    let update_method = quote! {
-        impl  #builder_struct_name_with_data  {
-                pub async fn update<'e, E: sqlx::PgExecutor<'e>>(
-                    self,
-                    executor: E,
-                ) -> Result<#struct_name, sqlx::Error> {
-                    sqlx::query_as!(
-                        #struct_name,
-                        #query,
-                        #(#query_args)*
-                    )
-                        .fetch_one(executor)
-                        .await
+    } else {
+        quote! {
+            impl  #builder_struct_name_with_data  {
+                    pub async fn update<'e, E: sqlx::PgExecutor<'e>>(
+                        self,
+                        executor: E,
+                    ) -> Result<#struct_name, sqlx::Error> {
+                        #query_as(
+                            #query_as_struct
+                            #query,
+                            #(#query_args)*
+                        )
+                            #query_as_map
+                            .fetch_one(executor)
+                            .await
+                }
             }
         }
     };
 
+    let update_error = if version_field.is_some() {
+        get_update_error(input)
+    } else {
+        quote! {}
+    };
+
+    let tracked_update = if has_dbset_flag(&input.attrs, "tracked") {
+        get_tracked_update(input)
+    } else {
+        quote! {}
+    };
+
     let builder_struct_impl = quote! {
         #builder_struct
 
@@ -111,8 +959,90 @@ pub fn get_update_query_builder(input: &DeriveInput) -> proc_macro2::TokenStream
 
         #builder_method
 
+        #(#key_methods)*
+
+        #(#unique_methods)*
+
+        #set_methods_impl
+
+        #version_method
+
+        #update_error
+
         #update_method
+
+        #key_execute_method
+
+        #unique_execute_method
+
+        #tracked_update
     };
 
     builder_struct_impl
 }
+
+pub fn get_many_update_query_builder(input: &DeriveInput) -> proc_macro2::TokenStream {
+    let table_name = get_table_name(input);
+    let builder_struct_name = get_many_update_builder_struct_name(input);
+    let many_query_builder_struct_name = get_many_query_builder_struct_name(input);
+    let settable_fields = get_settable_fields(input);
+
+    let set_struct_fields = settable_fields.iter().map(|(name, ty, _)| {
+        quote! { #name: Option< #ty >, }
+    });
+
+    let initial_struct_fields = settable_fields.iter().map(|(name, _, _)| {
+        quote! { #name: None, }
+    });
+
+    let set_methods = get_set_methods(&settable_fields);
+
+    let execute_method = if settable_fields.is_empty() {
+        quote! {}
+    } else {
+        let none_set_checks = get_none_set_checks(&settable_fields);
+        let update_query_start = format!("UPDATE {table_name} SET ");
+        let push_set_fields = get_push_set_fields(input, &settable_fields);
+        let generated_set_fields = get_generated_set_fields(input);
+        let push_filters = get_many_query_builder_push_filters(input, "");
+
+        quote! {
+                pub async fn execute<'e, E: sqlx::PgExecutor<'e>>(
+                    self,
+                    executor: E,
+                ) -> Result<u64, sqlx::Error> {
+                    if #(#none_set_checks)&&* {
+                        return Ok(0);
+                    }
+                    let mut query_builder = sqlx::QueryBuilder::<sqlx::Postgres>::new(#update_query_start);
+                    let mut set_fields = query_builder.separated(", ");
+                    #(#push_set_fields)*
+                    #(set_fields.push(#generated_set_fields);)*
+                    let filters = self.filters;
+                    #push_filters
+                    let result = query_builder.build().execute(executor).await?;
+                    Ok(result.rows_affected())
+            }
+        }
+    };
+
+    quote! {
+        pub struct #builder_struct_name {
+            filters: #many_query_builder_struct_name<Set>,
+            #(#set_struct_fields)*
+        }
+
+        impl #builder_struct_name {
+            pub fn new(filters: #many_query_builder_struct_name<Set>) -> Self {
+                Self {
+                    filters,
+                    #(#initial_struct_fields)*
+                }
+            }
+
+            #(#set_methods)*
+
+            #execute_method
+        }
+    }
+}
@@ -0,0 +1,32 @@
+DO $$
+BEGIN
+  IF NOT EXISTS (SELECT 1 FROM pg_type WHERE typname = 'user_status') THEN
+    CREATE TYPE user_status AS ENUM ('verified', 'unverified');
+  END IF;
+END
+$$;
+
+CREATE TABLE IF NOT EXISTS users (name text not null, id text not null, email text not null, details text, status user_status not null);
+
+CREATE TABLE IF NOT EXISTS counters (id serial primary key, hits int not null default 0);
+CREATE TABLE IF NOT EXISTS tickets (code text primary key, title text not null, labels text, version int not null default 1);
+CREATE TABLE IF NOT EXISTS audit_logs (message text not null);
+
+CREATE TABLE IF NOT EXISTS notes (id int primary key, body text not null, deleted_at timestamptz);
+
+CREATE TABLE IF NOT EXISTS sponsors (id int primary key, name text not null);
+CREATE TABLE IF NOT EXISTS teams (id int primary key, name text not null, sponsor_id int references sponsors(id));
+CREATE TABLE IF NOT EXISTS players (id int primary key, name text not null, team_id int not null references teams(id));
+CREATE TABLE IF NOT EXISTS posts (id int primary key, title text not null);
+CREATE TABLE IF NOT EXISTS categories (id int primary key, name text not null);
+CREATE TABLE IF NOT EXISTS regions (id int primary key, code text unique not null, name text not null);
+CREATE TABLE IF NOT EXISTS offices (id int primary key, region_code text not null references regions(code));
+CREATE TABLE IF NOT EXISTS post_categories (post_id int not null references posts(id), category_id int not null references categories(id), primary key (post_id, category_id));
+
+CREATE TABLE IF NOT EXISTS customers ("customerId" int primary key, "displayName" text not null, kind text not null);
+CREATE SCHEMA IF NOT EXISTS billing;
+CREATE TABLE IF NOT EXISTS billing.invoices (id int primary key, "order" int not null);
+CREATE TABLE IF NOT EXISTS books (id int primary key, title text not null);
+CREATE TABLE IF NOT EXISTS venues (id int primary key, address_street text not null, address_city text, postal_street text not null, postal_city text);
+
+CREATE TABLE IF NOT EXISTS profiles (id int primary key, settings jsonb not null, metadata jsonb);
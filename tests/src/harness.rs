@@ -28,21 +28,7 @@ async fn prepare_db() -> (Container<'static, TestPostgres>, Pool<Postgres>) {
         .await
         .expect("Could not connect to postgres");
 
-    sqlx::query(
-        r#"
-            DO $$
-            BEGIN
-              IF NOT EXISTS (SELECT 1 FROM pg_type WHERE typname = 'user_status') THEN
-                CREATE TYPE user_status AS ENUM ('verified', 'unverified');
-              END IF;
-            END
-            $$;
-    "#,
-    )
-    .execute(&pool)
-    .await
-    .expect("Could not initialise db");
-    sqlx::query("CREATE TABLE IF NOT EXISTS users (name text not null, id text not null, email text not null, details text, status user_status not null);")
+    sqlx::raw_sql(include_str!("../schema.sql"))
         .execute(&pool)
         .await
         .expect("Could not initialise db");
@@ -1,4 +1,14 @@
 #[cfg(test)]
 pub mod harness;
 #[cfg(test)]
+pub mod test_column_mapping;
+#[cfg(test)]
+pub mod test_defaults_and_versions;
+#[cfg(test)]
+pub mod test_json;
+#[cfg(test)]
+pub mod test_relations;
+#[cfg(test)]
+pub mod test_soft_delete;
+#[cfg(test)]
 pub mod test_user_with_one_key_one_unique_one_optional;
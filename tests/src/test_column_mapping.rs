@@ -0,0 +1,196 @@
+use db_set_macros::DbSet;
+
+use crate::harness::get_db_pool;
+
+#[derive(DbSet, Debug, Clone)]
+#[dbset(table_name = "customers")]
+#[dbset(rename_all = "camelCase")]
+pub struct Customer {
+    #[key]
+    customer_id: i32,
+    display_name: String,
+    #[dbset(column = "kind")]
+    r#type: String,
+}
+
+#[derive(DbSet, Debug, Clone)]
+#[dbset(table_name = "invoices", schema = "billing")]
+pub struct Invoice {
+    #[key]
+    id: i32,
+    order: i32,
+}
+
+#[derive(DbSet, Debug, Clone)]
+#[dbset(table_name = "books")]
+pub struct Book {
+    #[key]
+    id: i32,
+    title: String,
+    #[dbset(skip)]
+    word_count: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Address {
+    street: String,
+    city: Option<String>,
+}
+
+#[derive(DbSet, Debug, Clone)]
+#[dbset(table_name = "venues")]
+pub struct Venue {
+    #[key]
+    id: i32,
+    #[dbset(flatten(street, city))]
+    address: Address,
+    #[dbset(flatten(street, city, prefix = "postal_"))]
+    mailing_address: Address,
+}
+
+#[tokio::test]
+async fn test_renamed_columns() -> Result<(), String> {
+    let pool = get_db_pool().await;
+
+    CustomerDbSet::insert()
+        .customer_id(1)
+        .display_name("Bob's Burgers".to_string())
+        .r#type("business".to_string())
+        .insert(pool)
+        .await
+        .expect("Could not insert");
+
+    let customers = CustomerDbSet::many()
+        .type_eq("business".to_string())
+        .fetch_all(pool)
+        .await
+        .expect("Could not fetch");
+
+    assert_eq!(customers.len(), 1);
+    assert_eq!(customers[0].display_name, "Bob's Burgers");
+
+    let rows_affected = CustomerDbSet::update()
+        .customer_id_eq(1)
+        .set_display_name("Bob's Burgers & Fries".to_string())
+        .execute(pool)
+        .await
+        .expect("Could not update");
+
+    assert_eq!(rows_affected, 1);
+
+    let customer = CustomerDbSet::one()
+        .customer_id_eq(1)
+        .fetch_one(pool)
+        .await
+        .expect("Could not fetch one");
+
+    assert_eq!(customer.display_name, "Bob's Burgers & Fries");
+    assert_eq!(customer.r#type, "business");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_schema_table_with_reserved_column() -> Result<(), String> {
+    let pool = get_db_pool().await;
+
+    InvoiceDbSet::insert()
+        .id(1)
+        .order(42)
+        .insert(pool)
+        .await
+        .expect("Could not insert");
+
+    let invoices = InvoiceDbSet::many()
+        .order_eq(42)
+        .fetch_all(pool)
+        .await
+        .expect("Could not fetch");
+
+    assert_eq!(invoices.len(), 1);
+
+    let rows_affected = InvoiceDbSet::delete()
+        .id_eq(1)
+        .delete(pool)
+        .await
+        .expect("Could not delete");
+
+    assert_eq!(rows_affected, 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_skipped_fields() -> Result<(), String> {
+    let pool = get_db_pool().await;
+
+    let book = BookDbSet::insert()
+        .id(1)
+        .title("Dune".to_string())
+        .insert(pool)
+        .await
+        .expect("Could not insert");
+
+    assert_eq!(book.word_count, None);
+
+    let mut book = BookDbSet::one()
+        .id_eq(1)
+        .fetch_one(pool)
+        .await
+        .expect("Could not fetch one");
+
+    assert_eq!(book.word_count, None);
+
+    book.title = "Dune Messiah".to_string();
+    book.word_count = Some(100_000);
+    let updated = BookDbSet::update()
+        .data(book)
+        .update(pool)
+        .await
+        .expect("Could not update");
+
+    assert_eq!(updated.title, "Dune Messiah");
+    assert_eq!(updated.word_count, None);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_flattened_fields() -> Result<(), String> {
+    let pool = get_db_pool().await;
+
+    let address = Address {
+        street: "1 Main St".to_string(),
+        city: Some("Springfield".to_string()),
+    };
+    let po_box = Address {
+        street: "PO Box 7".to_string(),
+        city: None,
+    };
+
+    let venue = VenueDbSet::insert()
+        .id(1)
+        .address(address.clone())
+        .mailing_address(po_box.clone())
+        .insert(pool)
+        .await
+        .expect("Could not insert");
+
+    assert_eq!(venue.address, address);
+    assert_eq!(venue.mailing_address, po_box);
+
+    let mut venue = VenueDbSet::one()
+        .id_eq(1)
+        .fetch_one(pool)
+        .await
+        .expect("Could not fetch one");
+
+    assert_eq!(venue.address, address);
+
+    venue.mailing_address = address.clone();
+    let updated = VenueDbSet::update()
+        .data(venue)
+        .update(pool)
+        .await
+        .expect("Could not update");
+
+    assert_eq!(updated.mailing_address, address);
+    Ok(())
+}
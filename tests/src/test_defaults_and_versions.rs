@@ -0,0 +1,191 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use db_set_macros::DbSet;
+
+use crate::harness::get_db_pool;
+
+#[derive(DbSet, Debug, Clone)]
+#[dbset(table_name = "counters")]
+pub struct Counter {
+    #[key]
+    #[auto]
+    id: i32,
+    #[db_default]
+    hits: i32,
+}
+
+static NEXT_TICKET: AtomicUsize = AtomicUsize::new(1);
+
+fn next_ticket_code() -> String {
+    format!("ticket-{}", NEXT_TICKET.fetch_add(1, Ordering::SeqCst))
+}
+
+#[derive(DbSet, Debug, Clone)]
+#[dbset(table_name = "tickets")]
+pub struct Ticket {
+    #[key]
+    #[dbset(default = "next_ticket_code")]
+    code: String,
+    title: String,
+    #[dbset(default = "\"triage\".to_string()")]
+    labels: Option<String>,
+    #[version]
+    version: i32,
+}
+
+// An append-only table: no #[key] or #[unique] field, so every column has to be #[readonly]
+#[derive(DbSet, Debug, Clone)]
+#[dbset(table_name = "audit_logs")]
+pub struct AuditLog {
+    #[readonly]
+    message: String,
+}
+
+#[tokio::test]
+async fn test_insert_only_db_default_fields() -> Result<(), String> {
+    let pool = get_db_pool().await;
+
+    let counter = CounterDbSet::insert()
+        .insert(pool)
+        .await
+        .expect("Could not insert with DEFAULT VALUES");
+
+    assert_eq!(counter.hits, 0);
+
+    let counter = CounterDbSet::insert()
+        .hits(5)
+        .insert(pool)
+        .await
+        .expect("Could not insert");
+
+    assert_eq!(counter.hits, 5);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_insert_rust_defaults() -> Result<(), String> {
+    let pool = get_db_pool().await;
+
+    let ticket = TicketDbSet::insert()
+        .title("printer is on fire".to_string())
+        .insert(pool)
+        .await
+        .expect("Could not insert");
+
+    assert!(ticket.code.starts_with("ticket-"));
+    assert_eq!(ticket.labels.as_deref(), Some("triage"));
+    assert_eq!(ticket.version, 1);
+
+    let ticket = TicketDbSet::insert()
+        .code("ticket-custom".to_string())
+        .title("printer is fine".to_string())
+        .labels("hardware".to_string())
+        .insert(pool)
+        .await
+        .expect("Could not insert");
+
+    assert_eq!(ticket.code, "ticket-custom");
+    assert_eq!(ticket.labels.as_deref(), Some("hardware"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_update_checks_version() -> Result<(), String> {
+    let pool = get_db_pool().await;
+
+    let ticket = TicketDbSet::insert()
+        .title("login is slow".to_string())
+        .insert(pool)
+        .await
+        .expect("Could not insert");
+
+    let mut updated = ticket.clone();
+    updated.title = "login is very slow".to_string();
+    let updated = TicketDbSet::update()
+        .data(updated)
+        .update(pool)
+        .await
+        .expect("Could not update");
+
+    assert_eq!(updated.version, 2);
+
+    let stale_update = TicketDbSet::update()
+        .data(ticket.clone())
+        .update(pool)
+        .await;
+
+    assert!(matches!(
+        stale_update,
+        Err(TicketDbSetUpdateError::ConcurrencyConflict)
+    ));
+
+    let stale_partial_update = TicketDbSet::update()
+        .code_eq(ticket.code.clone())
+        .version_eq(ticket.version)
+        .set_title("login is fast".to_string())
+        .execute(pool)
+        .await;
+
+    assert!(matches!(
+        stale_partial_update,
+        Err(TicketDbSetUpdateError::ConcurrencyConflict)
+    ));
+
+    let rows_affected = TicketDbSet::update()
+        .code_eq(ticket.code.clone())
+        .version_eq(updated.version)
+        .set_title("login is fast".to_string())
+        .execute(pool)
+        .await
+        .expect("Could not update");
+
+    assert_eq!(rows_affected, 1);
+
+    let fetched = TicketDbSet::one()
+        .code_eq(ticket.code)
+        .fetch_one(pool)
+        .await
+        .expect("Could not fetch one");
+
+    assert_eq!(fetched.title, "login is fast");
+    assert_eq!(fetched.version, 3);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_set_without_key_inserts_and_deletes_through_many() -> Result<(), String> {
+    let pool = get_db_pool().await;
+
+    for message in ["started", "stopped"] {
+        AuditLogDbSet::insert()
+            .message(message.to_string())
+            .insert(pool)
+            .await
+            .expect("Could not insert");
+    }
+
+    let logs = AuditLogDbSet::many()
+        .fetch_all(pool)
+        .await
+        .expect("Could not fetch");
+
+    assert_eq!(logs.len(), 2);
+    assert!(logs.iter().any(|log| log.message == "started"));
+
+    let rows_affected = AuditLogDbSet::many()
+        .message_eq("started".to_string())
+        .delete(pool)
+        .await
+        .expect("Could not delete");
+
+    assert_eq!(rows_affected, 1);
+
+    let remaining = AuditLogDbSet::many()
+        .fetch_all(pool)
+        .await
+        .expect("Could not fetch");
+
+    assert_eq!(remaining.len(), 1);
+    assert_eq!(remaining[0].message, "stopped");
+    Ok(())
+}
@@ -0,0 +1,111 @@
+use std::collections::HashMap;
+
+use db_set_macros::DbSet;
+use serde_derive::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::harness::get_db_pool;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Settings {
+    theme: String,
+    beta: bool,
+}
+
+#[derive(DbSet, Debug, Clone)]
+#[dbset(table_name = "profiles")]
+pub struct Profile {
+    #[key]
+    id: i32,
+    #[json]
+    settings: Settings,
+    #[json]
+    metadata: Option<HashMap<String, String>>,
+}
+
+async fn insert_profile(id: i32, theme: &str, beta: bool, referrer: Option<&str>) -> Profile {
+    let pool = get_db_pool().await;
+    let profile = ProfileDbSet::insert().id(id).settings(Settings {
+        theme: theme.to_string(),
+        beta,
+    });
+    let profile = match referrer {
+        Some(referrer) => profile.metadata(HashMap::from([(
+            "referrer".to_string(),
+            referrer.to_string(),
+        )])),
+        None => profile,
+    };
+    profile.insert(pool).await.expect("Could not insert")
+}
+
+#[tokio::test]
+async fn test_json_round_trip() -> Result<(), String> {
+    let pool = get_db_pool().await;
+
+    let profile = insert_profile(1, "dark", true, Some("newsletter")).await;
+
+    assert_eq!(profile.settings.theme, "dark");
+    assert_eq!(
+        profile
+            .metadata
+            .as_ref()
+            .and_then(|metadata| metadata.get("referrer"))
+            .map(String::as_str),
+        Some("newsletter")
+    );
+
+    let mut profile = ProfileDbSet::one()
+        .id_eq(1)
+        .fetch_one(pool)
+        .await
+        .expect("Could not fetch one");
+
+    profile.settings.beta = false;
+    profile.metadata = None;
+    let updated = ProfileDbSet::update()
+        .data(profile)
+        .update(pool)
+        .await
+        .expect("Could not update");
+
+    assert!(!updated.settings.beta);
+    assert!(updated.metadata.is_none());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_json_filters() -> Result<(), String> {
+    let pool = get_db_pool().await;
+
+    insert_profile(10, "solarized", true, Some("search")).await;
+    insert_profile(11, "solarized", false, None).await;
+
+    let beta_testers = ProfileDbSet::many()
+        .settings_contains(json!({ "theme": "solarized", "beta": true }))
+        .fetch_all(pool)
+        .await
+        .expect("Could not fetch");
+
+    assert_eq!(beta_testers.len(), 1);
+    assert_eq!(beta_testers[0].id, 10);
+
+    let referred = ProfileDbSet::many()
+        .settings_path_eq(&["theme"], json!("solarized"))
+        .metadata_has_key("referrer")
+        .fetch_all(pool)
+        .await
+        .expect("Could not fetch");
+
+    assert_eq!(referred.len(), 1);
+    assert_eq!(referred[0].id, 10);
+
+    let solarized = ProfileDbSet::many()
+        .settings_path_eq(&["theme"], json!("solarized"))
+        .fetch_all(pool)
+        .await
+        .expect("Could not fetch");
+
+    assert_eq!(solarized.len(), 2);
+    Ok(())
+}
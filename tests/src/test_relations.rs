@@ -0,0 +1,325 @@
+use db_set_macros::DbSet;
+
+use crate::harness::get_db_pool;
+
+// Has no relations of its own, but teams can still be filtered on it
+#[derive(DbSet, Debug, Clone)]
+#[dbset(table_name = "sponsors")]
+pub struct Sponsor {
+    #[key]
+    id: i32,
+    name: String,
+}
+
+#[derive(DbSet, Debug, Clone)]
+#[dbset(table_name = "teams")]
+#[relation(belongs_to = "Sponsor", foreign_key = "sponsor_id")]
+#[dbset(has_many(Player, foreign_key = "team_id"))]
+pub struct Team {
+    #[key]
+    id: i32,
+    name: String,
+    sponsor_id: Option<i32>,
+}
+
+#[derive(DbSet, Debug, Clone)]
+#[dbset(table_name = "players")]
+#[relation(belongs_to = "Team", foreign_key = "team_id")]
+pub struct Player {
+    #[key]
+    id: i32,
+    name: String,
+    team_id: i32,
+}
+
+#[derive(DbSet, Debug, Clone)]
+#[dbset(table_name = "categories")]
+pub struct Category {
+    #[key]
+    id: i32,
+    name: String,
+}
+
+#[derive(DbSet, Debug, Clone)]
+#[dbset(table_name = "posts")]
+#[dbset(many_to_many(
+    Category,
+    through = "post_categories",
+    local = "post_id",
+    remote = "category_id"
+))]
+pub struct Post {
+    #[key]
+    id: i32,
+    title: String,
+}
+
+// The join table behind Post's categories, with nothing to update but its keys
+#[derive(DbSet, Debug, Clone)]
+#[dbset(table_name = "post_categories")]
+pub struct PostCategory {
+    #[key]
+    post_id: i32,
+    #[key]
+    category_id: i32,
+}
+
+// Offices point at a region through its unique code rather than its key
+#[derive(DbSet, Debug, Clone)]
+#[dbset(table_name = "regions")]
+pub struct Region {
+    #[key]
+    id: i32,
+    #[unique]
+    code: String,
+    name: String,
+}
+
+#[derive(DbSet, Debug, Clone)]
+#[dbset(table_name = "offices")]
+#[relation(belongs_to = "Region", foreign_key = "region_code", references = "code")]
+pub struct Office {
+    #[key]
+    id: i32,
+    region_code: String,
+}
+
+async fn insert_team(id: i32, name: &str, sponsor_id: Option<i32>) -> Team {
+    let pool = get_db_pool().await;
+    let team = TeamDbSet::insert().id(id).name(name.to_string());
+    let team = match sponsor_id {
+        Some(sponsor_id) => team.sponsor_id(sponsor_id),
+        None => team,
+    };
+    team.insert(pool).await.expect("Could not insert team")
+}
+
+async fn insert_player(id: i32, name: &str, team_id: i32) -> Player {
+    let pool = get_db_pool().await;
+    PlayerDbSet::insert()
+        .id(id)
+        .name(name.to_string())
+        .team_id(team_id)
+        .insert(pool)
+        .await
+        .expect("Could not insert player")
+}
+
+#[tokio::test]
+async fn test_belongs_to_loading() -> Result<(), String> {
+    let pool = get_db_pool().await;
+
+    let reds = insert_team(10, "reds", None).await;
+    let blues = insert_team(11, "blues", None).await;
+    let ann = insert_player(10, "ann", reds.id).await;
+    let ben = insert_player(11, "ben", blues.id).await;
+
+    let team = ann.team(pool).await.expect("Could not load team");
+    assert_eq!(team.name, "reds");
+
+    let sponsor = reds.sponsor(pool).await.expect("Could not load sponsor");
+    assert!(sponsor.is_none());
+
+    let teams = PlayerDbSet::load_teams(&[ann.clone(), ben.clone()], pool)
+        .await
+        .expect("Could not load teams");
+
+    assert_eq!(teams.len(), 2);
+    assert_eq!(teams[&ann.team_id].name, "reds");
+    assert_eq!(teams[&ben.team_id].name, "blues");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_belongs_to_loading_by_referenced_field() -> Result<(), String> {
+    let pool = get_db_pool().await;
+
+    // The ids are swapped against the codes, so loading by key would pick the wrong regions
+    for (id, code, name) in [(61, "eu", "Europe"), (60, "us", "United States")] {
+        RegionDbSet::insert()
+            .id(id)
+            .code(code.to_string())
+            .name(name.to_string())
+            .insert(pool)
+            .await
+            .expect("Could not insert region");
+    }
+    let mut offices = Vec::new();
+    for (id, region_code) in [(60, "eu"), (61, "us")] {
+        let office = OfficeDbSet::insert()
+            .id(id)
+            .region_code(region_code.to_string())
+            .insert(pool)
+            .await
+            .expect("Could not insert office");
+        offices.push(office);
+    }
+
+    let region = offices[0].region(pool).await.expect("Could not load region");
+    assert_eq!(region.name, "Europe");
+
+    let regions = OfficeDbSet::load_regions(&offices, pool)
+        .await
+        .expect("Could not load regions");
+
+    assert_eq!(regions.len(), 2);
+    assert_eq!(regions["eu"].name, "Europe");
+    assert_eq!(regions["us"].name, "United States");
+
+    let offices_with_regions = OfficeDbSet::many()
+        .region_code_eq("us".to_string())
+        .with_region()
+        .fetch_all(pool)
+        .await
+        .expect("Could not fetch offices");
+
+    assert_eq!(offices_with_regions.len(), 1);
+    assert_eq!(
+        offices_with_regions[0].1.as_ref().map(|region| region.id),
+        Some(60)
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_has_many_loading() -> Result<(), String> {
+    let pool = get_db_pool().await;
+
+    let greens = insert_team(20, "greens", None).await;
+    let golds = insert_team(21, "golds", None).await;
+    insert_player(20, "cat", greens.id).await;
+    insert_player(21, "dan", greens.id).await;
+    insert_player(22, "eve", golds.id).await;
+
+    let players = greens
+        .players()
+        .fetch_all(pool)
+        .await
+        .expect("Could not fetch players");
+
+    assert_eq!(players.len(), 2);
+
+    let dans = greens
+        .players()
+        .name_eq("dan".to_string())
+        .fetch_all(pool)
+        .await
+        .expect("Could not fetch players");
+
+    assert_eq!(dans.len(), 1);
+
+    let players_by_team = TeamDbSet::load_players(&[greens.clone(), golds.clone()], pool)
+        .await
+        .expect("Could not load players");
+
+    assert_eq!(players_by_team[&greens.id].len(), 2);
+    assert_eq!(players_by_team[&golds.id].len(), 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_many_to_many_linking() -> Result<(), String> {
+    let pool = get_db_pool().await;
+
+    let post = PostDbSet::insert()
+        .id(30)
+        .title("relations in rust".to_string())
+        .insert(pool)
+        .await
+        .expect("Could not insert post");
+    for (id, name) in [(30, "rust"), (31, "sql")] {
+        CategoryDbSet::insert()
+            .id(id)
+            .name(name.to_string())
+            .insert(pool)
+            .await
+            .expect("Could not insert category");
+    }
+
+    assert_eq!(post.add_category(30, pool).await.expect("Could not add"), 1);
+    assert_eq!(post.add_category(31, pool).await.expect("Could not add"), 1);
+    assert_eq!(post.add_category(31, pool).await.expect("Could not add"), 0);
+
+    let categories = post.categories(pool).await.expect("Could not load");
+    assert_eq!(categories.len(), 2);
+
+    let link = PostCategoryDbSet::one()
+        .post_id_eq(post.id)
+        .category_id_eq(31)
+        .fetch_optional(pool)
+        .await
+        .expect("Could not fetch link")
+        .expect("Link was not found");
+    assert_eq!((link.post_id, link.category_id), (post.id, 31));
+
+    assert_eq!(
+        post.remove_category(30, pool)
+            .await
+            .expect("Could not remove"),
+        1
+    );
+
+    let categories = post.categories(pool).await.expect("Could not load");
+    assert_eq!(categories.len(), 1);
+    assert_eq!(categories[0].name, "sql");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_fetch_with_joined_relation() -> Result<(), String> {
+    let pool = get_db_pool().await;
+
+    let purples = insert_team(40, "purples", None).await;
+    insert_player(40, "fay", purples.id).await;
+
+    let players_with_teams = PlayerDbSet::many()
+        .name_eq("fay".to_string())
+        .with_team()
+        .fetch_all(pool)
+        .await
+        .expect("Could not fetch players");
+
+    assert_eq!(players_with_teams.len(), 1);
+    let (player, team) = &players_with_teams[0];
+    assert_eq!(player.name, "fay");
+    assert_eq!(
+        team.as_ref().map(|team| team.name.as_str()),
+        Some("purples")
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_filter_on_related_fields() -> Result<(), String> {
+    let pool = get_db_pool().await;
+
+    SponsorDbSet::insert()
+        .id(50)
+        .name("acme".to_string())
+        .insert(pool)
+        .await
+        .expect("Could not insert sponsor");
+    let oranges = insert_team(50, "oranges", Some(50)).await;
+    insert_team(51, "limes", None).await;
+    insert_player(50, "gus", oranges.id).await;
+    insert_player(51, "hal", 51).await;
+
+    let sponsored = TeamDbSet::many()
+        .sponsor(|sponsors| sponsors.name_eq("acme".to_string()))
+        .fetch_all(pool)
+        .await
+        .expect("Could not fetch teams");
+
+    assert_eq!(sponsored.len(), 1);
+    assert_eq!(sponsored[0].name, "oranges");
+
+    let players = PlayerDbSet::many()
+        .team(|teams| teams.name_eq("oranges".to_string()))
+        .fetch_all(pool)
+        .await
+        .expect("Could not fetch players");
+
+    assert_eq!(players.len(), 1);
+    assert_eq!(players[0].name, "gus");
+    Ok(())
+}
@@ -0,0 +1,109 @@
+use db_set_macros::DbSet;
+use sqlx::types::chrono::{DateTime, Utc};
+
+use crate::harness::get_db_pool;
+
+#[derive(DbSet, Debug, Clone)]
+#[dbset(table_name = "notes")]
+pub struct Note {
+    #[key]
+    id: i32,
+    body: String,
+    #[soft_delete]
+    deleted_at: Option<DateTime<Utc>>,
+}
+
+#[tokio::test]
+async fn test_soft_delete_and_restore() -> Result<(), String> {
+    let pool = get_db_pool().await;
+
+    NoteDbSet::insert()
+        .id(1)
+        .body("buy milk".to_string())
+        .insert(pool)
+        .await
+        .expect("Could not insert");
+
+    let rows_affected = NoteDbSet::delete()
+        .id_eq(1)
+        .delete(pool)
+        .await
+        .expect("Could not delete");
+
+    assert_eq!(rows_affected, 1);
+
+    let hidden = NoteDbSet::one()
+        .id_eq(1)
+        .fetch_optional(pool)
+        .await
+        .expect("Could not fetch");
+
+    assert!(hidden.is_none());
+
+    let deleted = NoteDbSet::many()
+        .body_eq("buy milk".to_string())
+        .only_deleted()
+        .fetch_all(pool)
+        .await
+        .expect("Could not fetch");
+
+    assert_eq!(deleted.len(), 1);
+    assert!(deleted[0].deleted_at.is_some());
+
+    let rows_affected = NoteDbSet::one()
+        .id_eq(1)
+        .restore(pool)
+        .await
+        .expect("Could not restore");
+
+    assert_eq!(rows_affected, 1);
+
+    let restored = NoteDbSet::one()
+        .id_eq(1)
+        .fetch_one(pool)
+        .await
+        .expect("Could not fetch one");
+
+    assert!(restored.deleted_at.is_none());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_bulk_soft_delete() -> Result<(), String> {
+    let pool = get_db_pool().await;
+
+    for id in [2, 3] {
+        NoteDbSet::insert()
+            .id(id)
+            .body("draft".to_string())
+            .insert(pool)
+            .await
+            .expect("Could not insert");
+    }
+
+    let rows_affected = NoteDbSet::many()
+        .body_eq("draft".to_string())
+        .delete(pool)
+        .await
+        .expect("Could not delete");
+
+    assert_eq!(rows_affected, 2);
+
+    let visible = NoteDbSet::many()
+        .body_eq("draft".to_string())
+        .fetch_all(pool)
+        .await
+        .expect("Could not fetch");
+
+    assert!(visible.is_empty());
+
+    let all_drafts = NoteDbSet::many()
+        .body_eq("draft".to_string())
+        .with_deleted()
+        .fetch_all(pool)
+        .await
+        .expect("Could not fetch");
+
+    assert_eq!(all_drafts.len(), 2);
+    Ok(())
+}
@@ -122,6 +122,35 @@ async fn test_insert_users() -> Result<(), String> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_insert_users_returning_key() -> Result<(), String> {
+    let pool = get_db_pool().await;
+
+    let inserted_id = UserDbSet::insert()
+        .id("id-4".to_string())
+        .email("stacey@staceyson.com".to_string())
+        .name("stacey".to_string())
+        .status(UserStatus::Unverified)
+        .insert_returning_key(pool)
+        .await
+        .expect("Could not insert");
+
+    assert_eq!(inserted_id, "id-4");
+
+    let rows_affected = UserDbSet::insert()
+        .id("id-5".to_string())
+        .email("sam@samson.com".to_string())
+        .name("sam".to_string())
+        .status(UserStatus::Unverified)
+        .insert_no_return(pool)
+        .await
+        .expect("Could not insert");
+
+    assert_eq!(rows_affected, 1);
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_update_users() -> Result<(), String> {
     let pool = get_db_pool().await;
@@ -156,6 +185,101 @@ async fn test_update_users() -> Result<(), String> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_partial_update_users() -> Result<(), String> {
+    let pool = get_db_pool().await;
+
+    sqlx::query("INSERT INTO users (name, id, email, details, status) VALUES ('rob', 'id-7', 'rob@bigpond.com.au', 'some details', 'verified');")
+        .execute(pool)
+        .await
+        .expect("Could not initialise db");
+
+    let rows_affected = UserDbSet::update()
+        .id_eq("id-7".to_string())
+        .set_name("robert".to_string())
+        .set_details(None)
+        .execute(pool)
+        .await
+        .expect("Could not update");
+
+    assert_eq!(rows_affected, 1);
+
+    let updated_user = UserDbSet::one()
+        .id_eq("id-7".to_string())
+        .fetch_one(pool)
+        .await
+        .expect("Could not fetch one");
+
+    assert_eq!(updated_user.name, "robert");
+    assert_eq!(updated_user.details, None);
+    assert_eq!(updated_user.email, "rob@bigpond.com.au");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_update_users_by_unique_field() -> Result<(), String> {
+    let pool = get_db_pool().await;
+
+    sqlx::query("INSERT INTO users (name, id, email, status) VALUES ('sam', 'id-10', 'sam@bigpond.com.au', 'unverified');")
+        .execute(pool)
+        .await
+        .expect("Could not initialise db");
+
+    let rows_affected = UserDbSet::update()
+        .email_eq("sam@bigpond.com.au".to_string())
+        .set_name("samantha".to_string())
+        .set_email("samantha@bigpond.com.au".to_string())
+        .execute(pool)
+        .await
+        .expect("Could not update");
+
+    assert_eq!(rows_affected, 1);
+
+    let updated_user = UserDbSet::one()
+        .id_eq("id-10".to_string())
+        .fetch_one(pool)
+        .await
+        .expect("Could not fetch one");
+
+    assert_eq!(updated_user.name, "samantha");
+    assert_eq!(updated_user.email, "samantha@bigpond.com.au");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_bulk_update_users() -> Result<(), String> {
+    let pool = get_db_pool().await;
+
+    sqlx::query("INSERT INTO users (name, id, email, status) VALUES ('kim', 'id-8', 'kim@bigpond.com.au', 'unverified'), ('kim', 'id-9', 'kimmy@bigpond.com.au', 'unverified');")
+        .execute(pool)
+        .await
+        .expect("Could not initialise db");
+
+    let rows_affected = UserDbSet::many()
+        .name_eq("kim".to_string())
+        .update()
+        .set_status(UserStatus::Verified)
+        .set_details(Some("bulk updated".to_string()))
+        .execute(pool)
+        .await
+        .expect("Could not update");
+
+    assert_eq!(rows_affected, 2);
+
+    let updated_users = UserDbSet::many()
+        .name_eq("kim".to_string())
+        .details_eq("bulk updated".to_string())
+        .fetch_all(pool)
+        .await
+        .expect("Could not fetch users");
+
+    assert_eq!(updated_users.len(), 2);
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_delete_users() -> Result<(), String> {
     let pool = get_db_pool().await;
@@ -165,12 +289,14 @@ async fn test_delete_users() -> Result<(), String> {
         .await
         .expect("Could not initialise db");
 
-    UserDbSet::delete()
+    let rows_affected = UserDbSet::delete()
         .id_eq("id-6".to_string())
         .delete(pool)
         .await
         .expect("could not delete");
 
+    assert_eq!(rows_affected, 1);
+
     let matched_user = sqlx::query_as!(
         User,
         "SELECT id, name, email, details, status as \"status:UserStatus\" FROM users WHERE id = 'id-6';"
@@ -183,3 +309,73 @@ async fn test_delete_users() -> Result<(), String> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_bulk_delete_users() -> Result<(), String> {
+    let pool = get_db_pool().await;
+
+    sqlx::query("INSERT INTO users (name, id, email, details, status) VALUES ('del', 'id-11', 'del@bigpond.com.au', 'to delete', 'unverified'), ('del', 'id-12', 'dell@bigpond.com.au', 'to delete', 'unverified');")
+        .execute(pool)
+        .await
+        .expect("Could not initialise db");
+
+    let rows_affected = UserDbSet::many()
+        .name_eq("del".to_string())
+        .details_eq("to delete".to_string())
+        .delete(pool)
+        .await
+        .expect("Could not delete");
+
+    assert_eq!(rows_affected, 2);
+
+    let remaining_users = UserDbSet::many()
+        .name_eq("del".to_string())
+        .fetch_all(pool)
+        .await
+        .expect("Could not fetch users");
+
+    assert!(remaining_users.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_delete_returning_users() -> Result<(), String> {
+    let pool = get_db_pool().await;
+
+    sqlx::query("INSERT INTO users (name, id, email, status) VALUES ('archie', 'id-13', 'archie@bigpond.com.au', 'verified'), ('archie', 'id-14', 'arch@bigpond.com.au', 'unverified'), ('archie', 'id-15', 'archibald@bigpond.com.au', 'unverified');")
+        .execute(pool)
+        .await
+        .expect("Could not initialise db");
+
+    let deleted_user = UserDbSet::delete()
+        .id_eq("id-13".to_string())
+        .delete_returning(pool)
+        .await
+        .expect("Could not delete")
+        .expect("Deleted user was not returned");
+
+    assert_eq!(deleted_user.email, "archie@bigpond.com.au");
+    assert!(matches!(deleted_user.status, UserStatus::Verified));
+
+    let missing_user = UserDbSet::delete()
+        .id_eq("id-13".to_string())
+        .delete_returning(pool)
+        .await
+        .expect("Could not delete");
+
+    assert!(missing_user.is_none());
+
+    let deleted_users = UserDbSet::many()
+        .name_eq("archie".to_string())
+        .delete_returning(pool)
+        .await
+        .expect("Could not delete");
+
+    assert_eq!(deleted_users.len(), 2);
+    assert!(deleted_users
+        .iter()
+        .all(|user| matches!(user.status, UserStatus::Unverified)));
+
+    Ok(())
+}